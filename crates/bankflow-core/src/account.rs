@@ -0,0 +1,118 @@
+//! Account identifiers as join keys
+//!
+//! The matcher joins statement rows to IP log rows on the account. The two
+//! files rarely write it the same way: Excel stores an account typed as a
+//! number without its leading zeros, banks add dashes or spaces
+//! ("012-345-678901"), and some exports prefix the branch code. An
+//! `AccountNormalizer` reduces both sides to one key; the records keep the
+//! account as written for display.
+
+use serde::{Deserialize, Serialize};
+
+/// Rules turning an account as written into its join key. The default only
+/// trims surrounding whitespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountNormalizer {
+    /// Keep only the digits ("012-345 678901" becomes "012345678901");
+    /// full-width digits count as digits
+    pub digits_only: bool,
+    /// Left-pad shorter all-digit keys with zeros to this many characters,
+    /// restoring zeros lost to a numeric cell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pad_width: Option<usize>,
+    /// Branch codes removed from the start of the key. With `pad_width` set,
+    /// a prefix is only removed from keys longer than that width.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub strip_prefixes: Vec<String>,
+}
+
+impl AccountNormalizer {
+    /// Digits only, zero-padded to `width`
+    pub fn digits(width: usize) -> Self {
+        Self {
+            digits_only: true,
+            pad_width: Some(width),
+            strip_prefixes: Vec::new(),
+        }
+    }
+
+    /// Join key of an account
+    pub fn key(&self, account: &str) -> String {
+        let trimmed = account.trim();
+        let mut key = if self.digits_only {
+            let digits: String = trimmed.chars().filter_map(ascii_digit).collect();
+            // An account with no digits at all ("N/A") is kept as written
+            if digits.is_empty() {
+                trimmed.to_string()
+            } else {
+                digits
+            }
+        } else {
+            trimmed.to_string()
+        };
+
+        let min_rest = self.pad_width.unwrap_or(1);
+        let prefix = self.strip_prefixes.iter().find(|p| {
+            !p.is_empty() && key.starts_with(p.as_str()) && key.chars().count() - p.chars().count() >= min_rest
+        });
+        if let Some(prefix) = prefix {
+            key.replace_range(..prefix.len(), "");
+        }
+
+        if let Some(width) = self.pad_width {
+            let len = key.chars().count();
+            if len < width && key.bytes().all(|b| b.is_ascii_digit()) {
+                key.insert_str(0, &"0".repeat(width - len));
+            }
+        }
+        key
+    }
+}
+
+/// ASCII digit for `0`-`9` and full-width `０`-`９`
+fn ascii_digit(c: char) -> Option<char> {
+    match c {
+        '0'..='9' => Some(c),
+        '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_only_trims() {
+        let normalizer = AccountNormalizer::default();
+        assert_eq!(normalizer.key(" 012-345 "), "012-345");
+    }
+
+    #[test]
+    fn test_digits_and_padding() {
+        let normalizer = AccountNormalizer::digits(12);
+        assert_eq!(normalizer.key("012-345-678901"), "012345678901");
+        assert_eq!(normalizer.key("12345678901"), "012345678901");
+        assert_eq!(normalizer.key("０１２ ３４５ ６７８９０１"), "012345678901");
+        assert_eq!(normalizer.key("N/A"), "N/A");
+    }
+
+    #[test]
+    fn test_branch_prefix_stripping() {
+        let normalizer = AccountNormalizer {
+            strip_prefixes: vec!["700".to_string()],
+            ..AccountNormalizer::digits(12)
+        };
+        assert_eq!(normalizer.key("700-012345678901"), "012345678901");
+        // Already at full width: "700" is part of the account itself
+        assert_eq!(normalizer.key("700345678901"), "700345678901");
+
+        let unpadded = AccountNormalizer {
+            strip_prefixes: vec!["BR01-".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(unpadded.key("BR01-12345"), "12345");
+        assert_eq!(unpadded.key("BR01-"), "BR01-");
+    }
+}
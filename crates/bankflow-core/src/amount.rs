@@ -0,0 +1,182 @@
+//! Monetary amount parsing
+//!
+//! Statement amounts arrive as text in many shapes: "1,234,567", "NT$5,000",
+//! accounting negatives like "(3,000)", full-width digits, or a dash for
+//! "no amount". Text is cleaned up here before being read as a number.
+//! Single-amount layouts also need the 借/貸 (debit/credit) indicator read.
+
+use crate::error::CoreError;
+use crate::timestamp::to_half_width;
+
+/// Currency markers stripped before parsing (matched case-insensitively)
+const CURRENCY_MARKERS: [&str; 12] = [
+    "nt$", "ntd", "twd", "us$", "usd", "rmb", "cny", "$", "¥", "€", "£", "元",
+];
+
+/// Cell values that mean "no amount"
+const PLACEHOLDERS: [&str; 7] = ["-", "--", "—", "–", "n/a", "na", "無"];
+
+/// Parse an amount cell's text.
+///
+/// Returns `Ok(None)` for blanks and placeholder dashes, and an error when
+/// the text holds something that is not a readable amount.
+pub fn parse_amount(s: &str) -> Result<Option<f64>, CoreError> {
+    let mut text: String = s
+        .chars()
+        .map(|c| match to_half_width(c) {
+            '\u{2212}' => '-',
+            c => c,
+        })
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+
+    if text.is_empty() || PLACEHOLDERS.contains(&text.as_str()) {
+        return Ok(None);
+    }
+
+    let mut negative = false;
+    if text.starts_with('(') && text.ends_with(')') {
+        negative = true;
+        text = text[1..text.len() - 1].to_string();
+    }
+    for marker in CURRENCY_MARKERS {
+        text = text.replace(marker, "");
+    }
+    if let Some(rest) = text.strip_suffix('-') {
+        // Trailing minus, as printed by some accounting systems
+        negative = !negative;
+        text = rest.to_string();
+    } else if let Some(rest) = text.strip_prefix('-') {
+        negative = !negative;
+        text = rest.to_string();
+    } else if let Some(rest) = text.strip_prefix('+') {
+        text = rest.to_string();
+    }
+
+    let invalid = || CoreError::InvalidData(format!("Not an amount: {}", s.trim()));
+    let value: f64 = normalize_separators(&text).parse().map_err(|_| invalid())?;
+    if !value.is_finite() {
+        return Err(invalid());
+    }
+    Ok(Some(if negative { -value } else { value }))
+}
+
+/// Direction of a transaction as given by a 借/貸 indicator column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 借 — money going out (expense)
+    Debit,
+    /// 貸 — money coming in (income)
+    Credit,
+}
+
+const DEBIT_MARKERS: [&str; 9] = ["借", "支", "支出", "提", "提款", "debit", "dr", "d", "-"];
+const CREDIT_MARKERS: [&str; 9] = ["貸", "存", "存入", "收", "收入", "credit", "cr", "c", "+"];
+
+/// Read a 借/貸 indicator cell; `None` when the text is not a known marker
+pub fn parse_direction(s: &str) -> Option<Direction> {
+    let text: String = s.chars().map(to_half_width).collect::<String>().trim().to_lowercase();
+    if DEBIT_MARKERS.contains(&text.as_str()) {
+        Some(Direction::Debit)
+    } else if CREDIT_MARKERS.contains(&text.as_str()) {
+        Some(Direction::Credit)
+    } else {
+        None
+    }
+}
+
+/// Split a signed amount into (expense, income): negative values are expense
+pub fn split_signed(value: Option<f64>) -> (Option<f64>, Option<f64>) {
+    match value {
+        Some(v) if v < 0.0 => (Some(-v), None),
+        Some(v) => (None, Some(v)),
+        None => (None, None),
+    }
+}
+
+/// Rewrite digit grouping so the number uses `.` as its only decimal mark.
+///
+/// When both `,` and `.` appear, the later one is the decimal mark
+/// ("1.234,56" or "1,234.56"). A lone `,` is a thousands separator if it is
+/// repeated or followed by exactly three digits, and a decimal mark otherwise;
+/// a repeated `.` ("1.234.567") is always grouping.
+fn normalize_separators(text: &str) -> String {
+    let last_comma = text.rfind(',');
+    let last_dot = text.rfind('.');
+
+    let decimal = match (last_comma, last_dot) {
+        (Some(c), Some(d)) => Some(if c > d { ',' } else { '.' }),
+        (Some(c), None) => {
+            let repeated = text.matches(',').count() > 1;
+            let grouped = text.len() - c - 1 == 3;
+            if repeated || grouped {
+                None
+            } else {
+                Some(',')
+            }
+        }
+        (None, Some(_)) if text.matches('.').count() > 1 => None,
+        (None, _) => Some('.'),
+    };
+
+    text.chars()
+        .filter_map(|c| match c {
+            ',' | '.' if Some(c) == decimal => Some('.'),
+            ',' | '.' | '\'' | '_' => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_and_grouped_numbers() {
+        assert_eq!(parse_amount("500").unwrap(), Some(500.0));
+        assert_eq!(parse_amount("1,234,567").unwrap(), Some(1234567.0));
+        assert_eq!(parse_amount("1,234.50").unwrap(), Some(1234.5));
+        assert_eq!(parse_amount("1.234,50").unwrap(), Some(1234.5));
+        assert_eq!(parse_amount("12,5").unwrap(), Some(12.5));
+        assert_eq!(parse_amount("1.234.567").unwrap(), Some(1234567.0));
+    }
+
+    #[test]
+    fn test_currency_and_negatives() {
+        assert_eq!(parse_amount("NT$5,000").unwrap(), Some(5000.0));
+        assert_eq!(parse_amount("TWD 1,200").unwrap(), Some(1200.0));
+        assert_eq!(parse_amount("300元").unwrap(), Some(300.0));
+        assert_eq!(parse_amount("(3,000)").unwrap(), Some(-3000.0));
+        assert_eq!(parse_amount("3,000-").unwrap(), Some(-3000.0));
+        assert_eq!(parse_amount("-NT$20").unwrap(), Some(-20.0));
+        assert_eq!(parse_amount("+15").unwrap(), Some(15.0));
+    }
+
+    #[test]
+    fn test_full_width_and_placeholders() {
+        assert_eq!(parse_amount("５０００").unwrap(), Some(5000.0));
+        assert_eq!(parse_amount("１，２００．５").unwrap(), Some(1200.5));
+        assert_eq!(parse_amount("-").unwrap(), None);
+        assert_eq!(parse_amount("－").unwrap(), None);
+        assert_eq!(parse_amount("  ").unwrap(), None);
+    }
+
+    #[test]
+    fn test_direction_and_sign() {
+        assert_eq!(parse_direction("借"), Some(Direction::Debit));
+        assert_eq!(parse_direction(" CR "), Some(Direction::Credit));
+        assert_eq!(parse_direction("存入"), Some(Direction::Credit));
+        assert_eq!(parse_direction("轉帳"), None);
+        assert_eq!(split_signed(Some(-300.0)), (Some(300.0), None));
+        assert_eq!(split_signed(Some(200.0)), (None, Some(200.0)));
+    }
+
+    #[test]
+    fn test_unreadable_text() {
+        assert!(parse_amount("轉帳").is_err());
+        assert!(parse_amount("12abc").is_err());
+        assert!(parse_amount("inf").is_err());
+    }
+}
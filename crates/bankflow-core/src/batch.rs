@@ -3,6 +3,9 @@ use serde::{Serialize, Deserialize};
 use walkdir::WalkDir;
 use std::collections::HashMap;
 
+/// File extensions picked up when scanning for A/B pairs
+const SUPPORTED_EXTENSIONS: &[&str] = &["xlsx", "xls", "csv", "tsv", "json", "ndjson", "jsonl", "htm", "html", "xml", "pdf"];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchPair {
     pub folder_name: String,
@@ -36,7 +39,8 @@ impl BatchProcessor {
             if entry.file_type().is_file() {
                 let path = entry.path().to_path_buf();
                 if let Some(ext) = path.extension() {
-                    if ext.to_string_lossy().eq_ignore_ascii_case("xlsx") {
+                    let ext = ext.to_string_lossy();
+                    if SUPPORTED_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
                         if let Some(parent) = path.parent() {
                             folder_map.entry(parent.to_path_buf())
                                 .or_default()
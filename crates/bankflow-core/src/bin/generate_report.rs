@@ -1,6 +1,6 @@
 use bankflow_core::exporter::Exporter;
 use bankflow_core::matcher::IpMatcher;
-use bankflow_core::parser::Parser;
+use bankflow_core::parser::{ParseOptions, Parser};
 use bankflow_core::processor::Processor;
 use std::fs;
 use std::path::PathBuf;
@@ -15,7 +15,9 @@ fn main() {
 
 fn run() -> Result<(), String> {
     let args: Vec<String> = std::env::args().collect();
-    let (file_a_path, file_b_path, out_path, enable_whois, hide_sensitive) = parse_args(&args)?;
+    let Args { file_a_path, file_b_path, out_path, enable_whois, hide_sensitive, password_a, password_b } =
+        parse_args(&args)?;
+    let options = |password: Option<String>| ParseOptions { password, ..Default::default() };
 
     println!("\n=== BankFlow Report Generator Audit ===");
     println!("File A Input:    {:?}", file_a_path);
@@ -34,15 +36,17 @@ fn run() -> Result<(), String> {
     print!("> Parsing File A... ");
     let bytes_a = fs::read(&file_a_path)
         .map_err(|e| format!("Failed to read File A: {}", e))?;
-    let (mut transactions, _meta_a) = Parser::parse_transactions_from_bytes(&bytes_a, "FileA.xlsx")
+    let (mut transactions, meta_a) = Parser::parse_transactions_from_bytes(&bytes_a, "FileA.xlsx", None, Some(&options(password_a)))
         .map_err(|e| e.to_string())?;
     println!("OK ({} tx)", transactions.len());
 
     // 2. Parse File B
+    let mut raw_headers = meta_a.headers;
+
     print!("> Parsing File B... ");
     let bytes_b = fs::read(&file_b_path)
         .map_err(|e| format!("Failed to read File B: {}", e))?;
-    let (ip_records, _meta_b) = Parser::parse_ip_records_from_bytes(&bytes_b, "FileB.xlsx")
+    let (ip_records, _meta_b) = Parser::parse_ip_records_from_bytes(&bytes_b, "FileB.xlsx", None, Some(&options(password_b)))
         .map_err(|e| e.to_string())?;
     println!("OK ({} rec)", ip_records.len());
 
@@ -88,7 +92,7 @@ fn run() -> Result<(), String> {
     if hide_sensitive {
         print!("> Masking Columns...  ");
         let original_cols = transactions[0].raw_columns.len();
-        Processor::new(true).process(&mut transactions);
+        Processor::new(true).process_with_headers(&mut transactions, &mut raw_headers);
         let new_cols = transactions[0].raw_columns.len();
         println!("OK (Cols: {} -> {})", original_cols, new_cols);
         if new_cols >= original_cols {
@@ -105,7 +109,7 @@ fn run() -> Result<(), String> {
 
     // 7. Export
     print!("> Exporting XLSX...   ");
-    let xlsx_bytes = Exporter::export_to_bytes(&transactions, &income, &expense)
+    let xlsx_bytes = Exporter::export_to_bytes(&transactions, &income, &expense, &raw_headers)
         .map_err(|e| e.to_string())?;
 
     fs::write(&out_path, xlsx_bytes)
@@ -117,12 +121,25 @@ fn run() -> Result<(), String> {
     Ok(())
 }
 
-fn parse_args(args: &[String]) -> Result<(PathBuf, PathBuf, PathBuf, bool, bool), String> {
+struct Args {
+    file_a_path: PathBuf,
+    file_b_path: PathBuf,
+    out_path: PathBuf,
+    enable_whois: bool,
+    hide_sensitive: bool,
+    /// Passwords of encrypted input workbooks
+    password_a: Option<String>,
+    password_b: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
     let mut file_a: Option<PathBuf> = None;
     let mut file_b: Option<PathBuf> = None;
     let mut out: Option<PathBuf> = None;
     let mut whois = false;
     let mut hide_sensitive = false;
+    let mut password_a: Option<String> = None;
+    let mut password_b: Option<String> = None;
 
     let mut i = 1;
     while i < args.len() {
@@ -139,6 +156,14 @@ fn parse_args(args: &[String]) -> Result<(PathBuf, PathBuf, PathBuf, bool, bool)
                 i += 1;
                 if i < args.len() { out = Some(PathBuf::from(&args[i])); }
             }
+            "--password-a" => {
+                i += 1;
+                if i < args.len() { password_a = Some(args[i].clone()); }
+            }
+            "--password-b" => {
+                i += 1;
+                if i < args.len() { password_b = Some(args[i].clone()); }
+            }
             "--whois" => {
                 whois = true;
             }
@@ -151,7 +176,17 @@ fn parse_args(args: &[String]) -> Result<(PathBuf, PathBuf, PathBuf, bool, bool)
     }
 
     match (file_a, file_b, out) {
-        (Some(a), Some(b), Some(o)) => Ok((a, b, o, whois, hide_sensitive)),
-        _ => Err("Usage: generate_report --file-a <path> --file-b <path> --out <path> [--whois] [--hide-sensitive]".to_string()),
+        (Some(a), Some(b), Some(o)) => Ok(Args {
+            file_a_path: a,
+            file_b_path: b,
+            out_path: o,
+            enable_whois: whois,
+            hide_sensitive,
+            password_a,
+            password_b,
+        }),
+        _ => Err("Usage: generate_report --file-a <path> --file-b <path> --out <path> \
+                  [--password-a <password>] [--password-b <password>] [--whois] [--hide-sensitive]"
+            .to_string()),
     }
 }
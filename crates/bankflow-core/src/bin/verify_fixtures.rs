@@ -20,9 +20,9 @@ fn run() -> Result<(), String> {
         fs::read(&file_b).map_err(|e| format!("Failed to read File B ({file_b:?}): {e}"))?;
 
     let (mut transactions, _meta_a) =
-        Parser::parse_transactions_from_bytes(&bytes_a, "FileA.xlsx").map_err(|e| e.to_string())?;
+        Parser::parse_transactions_from_bytes(&bytes_a, "FileA.xlsx", None, None).map_err(|e| e.to_string())?;
     let (ip_records, _meta_b) =
-        Parser::parse_ip_records_from_bytes(&bytes_b, "FileB.xlsx").map_err(|e| e.to_string())?;
+        Parser::parse_ip_records_from_bytes(&bytes_b, "FileB.xlsx", None, None).map_err(|e| e.to_string())?;
 
     if transactions.is_empty() {
         return Err("File A parsed to 0 transactions".to_string());
@@ -40,20 +40,13 @@ fn run() -> Result<(), String> {
         ));
     }
 
-    // Ensure counterparty column (canonical index 11) exists and is non-empty for at least one row.
+    // Ensure the counterparty column (對方帳號) is resolved and non-empty for at least one row.
     let counterparty_nonempty = transactions
         .iter()
-        .filter(|tx| {
-            tx.raw_columns
-                .get(11)
-                .map(|s| !s.trim().is_empty())
-                .unwrap_or(false)
-        })
+        .filter(|tx| tx.counterparty_account.is_some())
         .count();
     if counterparty_nonempty == 0 {
-        return Err(
-            "No non-empty counterparty accounts found in canonical column L (index 11)".to_string(),
-        );
+        return Err("No non-empty counterparty accounts found (對方帳號 column)".to_string());
     }
 
     // Ensure IP matching produces both matched and multi-IP cases.
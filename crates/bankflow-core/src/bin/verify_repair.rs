@@ -17,7 +17,8 @@ fn main() {
         Ok(_) => println!("❌ Test 1 Failed: Should have rejected 'CustomTime'"),
         Err(e) => {
             println!("✅ Test 1 Passed: Correctly flagged missing columns: {:?}", e);
-            assert!(e.contains(&"交易時間/timestamp".to_string()));
+            let timestamp = e.iter().find(|m| m.key == "timestamp").expect("timestamp flagged");
+            assert_eq!(timestamp.suggestions.first().map(String::as_str), Some("CustomTime"));
         }
     }
 
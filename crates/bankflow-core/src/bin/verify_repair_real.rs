@@ -13,7 +13,7 @@ fn main() {
 
     // 1. Attempt Default Parse (Should Fail)
     println!("\n--- Test 1: Default Parse (Expect Failure) ---");
-    match Parser::parse_transactions(path, None) {
+    match Parser::parse_transactions(path, None, None) {
         Ok(_) => println!("❌ Test 1 Failed: Should have rejected the broken file."),
         Err(e) => {
             println!("✅ Test 1 Passed: Rejected file as expected.");
@@ -27,7 +27,7 @@ fn main() {
     mapping.insert("timestamp".to_string(), "CustomTime".to_string());
     mapping.insert("account".to_string(), "UserID".to_string());
 
-    match Parser::parse_transactions(path, Some(&mapping)) {
+    match Parser::parse_transactions(path, Some(&mapping), None) {
         Ok(txs) => {
             println!("✅ Test 2 Passed: Successfully parsed file with mapping.");
             println!("   - Record Count: {}", txs.len());
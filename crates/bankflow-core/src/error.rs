@@ -1,5 +1,6 @@
 //! Error types for the core library
 
+use crate::models::MissingColumn;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,6 +16,30 @@ pub enum CoreError {
 
     #[error("IO error: {0}")]
     IoError(String),
+
+    #[error("The file is password-protected; a password is required to open it")]
+    PasswordRequired,
+
+    #[error("Wrong password for the encrypted file")]
+    WrongPassword,
+
+    #[error("Missing required columns: {}", describe_missing(.0))]
+    MissingColumns(Vec<MissingColumn>),
+}
+
+/// "交易時間/timestamp (did you mean: 交易日時?), 帳號/account"
+fn describe_missing(missing: &[MissingColumn]) -> String {
+    missing
+        .iter()
+        .map(|m| {
+            if m.suggestions.is_empty() {
+                m.label.clone()
+            } else {
+                format!("{} (did you mean: {}?)", m.label, m.suggestions.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl From<std::io::Error> for CoreError {
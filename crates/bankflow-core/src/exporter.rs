@@ -3,7 +3,7 @@
 //! Outputs to bytes for WASM compatibility (can also save to file on native).
 
 use crate::error::CoreError;
-use crate::models::Transaction;
+use crate::models::{FileMetadata, Transaction, UnparsedCell};
 use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, Worksheet};
 use std::collections::HashSet;
 
@@ -15,19 +15,59 @@ const BASE_HEADERS: &[&str] = &[
     "Matched IP",
     "IP Country",
     "IP ISP",
+    "Device",
+    "User Agent",
+    "Channel",
+    "Login Result",
+    "Session",
+    "Source",
 ];
 
-const COUNTERPARTY_RAW_INDEX: usize = 11; // canonical File A col L
+const REPORT_HEADERS: &[&str] = &["File", "Sheet", "Row", "Issue", "Column", "Value"];
 
 pub struct Exporter;
 
 impl Exporter {
-    /// Export to bytes (WASM compatible)
+    /// Export to bytes (WASM compatible).
+    ///
+    /// `raw_headers` names the raw columns after the base columns, normally
+    /// the File A headers (with any hidden columns removed); columns without
+    /// a header are labelled "Raw Column N".
     pub fn export_to_bytes(
         summary: &[Transaction],
         income: &[Transaction],
         expense: &[Transaction],
+        raw_headers: &[String],
     ) -> Result<Vec<u8>, CoreError> {
+        Exporter::export_to_bytes_with_report(summary, income, expense, raw_headers, &[])
+    }
+
+    /// Export to bytes, adding a "Parse Report" sheet that lists the declared
+    /// time zone, skipped rows, unreadable cells and IP addresses, and
+    /// merged-away duplicate rows of each loaded file (omitted when `files`
+    /// is empty)
+    pub fn export_to_bytes_with_report(
+        summary: &[Transaction],
+        income: &[Transaction],
+        expense: &[Transaction],
+        raw_headers: &[String],
+        files: &[FileMetadata],
+    ) -> Result<Vec<u8>, CoreError> {
+        Exporter::export_to_bytes_with_files(summary, income, expense, raw_headers, files, true)
+    }
+
+    /// Export to bytes for the loaded `files`. The timestamp header names the
+    /// time zone each file declared, so the export records how times were
+    /// compared; the "Parse Report" sheet is added with `include_report`.
+    pub fn export_to_bytes_with_files(
+        summary: &[Transaction],
+        income: &[Transaction],
+        expense: &[Transaction],
+        raw_headers: &[String],
+        files: &[FileMetadata],
+        include_report: bool,
+    ) -> Result<Vec<u8>, CoreError> {
+        let timestamp_header = timestamp_header(files);
         let mut workbook = Workbook::new();
 
         let header_format = Format::new()
@@ -53,7 +93,7 @@ impl Exporter {
             ws.set_name("Summary")
                 .map_err(|e| CoreError::ExportError(e.to_string()))?;
             let raw_count = max_raw_columns(summary);
-            write_headers(ws, &header_format, raw_count)?;
+            write_headers(ws, &header_format, &timestamp_header, raw_headers, raw_count)?;
             write_transactions(
                 ws,
                 summary,
@@ -71,7 +111,7 @@ impl Exporter {
             ws.set_name("Income")
                 .map_err(|e| CoreError::ExportError(e.to_string()))?;
             let raw_count = max_raw_columns(income);
-            write_headers(ws, &header_format, raw_count)?;
+            write_headers(ws, &header_format, &timestamp_header, raw_headers, raw_count)?;
             write_transactions(
                 ws,
                 income,
@@ -89,7 +129,7 @@ impl Exporter {
             ws.set_name("Expense")
                 .map_err(|e| CoreError::ExportError(e.to_string()))?;
             let raw_count = max_raw_columns(expense);
-            write_headers(ws, &header_format, raw_count)?;
+            write_headers(ws, &header_format, &timestamp_header, raw_headers, raw_count)?;
             write_transactions(
                 ws,
                 expense,
@@ -133,6 +173,14 @@ impl Exporter {
                 .map_err(|e| CoreError::ExportError(e.to_string()))?;
         }
 
+        // Parse report sheet
+        if include_report && !files.is_empty() {
+            let ws = workbook.add_worksheet();
+            ws.set_name("Parse Report")
+                .map_err(|e| CoreError::ExportError(e.to_string()))?;
+            write_parse_report(ws, files, &header_format, &data_format)?;
+        }
+
         let buffer = workbook
             .save_to_buffer()
             .map_err(|e| CoreError::ExportError(format!("Failed to save: {}", e)))?;
@@ -141,13 +189,38 @@ impl Exporter {
     }
 }
 
-fn write_headers(ws: &mut Worksheet, fmt: &Format, raw_count: usize) -> Result<(), CoreError> {
-    for (col, header) in BASE_HEADERS.iter().enumerate() {
+/// "Timestamp", or with declared zones
+/// "Timestamp (as written; a.csv Asia/Taipei, b.csv UTC; compared in UTC)"
+fn timestamp_header(files: &[FileMetadata]) -> String {
+    let zones: Vec<String> = files
+        .iter()
+        .filter_map(|f| f.timezone.as_ref().map(|zone| format!("{} {}", f.filename, zone)))
+        .collect();
+    if zones.is_empty() {
+        return BASE_HEADERS[0].to_string();
+    }
+    let others = if files.iter().any(|f| f.timezone.is_none()) { ", others undeclared" } else { "" };
+    format!("{} (as written; {}{}; compared in UTC)", BASE_HEADERS[0], zones.join(", "), others)
+}
+
+fn write_headers(
+    ws: &mut Worksheet,
+    fmt: &Format,
+    timestamp_header: &str,
+    raw_headers: &[String],
+    raw_count: usize,
+) -> Result<(), CoreError> {
+    ws.write_string_with_format(0, 0, timestamp_header, fmt)
+        .map_err(|e| CoreError::ExportError(e.to_string()))?;
+    for (col, header) in BASE_HEADERS.iter().enumerate().skip(1) {
         ws.write_string_with_format(0, col as u16, *header, fmt)
             .map_err(|e| CoreError::ExportError(e.to_string()))?;
     }
     for idx in 0..raw_count {
-        let header = format!("Raw Column {}", idx + 1);
+        let header = match raw_headers.get(idx).map(|h| h.trim()) {
+            Some(h) if !h.is_empty() => h.to_string(),
+            _ => format!("Raw Column {}", idx + 1),
+        };
         ws.write_string_with_format(0, (BASE_HEADERS.len() + idx) as u16, &header, fmt)
             .map_err(|e| CoreError::ExportError(e.to_string()))?;
     }
@@ -192,6 +265,13 @@ fn write_transactions(
             .map_err(|e| CoreError::ExportError(e.to_string()))?;
         ws.write_string_with_format(row, 6, tx.ip_isp.as_deref().unwrap_or(""), data_fmt)
             .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        // Login columns of the matched File B rows
+        for (offset, value) in tx.matched_login.fields().into_iter().enumerate() {
+            ws.write_string_with_format(row, 7 + offset as u16, value.as_deref().unwrap_or(""), data_fmt)
+                .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        }
+        ws.write_string_with_format(row, 12, tx.source().to_string(), data_fmt)
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
 
         for (idx, value) in tx.raw_columns.iter().enumerate() {
             ws.write_string_with_format(row, (BASE_HEADERS.len() + idx) as u16, value, data_fmt)
@@ -202,7 +282,7 @@ fn write_transactions(
 }
 
 fn set_column_widths(ws: &mut Worksheet, raw_count: usize) -> Result<(), CoreError> {
-    let widths = [20, 15, 12, 12, 40, 10, 20];
+    let widths = [20, 15, 12, 12, 40, 10, 20, 20, 30, 10, 12, 20, 28];
     for (col, width) in widths.iter().enumerate() {
         ws.set_column_width(col as u16, *width)
             .map_err(|e| CoreError::ExportError(e.to_string()))?;
@@ -214,6 +294,78 @@ fn set_column_widths(ws: &mut Worksheet, raw_count: usize) -> Result<(), CoreErr
     Ok(())
 }
 
+fn write_parse_report(
+    ws: &mut Worksheet,
+    files: &[FileMetadata],
+    header_fmt: &Format,
+    data_fmt: &Format,
+) -> Result<(), CoreError> {
+    for (col, header) in REPORT_HEADERS.iter().enumerate() {
+        ws.write_string_with_format(0, col as u16, *header, header_fmt)
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
+    }
+
+    let mut lines: Vec<[String; 6]> = Vec::new();
+    for file in files {
+        let report = &file.report;
+        let line = |sheet: &Option<String>, row: usize, issue: &str, column: &str, value: &str| {
+            [
+                file.filename.clone(),
+                sheet.clone().unwrap_or_default(),
+                row.to_string(),
+                issue.to_string(),
+                column.to_string(),
+                value.to_string(),
+            ]
+        };
+        let cell_lines = |cells: &[UnparsedCell], issue: &str| -> Vec<[String; 6]> {
+            cells
+                .iter()
+                .map(|c| line(&c.sheet, c.row, issue, &c.column, &c.value))
+                .collect()
+        };
+
+        if let Some(zone) = &file.timezone {
+            let note = "Timestamps normalized to UTC".to_string();
+            lines.push([file.filename.clone(), String::new(), String::new(), "Time zone".to_string(), note, zone.clone()]);
+        }
+        if report.is_clean() {
+            let summary = format!("No issues ({} rows read)", report.rows_read);
+            lines.push([file.filename.clone(), String::new(), String::new(), summary, String::new(), String::new()]);
+            continue;
+        }
+        for skipped in &report.skipped_rows {
+            lines.push(line(&skipped.sheet, skipped.row, skipped.reason.describe(), "", ""));
+        }
+        lines.extend(cell_lines(&report.unparsed_timestamps, "Unparsed timestamp"));
+        lines.extend(cell_lines(&report.unparsed_amounts, "Unparsed amount"));
+        lines.extend(cell_lines(&report.unknown_directions, "Unknown debit/credit marker"));
+        lines.extend(cell_lines(&report.error_cells, "Error cell"));
+        lines.extend(cell_lines(&report.invalid_ips, "Invalid IP address"));
+        for duplicate in &report.duplicate_rows {
+            let original = format!("Same as {}", duplicate.original);
+            lines.push(line(&duplicate.sheet, duplicate.row, "Duplicate row", "", &original));
+        }
+        for uncertain in &report.uncertain_rows {
+            lines.push(line(&uncertain.sheet, uncertain.row, uncertain.reason.describe(), "", &uncertain.text));
+        }
+    }
+
+    for (row_idx, line) in lines.iter().enumerate() {
+        for (col, value) in line.iter().enumerate() {
+            ws.write_string_with_format(row_idx as u32 + 1, col as u16, value, data_fmt)
+                .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        }
+    }
+
+    let widths = [24, 16, 8, 22, 20, 28];
+    for (col, width) in widths.iter().enumerate() {
+        ws.set_column_width(col as u16, *width)
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
+    }
+    Ok(())
+}
+
 fn max_raw_columns(transactions: &[Transaction]) -> usize {
     transactions
         .iter()
@@ -225,7 +377,7 @@ fn max_raw_columns(transactions: &[Transaction]) -> usize {
 fn unique_counterparty_accounts(transactions: &[Transaction]) -> Vec<String> {
     let mut set: HashSet<String> = HashSet::new();
     for tx in transactions {
-        if let Some(value) = tx.raw_columns.get(COUNTERPARTY_RAW_INDEX) {
+        if let Some(value) = &tx.counterparty_account {
             let v = value.trim();
             if !v.is_empty() {
                 set.insert(v.to_string());
@@ -251,8 +403,35 @@ mod native {
             summary: &[Transaction],
             income: &[Transaction],
             expense: &[Transaction],
+            raw_headers: &[String],
+        ) -> Result<(), CoreError> {
+            Exporter::export_to_excel_with_report(path, summary, income, expense, raw_headers, &[])
+        }
+
+        /// Export to Excel file with a "Parse Report" sheet (native only)
+        pub fn export_to_excel_with_report(
+            path: &Path,
+            summary: &[Transaction],
+            income: &[Transaction],
+            expense: &[Transaction],
+            raw_headers: &[String],
+            files: &[FileMetadata],
+        ) -> Result<(), CoreError> {
+            Exporter::export_to_excel_with_files(path, summary, income, expense, raw_headers, files, true)
+        }
+
+        /// Export to Excel file, as `export_to_bytes_with_files` (native only)
+        pub fn export_to_excel_with_files(
+            path: &Path,
+            summary: &[Transaction],
+            income: &[Transaction],
+            expense: &[Transaction],
+            raw_headers: &[String],
+            files: &[FileMetadata],
+            include_report: bool,
         ) -> Result<(), CoreError> {
-            let bytes = Exporter::export_to_bytes(summary, income, expense)?;
+            let bytes =
+                Exporter::export_to_bytes_with_files(summary, income, expense, raw_headers, files, include_report)?;
             fs::write(path, bytes)
                 .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
             Ok(())
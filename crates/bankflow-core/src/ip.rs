@@ -0,0 +1,128 @@
+//! IP address cells of login logs
+//!
+//! Platforms write addresses in many shapes: with a port ("1.2.3.4:443",
+//! "[2001:db8::1]:443"), with an IPv6 zone ("fe80::1%eth0"), as
+//! IPv4-mapped IPv6 ("::ffff:1.2.3.4"), in upper case, or several to a
+//! cell. Each address is parsed into `IpAddr` and written back in one
+//! canonical form, so the same address always compares equal.
+
+use std::net::IpAddr;
+
+/// Result of reading one IP cell
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IpCell {
+    /// Addresses found, in cell order, without repeats
+    pub addresses: Vec<IpAddr>,
+    /// Parts of the cell that are not an IP address
+    pub invalid: Vec<String>,
+}
+
+/// Split a cell on commas, semicolons, pipes and whitespace and parse each part
+pub fn parse_ip_cell(text: &str) -> IpCell {
+    let mut cell = IpCell::default();
+    let parts = text
+        .split(|c: char| matches!(c, ',' | ';' | '|') || c.is_whitespace())
+        .filter(|p| !p.is_empty());
+    for part in parts {
+        match parse_ip(part) {
+            Some(ip) if !cell.addresses.contains(&ip) => cell.addresses.push(ip),
+            Some(_) => {}
+            None => cell.invalid.push(part.to_string()),
+        }
+    }
+    cell
+}
+
+/// Parse one address, dropping any port, brackets and IPv6 zone. IPv4-mapped
+/// IPv6 addresses become plain IPv4.
+pub fn parse_ip(text: &str) -> Option<IpAddr> {
+    let text = text.trim();
+    let host = if let Some(rest) = text.strip_prefix('[') {
+        // "[v6]" or "[v6]:port"
+        let (host, tail) = rest.split_once(']')?;
+        if !(tail.is_empty() || is_port_suffix(tail)) {
+            return None;
+        }
+        host
+    } else {
+        match text.split_once(':') {
+            // A single colon can only be IPv4 with a port
+            Some((host, port)) if !port.contains(':') => {
+                if !is_port_suffix(&text[host.len()..]) {
+                    return None;
+                }
+                host
+            }
+            _ => text,
+        }
+    };
+    let host = host.split_once('%').map_or(host, |(addr, _zone)| addr);
+    let ip: IpAddr = host.parse().ok()?;
+    Some(ip.to_canonical())
+}
+
+/// `:` followed by a port number
+fn is_port_suffix(text: &str) -> bool {
+    text.strip_prefix(':')
+        .is_some_and(|port| !port.is_empty() && port.parse::<u16>().is_ok())
+}
+
+/// True for loopback, private, link-local, unique-local and unspecified
+/// addresses, which a whois lookup cannot resolve
+pub fn is_non_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified(),
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback() || v6.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(text: &str) -> Option<String> {
+        parse_ip(text).map(|ip| ip.to_string())
+    }
+
+    #[test]
+    fn test_parse_ip_forms() {
+        assert_eq!(canonical(" 1.2.3.4 "), Some("1.2.3.4".to_string()));
+        assert_eq!(canonical("1.2.3.4:443"), Some("1.2.3.4".to_string()));
+        assert_eq!(canonical("[2001:DB8:0:0::1]:8443"), Some("2001:db8::1".to_string()));
+        assert_eq!(canonical("[2001:db8::1]"), Some("2001:db8::1".to_string()));
+        assert_eq!(canonical("fe80::1%eth0"), Some("fe80::1".to_string()));
+        assert_eq!(canonical("::ffff:1.2.3.4"), Some("1.2.3.4".to_string()));
+        assert_eq!(canonical("2001:db8::1"), Some("2001:db8::1".to_string()));
+    }
+
+    #[test]
+    fn test_parse_ip_rejects_garbage() {
+        assert_eq!(canonical("1.2.3"), None);
+        assert_eq!(canonical("256.1.1.1"), None);
+        assert_eq!(canonical("1.2.3.4:99999"), None);
+        assert_eq!(canonical("[::1]x"), None);
+        assert_eq!(canonical("unknown"), None);
+    }
+
+    #[test]
+    fn test_parse_ip_cell_splits_and_flags() {
+        let cell = parse_ip_cell("1.2.3.4, 1.2.3.4:80; 2001:db8::1 | n/a");
+        let addresses: Vec<String> = cell.addresses.iter().map(|ip| ip.to_string()).collect();
+        assert_eq!(addresses, ["1.2.3.4", "2001:db8::1"]);
+        assert_eq!(cell.invalid, ["n/a"]);
+    }
+
+    #[test]
+    fn test_is_non_public() {
+        let check = |s: &str| is_non_public(&parse_ip(s).unwrap());
+        assert!(check("10.0.0.1"));
+        assert!(check("::1"));
+        assert!(check("fd00::1"));
+        assert!(check("fe80::1"));
+        assert!(!check("8.8.8.8"));
+        assert!(!check("2001:4860:4860::8888"));
+    }
+}
@@ -3,23 +3,34 @@
 //! This crate provides the core business logic that can be compiled
 //! for both native (Tauri desktop) and WebAssembly targets.
 
+pub mod account;
+pub mod amount;
 pub mod error;
 pub mod exporter;
+pub mod ip;
 pub mod matcher;
+pub mod merge;
 pub mod models;
 pub mod parser;
 pub mod batch;
 pub mod processor;
+pub mod profile;
+pub mod timestamp;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+pub use account::AccountNormalizer;
 pub use error::CoreError;
 pub use exporter::Exporter;
-pub use matcher::{IpMatcher, MatchStats, TimeWindow};
+pub use matcher::{matched_ips, IpMatcher, MatchStats, TimeWindow};
+pub use merge::{Dataset, SourcedRecord};
 pub use models::{
-    excel_date_to_datetime, AnalysisResult, AnalysisSettings, FileMetadata, IpRecord, Transaction,
-    WhoisResult,
+    excel_date_to_datetime, AnalysisResult, AnalysisSettings, DuplicateRow, EncodingInfo,
+    EncodingSource, FileMetadata, IpRecord, LoginDetails, MissingColumn, ParseProgress,
+    ParseReport, RowSource, SheetHeaders, SkipReason, SkippedRow, Transaction, UncertainReason,
+    UncertainRow, UnparsedCell, WhoisResult,
 };
-pub use parser::Parser;
+pub use parser::{ParseOptions, Parser, SheetSelector};
 pub use processor::{ProcessingStats, Processor};
+pub use profile::{AmountMode, FixedWidthField, FixedWidthLayout, FormatProfile, ProfileFormat, ProfileKind, RecordTypeFilter};
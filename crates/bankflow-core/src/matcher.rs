@@ -3,7 +3,8 @@
 //! Uses NaiveDateTime for WASM compatibility.
 //! Parallel processing via rayon is only available on native targets.
 
-use crate::models::{IpRecord, Transaction};
+use crate::account::AccountNormalizer;
+use crate::models::{IpRecord, LoginDetails, Transaction};
 use chrono::NaiveDateTime;
 use std::collections::HashMap;
 
@@ -24,8 +25,11 @@ impl Default for TimeWindow {
 #[derive(Debug, Clone)]
 pub struct IpMatch {
     pub ip: String,
-    pub offset_seconds: i64,
+    /// IP event time minus transaction time, in milliseconds
+    pub offset_millis: i64,
     pub row_index: usize,
+    /// Device, channel and session columns of the login
+    pub login: LoginDetails,
 }
 
 /// Reference to an IP record for the index
@@ -34,27 +38,37 @@ struct IpRecordRef {
     datetime: NaiveDateTime,
     ip_address: String,
     row_index: usize,
+    login: LoginDetails,
 }
 
 /// IP Matcher engine
 pub struct IpMatcher {
     window: TimeWindow,
+    accounts: AccountNormalizer,
+    /// IP records by account join key
     account_index: HashMap<String, Vec<IpRecordRef>>,
 }
 
 impl IpMatcher {
     pub fn new(ip_records: &[IpRecord], window: TimeWindow) -> Self {
+        Self::with_normalizer(ip_records, window, AccountNormalizer::default())
+    }
+
+    /// Matcher joining on accounts reduced by `accounts`, so that differently
+    /// written accounts of the two files still meet
+    pub fn with_normalizer(ip_records: &[IpRecord], window: TimeWindow, accounts: AccountNormalizer) -> Self {
         let mut account_index: HashMap<String, Vec<IpRecordRef>> = HashMap::new();
 
         for record in ip_records {
             if let Some(dt) = record.datetime {
                 account_index
-                    .entry(record.account.clone())
+                    .entry(accounts.key(&record.account))
                     .or_default()
                     .push(IpRecordRef {
                         datetime: dt,
                         ip_address: record.ip_address.clone(),
                         row_index: record.row_index,
+                        login: record.login.clone(),
                     });
             }
         }
@@ -63,48 +77,52 @@ impl IpMatcher {
             records.sort_by_key(|r| r.datetime);
         }
 
-        Self { window, account_index }
+        Self { window, accounts, account_index }
     }
 
     pub fn with_default_window(ip_records: &[IpRecord]) -> Self {
         Self::new(ip_records, TimeWindow::default())
     }
 
-    /// Match a single transaction
-    fn match_single(&self, tx: &Transaction) -> Option<String> {
-        let tx_datetime = tx.datetime?;
-
-        let ip_records = self.account_index.get(&tx.account)?;
+    /// IP records of the transaction's account within the time window
+    fn match_single(&self, tx: &Transaction) -> Vec<IpMatch> {
+        let Some(tx_datetime) = tx.datetime else {
+            return Vec::new();
+        };
+        let Some(ip_records) = self.account_index.get(&self.accounts.key(&tx.account)) else {
+            return Vec::new();
+        };
 
-        let matches: Vec<IpMatch> = ip_records
+        ip_records
             .iter()
             .filter_map(|record| {
                 let diff = record.datetime.signed_duration_since(tx_datetime);
-                let offset_seconds = diff.num_seconds();
+                let offset_millis = diff.num_milliseconds();
 
-                if offset_seconds >= -(self.window.before) && offset_seconds <= self.window.after {
+                if offset_millis >= -(self.window.before * 1000) && offset_millis <= self.window.after * 1000 {
                     Some(IpMatch {
                         ip: record.ip_address.clone(),
-                        offset_seconds,
+                        offset_millis,
                         row_index: record.row_index,
+                        login: record.login.clone(),
                     })
                 } else {
                     None
                 }
             })
-            .collect();
-
-        if matches.is_empty() {
-            return Some("N/A".to_string());
-        }
-
-        Some(format_matches(&matches))
+            .collect()
     }
 
     /// Match all transactions (sequential, WASM compatible)
     pub fn match_all(&self, transactions: &mut [Transaction]) {
         for tx in transactions.iter_mut() {
-            tx.matched_ip = self.match_single(tx).or(Some("N/A".to_string()));
+            let matches = self.match_single(tx);
+            tx.matched_ip = Some(if matches.is_empty() {
+                "N/A".to_string()
+            } else {
+                format_matches(&matches)
+            });
+            tx.matched_login = LoginDetails::merge(matches.iter().map(|m| &m.login));
         }
     }
 
@@ -158,20 +176,50 @@ fn format_matches(matches: &[IpMatch]) -> String {
 
     unique_matches
         .iter()
-        .map(|m| {
-            let offset_str = if m.offset_seconds == 0 {
-                "0s".to_string()
-            } else if m.offset_seconds > 0 {
-                format!("+{}s", m.offset_seconds)
-            } else {
-                format!("{}s", m.offset_seconds)
-            };
-            format!("{}:{}", offset_str, m.ip)
-        })
+        .map(|m| format!("{}:{}", format_offset(m.offset_millis), m.ip))
         .collect::<Vec<_>>()
         .join(" | ")
 }
 
+/// IP addresses of a `matched_ip` value, e.g. `["1.1.1.1", "2001:db8::1"]`
+/// for "-1s:1.1.1.1 | +0.250s:2001:db8::1"; empty for "N/A"
+pub fn matched_ips(matched: &str) -> Vec<&str> {
+    if matched.starts_with("N/A") {
+        return Vec::new();
+    }
+    matched
+        .split(" | ")
+        .map(|part| match part.split_once(':') {
+            Some((offset, ip)) if is_offset_label(offset) => ip,
+            _ => part,
+        })
+        .collect()
+}
+
+/// `0s`, `+2s`, `-1.500s`
+fn is_offset_label(text: &str) -> bool {
+    let digits = text.trim_start_matches(['+', '-']);
+    digits.strip_suffix('s').is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit() || b == b'.'))
+}
+
+/// Offset label such as `+2s`, `-1s` or `0s`; sub-second offsets keep
+/// their milliseconds (`+0.250s`, `-1.500s`)
+fn format_offset(offset_millis: i64) -> String {
+    let sign = if offset_millis > 0 {
+        "+"
+    } else if offset_millis < 0 {
+        "-"
+    } else {
+        ""
+    };
+    let abs = offset_millis.unsigned_abs();
+    if abs.is_multiple_of(1000) {
+        format!("{}{}s", sign, abs / 1000)
+    } else {
+        format!("{}{}.{:03}s", sign, abs / 1000, abs % 1000)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,8 +250,9 @@ mod tests {
     fn test_format_matches_single() {
         let matches = vec![IpMatch {
             ip: "192.168.1.1".to_string(),
-            offset_seconds: 0,
+            offset_millis: 0,
             row_index: 1,
+            login: LoginDetails::default(),
         }];
         assert_eq!(format_matches(&matches), "192.168.1.1");
     }
@@ -213,13 +262,15 @@ mod tests {
         let matches = vec![
             IpMatch {
                 ip: "192.168.1.1".to_string(),
-                offset_seconds: -1,
+                offset_millis: -1000,
                 row_index: 1,
+                login: LoginDetails::default(),
             },
             IpMatch {
                 ip: "10.0.0.1".to_string(),
-                offset_seconds: 2,
+                offset_millis: 2000,
                 row_index: 2,
+                login: LoginDetails::default(),
             },
         ];
         assert_eq!(format_matches(&matches), "-1s:192.168.1.1 | +2s:10.0.0.1");
@@ -230,13 +281,15 @@ mod tests {
         let matches = vec![
             IpMatch {
                 ip: "192.168.1.1".to_string(),
-                offset_seconds: 0,
+                offset_millis: 0,
                 row_index: 1,
+                login: LoginDetails::default(),
             },
             IpMatch {
                 ip: "192.168.1.1".to_string(),
-                offset_seconds: 1,
+                offset_millis: 1000,
                 row_index: 2,
+                login: LoginDetails::default(),
             },
         ];
         // Should return single IP since both are the same
@@ -306,6 +359,64 @@ mod tests {
         assert_eq!(transactions[0].matched_ip, Some("N/A".to_string()));
     }
 
+    #[test]
+    fn test_matcher_normalized_account() {
+        // Numeric cell lost its leading zero; the statement uses dashes
+        let ip_records = vec![
+            create_test_ip_record("2024-01-15 10:30:00", "12345678901", "192.168.1.1", 1),
+        ];
+        let mut transactions = vec![
+            create_test_transaction("2024-01-15 10:30:00", "012-345-678901"),
+        ];
+
+        IpMatcher::with_default_window(&ip_records).match_all(&mut transactions);
+        assert_eq!(transactions[0].matched_ip, Some("N/A".to_string()));
+
+        let matcher = IpMatcher::with_normalizer(&ip_records, TimeWindow::default(), AccountNormalizer::digits(12));
+        matcher.match_all(&mut transactions);
+        assert_eq!(transactions[0].matched_ip, Some("192.168.1.1".to_string()));
+        assert_eq!(transactions[0].account, "012-345-678901");
+    }
+
+    #[test]
+    fn test_matched_ips() {
+        assert_eq!(matched_ips("1.1.1.1"), ["1.1.1.1"]);
+        assert_eq!(matched_ips("2001:db8::1"), ["2001:db8::1"]);
+        assert_eq!(matched_ips("-1s:1.1.1.1 | +0.250s:2001:db8::1"), ["1.1.1.1", "2001:db8::1"]);
+        assert!(matched_ips("N/A").is_empty());
+    }
+
+    #[test]
+    fn test_format_offset_sub_second() {
+        assert_eq!(format_offset(0), "0s");
+        assert_eq!(format_offset(250), "+0.250s");
+        assert_eq!(format_offset(-1500), "-1.500s");
+        assert_eq!(format_offset(-2000), "-2s");
+    }
+
+    #[test]
+    fn test_matcher_window_in_milliseconds() {
+        let ip_records = vec![
+            create_test_ip_record("2024-01-15 10:29:58.900", "ACC001", "10.0.0.1", 1),
+            create_test_ip_record("2024-01-15 10:29:59.250", "ACC001", "10.0.0.2", 2),
+            create_test_ip_record("2024-01-15 10:30:02.250", "ACC001", "10.0.0.3", 3),
+            create_test_ip_record("2024-01-15 10:30:02.500", "ACC001", "10.0.0.4", 4),
+        ];
+        let mut transactions = vec![
+            create_test_transaction("2024-01-15 10:30:00.250", "ACC001"),
+        ];
+
+        let matcher = IpMatcher::with_default_window(&ip_records);
+        matcher.match_all(&mut transactions);
+
+        // 1.35s before and 2.25s after fall outside the window even though
+        // their whole-second differences would not
+        assert_eq!(
+            transactions[0].matched_ip,
+            Some("-1s:10.0.0.2 | +2s:10.0.0.3".to_string())
+        );
+    }
+
     #[test]
     fn test_matcher_stats() {
         let ip_records = vec![
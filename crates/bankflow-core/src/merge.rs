@@ -0,0 +1,307 @@
+//! Combining records from several files into one dataset
+//!
+//! Cases usually come with several monthly statements and several IP log
+//! returns, and their periods often overlap. A `Dataset` appends the records
+//! of each file, tags them with the file they came from and leaves out rows
+//! that another file already supplied, so the overlap is counted once.
+
+use crate::error::CoreError;
+use crate::models::{DuplicateRow, FileMetadata, IpRecord, RowSource, Transaction};
+use crate::parser::same_layout;
+use crate::timestamp;
+use std::collections::HashMap;
+
+/// A record that can be traced back to its file and compared across files
+pub trait SourcedRecord {
+    /// File, sheet and row the record was read from
+    fn source(&self) -> RowSource;
+
+    fn set_source_file(&mut self, file: &str);
+
+    /// Content that identifies the same row in two exports. Times are
+    /// compared after time zone normalization, amounts exactly.
+    fn dedup_key(&self) -> String;
+
+    /// Whether every file must share one header layout. Raw statement
+    /// columns are exported and masked by the positions of the first file's
+    /// headers, so statements of another layout cannot be merged.
+    const SAME_LAYOUT: bool = false;
+}
+
+impl SourcedRecord for Transaction {
+    const SAME_LAYOUT: bool = true;
+
+    fn source(&self) -> RowSource {
+        Transaction::source(self)
+    }
+
+    fn set_source_file(&mut self, file: &str) {
+        self.source_file = Some(file.to_string());
+    }
+
+    fn dedup_key(&self) -> String {
+        format!(
+            "{}\u{1f}{}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}\u{1f}{:?}",
+            self.account,
+            time_key(self.datetime.as_ref(), &self.timestamp),
+            self.income,
+            self.expense,
+            self.balance,
+            self.counterparty_account,
+            self.memo,
+        )
+    }
+}
+
+impl SourcedRecord for IpRecord {
+    fn source(&self) -> RowSource {
+        IpRecord::source(self)
+    }
+
+    fn set_source_file(&mut self, file: &str) {
+        self.source_file = Some(file.to_string());
+    }
+
+    fn dedup_key(&self) -> String {
+        format!(
+            "{}\u{1f}{}\u{1f}{}",
+            self.account,
+            time_key(self.datetime.as_ref(), &self.timestamp),
+            self.ip_address,
+        )
+    }
+}
+
+/// Parsed time when there is one, else the text as written
+fn time_key(datetime: Option<&chrono::NaiveDateTime>, text: &str) -> String {
+    match datetime {
+        Some(dt) => timestamp::format_datetime(dt),
+        None => text.trim().to_string(),
+    }
+}
+
+/// Records merged from any number of files, with the metadata of each file
+/// in load order
+#[derive(Debug, Clone)]
+pub struct Dataset<T> {
+    pub records: Vec<T>,
+    pub files: Vec<FileMetadata>,
+    /// Rows left out as duplicates, kept so they return when the file they
+    /// duplicated is replaced
+    dropped: Vec<T>,
+}
+
+impl<T> Default for Dataset<T> {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+            files: Vec::new(),
+            dropped: Vec::new(),
+        }
+    }
+}
+
+impl<T: SourcedRecord> Dataset<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dataset of a single file
+    pub fn from_file(records: Vec<T>, metadata: FileMetadata) -> Self {
+        let mut dataset = Self::new();
+        dataset.add(records, metadata);
+        dataset
+    }
+
+    /// Add the records of one file and return how many were kept.
+    ///
+    /// A file that is already loaded (same path, or same name when there is
+    /// no path) is replaced, and the other files are merged again so rows
+    /// that only duplicated the old version come back. Another file with the
+    /// same name gets a numbered display name such as "statement.xlsx (2)".
+    /// A row equal to one from a different file is dropped and listed in the
+    /// file's `report.duplicate_rows`; repeated rows within one file are
+    /// kept. Statements must share the header layout of those loaded.
+    pub fn append(&mut self, records: Vec<T>, mut metadata: FileMetadata) -> Result<usize, CoreError> {
+        let replaced = self.files.iter().position(|f| same_file(f, &metadata));
+        if T::SAME_LAYOUT {
+            let mut loaded = self.files.iter().enumerate().filter(|(idx, _)| Some(*idx) != replaced);
+            if let Some((_, other)) = loaded.find(|(_, f)| !same_layout(&f.headers, &metadata.headers)) {
+                return Err(CoreError::InvalidData(format!(
+                    "'{}' has a different column layout from '{}'; load it on its own instead of appending",
+                    metadata.filename, other.filename
+                )));
+            }
+        }
+
+        if let Some(idx) = replaced {
+            let replaced = self.files.remove(idx);
+            self.rebuild_without(&replaced.filename);
+        }
+        metadata.filename = self.unique_name(&metadata.filename);
+        Ok(self.add(records, metadata))
+    }
+
+    /// Merge the rows of every file but `filename` again, in load order
+    fn rebuild_without(&mut self, filename: &str) {
+        let mut rows = std::mem::take(&mut self.records);
+        rows.append(&mut self.dropped);
+        rows.retain(|r| r.source().file.as_deref() != Some(filename));
+
+        for mut metadata in std::mem::take(&mut self.files) {
+            let (mut own, rest): (Vec<T>, Vec<T>) =
+                rows.into_iter().partition(|r| r.source().file.as_deref() == Some(metadata.filename.as_str()));
+            rows = rest;
+            // Back into file order: by sheet, then row
+            let sheet_idx = |r: &T| {
+                let sheet = r.source().sheet;
+                metadata.sheets.iter().position(|s| Some(s) == sheet.as_ref()).unwrap_or(0)
+            };
+            own.sort_by_key(|r| (sheet_idx(r), r.source().row));
+            metadata.report.duplicate_rows.clear();
+            self.add(own, metadata);
+        }
+    }
+
+    /// Merge the records of a file whose display name is settled
+    fn add(&mut self, records: Vec<T>, mut metadata: FileMetadata) -> usize {
+        let mut loaded: HashMap<String, Vec<RowSource>> = HashMap::new();
+        for record in &self.records {
+            loaded.entry(record.dedup_key()).or_default().push(record.source());
+        }
+
+        // Each loaded row absorbs at most one incoming copy
+        let mut matched: HashMap<String, usize> = HashMap::new();
+        let mut kept = 0;
+        for mut record in records {
+            record.set_source_file(&metadata.filename);
+            let key = record.dedup_key();
+            if let Some(originals) = loaded.get(&key) {
+                let used = matched.entry(key).or_insert(0);
+                if let Some(original) = originals.get(*used) {
+                    *used += 1;
+                    let source = record.source();
+                    metadata.report.duplicate_rows.push(DuplicateRow {
+                        sheet: source.sheet,
+                        row: source.row,
+                        original: original.clone(),
+                    });
+                    self.dropped.push(record);
+                    continue;
+                }
+            }
+            self.records.push(record);
+            kept += 1;
+        }
+
+        self.files.push(metadata);
+        kept
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.files.clear();
+        self.dropped.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// `name`, or `name (2)`, `name (3)`, ... if a loaded file already uses it
+    fn unique_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.files.iter().any(|f| f.filename == candidate);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} ({})", name, n))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_default()
+    }
+}
+
+/// Whether two loads are of the same file
+fn same_file(a: &FileMetadata, b: &FileMetadata) -> bool {
+    match (&a.path, &b.path) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => a.filename == b.filename,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(filename: &str, path: Option<&str>) -> FileMetadata {
+        FileMetadata {
+            filename: filename.to_string(),
+            path: path.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn record(timestamp: &str, ip: &str, row: usize) -> IpRecord {
+        IpRecord::new(timestamp.to_string(), "ACC1".to_string(), ip.to_string(), row)
+    }
+
+    #[test]
+    fn test_overlapping_rows_are_dropped_once() {
+        let mut dataset = Dataset::new();
+        let january = vec![
+            record("2024-01-31 10:00:00", "1.1.1.1", 2),
+            record("2024-01-31 10:00:00", "1.1.1.1", 3),
+        ];
+        assert_eq!(dataset.append(january, metadata("jan.csv", None)).unwrap(), 2);
+
+        // One copy of the repeated row overlaps; the second is new
+        let february = vec![
+            record("2024-01-31 10:00:00", "1.1.1.1", 2),
+            record("2024-01-31 10:00:00", "1.1.1.1", 3),
+            record("2024-01-31 10:00:00", "1.1.1.1", 4),
+            record("2024-02-01 09:00:00", "2.2.2.2", 5),
+        ];
+        assert_eq!(dataset.append(february, metadata("feb.csv", None)).unwrap(), 2);
+        assert_eq!(dataset.len(), 4);
+
+        let duplicates = &dataset.files[1].report.duplicate_rows;
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[1].row, 3);
+        assert_eq!(duplicates[1].original.to_string(), "jan.csv / row 3");
+        assert_eq!(dataset.records[3].source().to_string(), "feb.csv / row 5");
+    }
+
+    #[test]
+    fn test_reloading_a_file_replaces_it() {
+        let mut dataset = Dataset::new();
+        dataset.append(vec![record("2024-01-31 10:00:00", "1.1.1.1", 2)], metadata("log.csv", Some("/a/log.csv"))).unwrap();
+        dataset.append(vec![record("2024-01-31 11:00:00", "3.3.3.3", 2)], metadata("log.csv", Some("/b/log.csv"))).unwrap();
+        assert_eq!(dataset.files[1].filename, "log.csv (2)");
+
+        dataset.append(vec![record("2024-01-31 12:00:00", "4.4.4.4", 2)], metadata("log.csv", Some("/a/log.csv"))).unwrap();
+        let ips: Vec<&str> = dataset.records.iter().map(|r| r.ip_address.as_str()).collect();
+        assert_eq!(ips, ["3.3.3.3", "4.4.4.4"]);
+        assert_eq!(dataset.files.len(), 2);
+        assert!(dataset.files[1].report.duplicate_rows.is_empty());
+    }
+
+    #[test]
+    fn test_replacing_a_file_restores_rows_it_duplicated() {
+        let mut dataset = Dataset::new();
+        dataset.append(vec![record("2024-01-31 10:00:00", "1.1.1.1", 2)], metadata("a.csv", None)).unwrap();
+        let b = vec![record("2024-01-31 10:00:00", "1.1.1.1", 2), record("2024-02-01 09:00:00", "2.2.2.2", 3)];
+        assert_eq!(dataset.append(b, metadata("b.csv", None)).unwrap(), 1);
+        assert_eq!(dataset.files[1].report.duplicate_rows.len(), 1);
+
+        // The new version of a.csv no longer has the shared row
+        dataset.append(vec![record("2024-03-01 08:00:00", "3.3.3.3", 2)], metadata("a.csv", None)).unwrap();
+        let sources: Vec<String> = dataset.records.iter().map(|r| r.source().to_string()).collect();
+        assert_eq!(sources, ["b.csv / row 2", "b.csv / row 3", "a.csv / row 2"]);
+        assert!(dataset.files[0].report.duplicate_rows.is_empty());
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! Uses NaiveDateTime for WASM compatibility (no timezone support in browsers)
 
+use crate::account::AccountNormalizer;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
 
 /// Metadata about a loaded file
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileMetadata {
     /// Full path (optional, only available on native)
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -15,6 +18,247 @@ pub struct FileMetadata {
     pub row_count: usize,
     pub column_count: usize,
     pub file_type: String,
+    /// Text encoding used to decode the file (text formats only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<EncodingInfo>,
+    /// Workbook sheets the records were read from (empty for text formats)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sheets: Vec<String>,
+    /// 1-based row the column headers were read from (first sheet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_row: Option<usize>,
+    /// Column headers as written in the file, aligned with `raw_columns`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+    /// Declared time zone of the timestamps ("Asia/Taipei", "UTC+08:00");
+    /// parsed times were normalized from it to UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Rows and cells the parser skipped or could not read
+    #[serde(default)]
+    pub report: ParseReport,
+    /// Name of the format profile applied to the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// 0-based raw columns the profile marks as sensitive
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_columns: Vec<usize>,
+}
+
+/// Per-row diagnostics collected while parsing a file, so investigators can
+/// see what was dropped or only partly read
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseReport {
+    /// Non-blank data rows below the header
+    pub rows_read: usize,
+    /// Rows left out of the records because a required value was empty
+    pub skipped_rows: Vec<SkippedRow>,
+    /// Kept rows whose timestamp could not be parsed; they never match an IP
+    pub unparsed_timestamps: Vec<UnparsedCell>,
+    /// Amount cells that held text but no readable number
+    pub unparsed_amounts: Vec<UnparsedCell>,
+    /// Debit/credit marker cells that are neither debit nor credit; the
+    /// amount's own sign decided the side instead
+    pub unknown_directions: Vec<UnparsedCell>,
+    /// Cells holding a spreadsheet error such as `#N/A` or `#VALUE!`
+    pub error_cells: Vec<UnparsedCell>,
+    /// IP cells with text that is not an IP address
+    pub invalid_ips: Vec<UnparsedCell>,
+    /// Rows dropped on merging because another loaded file had the same row
+    pub duplicate_rows: Vec<DuplicateRow>,
+    /// PDF rows whose columns were rebuilt from a layout that did not fit
+    /// the header cleanly; kept, but worth checking against the statement
+    pub uncertain_rows: Vec<UncertainRow>,
+}
+
+impl ParseReport {
+    /// True if every data row was read without problems
+    pub fn is_clean(&self) -> bool {
+        self.skipped_rows.is_empty()
+            && self.unparsed_timestamps.is_empty()
+            && self.unparsed_amounts.is_empty()
+            && self.unknown_directions.is_empty()
+            && self.error_cells.is_empty()
+            && self.invalid_ips.is_empty()
+            && self.duplicate_rows.is_empty()
+            && self.uncertain_rows.is_empty()
+    }
+}
+
+/// How far a streaming parse has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParseProgress {
+    /// Non-blank data rows read so far
+    pub rows_read: usize,
+    /// Records delivered so far, including the current chunk
+    pub records: usize,
+    /// Estimated share of the input read, from 0.0 to 1.0
+    pub fraction: f64,
+}
+
+/// A data row that produced no record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedRow {
+    /// Worksheet name (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// Row in the original file (1-based)
+    pub row: usize,
+    pub reason: SkipReason,
+}
+
+/// Why a data row was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    MissingTimestamp,
+    MissingAccount,
+    MissingIpAddress,
+}
+
+impl SkipReason {
+    /// Short description for reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SkipReason::MissingTimestamp => "Missing timestamp",
+            SkipReason::MissingAccount => "Missing account",
+            SkipReason::MissingIpAddress => "Missing IP address",
+        }
+    }
+}
+
+/// A row read from a PDF page whose cells may be misplaced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UncertainRow {
+    /// Page of the row ("Page 2")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// Line on the page (1-based)
+    pub row: usize,
+    pub reason: UncertainReason,
+    /// The text in question, as laid out on the page
+    pub text: String,
+}
+
+/// Why the cells of a PDF row are uncertain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UncertainReason {
+    /// Text lies left or right of every column
+    OutsideColumns,
+    /// Text straddles the boundary of two columns
+    SpansColumns,
+    /// Separate pieces of text fell into one column and were joined
+    SharedColumn,
+    /// A wrapped line was joined to the row above
+    WrappedLine,
+}
+
+impl UncertainReason {
+    /// Short description for reports
+    pub fn describe(&self) -> &'static str {
+        match self {
+            UncertainReason::OutsideColumns => "Text outside the columns",
+            UncertainReason::SpansColumns => "Text across two columns",
+            UncertainReason::SharedColumn => "Texts joined in one column",
+            UncertainReason::WrappedLine => "Wrapped line joined",
+        }
+    }
+}
+
+/// A row left out of a merged dataset as a copy of a row from another file,
+/// as happens where statement periods overlap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateRow {
+    /// Worksheet name (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// Row in the original file (1-based)
+    pub row: usize,
+    /// The row that was kept
+    pub original: RowSource,
+}
+
+/// Where a record was read from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowSource {
+    /// Display name of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Worksheet name (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// Row in the original file (1-based)
+    pub row: usize,
+}
+
+impl fmt::Display for RowSource {
+    /// "a.xlsx / Sheet1 / row 5", leaving out the parts that are unknown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in self.file.iter().chain(self.sheet.iter()) {
+            write!(f, "{} / ", part)?;
+        }
+        write!(f, "row {}", self.row)
+    }
+}
+
+/// A cell the parser could not interpret
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnparsedCell {
+    /// Worksheet name (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// Row in the original file (1-based)
+    pub row: usize,
+    /// Header of the column the cell is in
+    pub column: String,
+    /// Cell content as text
+    pub value: String,
+}
+
+/// A required field no header could be matched to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingColumn {
+    /// Mapping key (`timestamp`, `account`, `expense`, ...)
+    pub key: String,
+    /// Display name, e.g. "交易時間/timestamp"
+    pub label: String,
+    /// Headers of the file most similar to the field's aliases, best first
+    pub suggestions: Vec<String>,
+}
+
+/// Header row of one sheet, used to offer a sheet choice before parsing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetHeaders {
+    /// 0-based sheet position in the workbook
+    pub index: usize,
+    pub name: String,
+    /// 1-based row the headers were found on
+    pub header_row: usize,
+    pub headers: Vec<String>,
+}
+
+/// Which text encoding was used to decode a file, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodingInfo {
+    /// Canonical encoding name (e.g. "UTF-8", "Big5", "UTF-16LE")
+    pub encoding: String,
+    pub source: EncodingSource,
+    /// True if some bytes were invalid and replaced with U+FFFD
+    pub had_errors: bool,
+}
+
+/// How the encoding of a text file was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodingSource {
+    /// Byte order mark at the start of the file
+    Bom,
+    /// Content-based detection
+    Detected,
+    /// Set explicitly in the parse options
+    Override,
 }
 
 /// A single transaction record from File A
@@ -31,8 +275,27 @@ pub struct Transaction {
     pub income: Option<f64>,
     /// Expense amount (Column I)
     pub expense: Option<f64>,
+    /// Account on the other side of the transfer (對方帳號)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty_account: Option<String>,
+    /// Holder name of the counterparty account (對方戶名)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty_name: Option<String>,
+    /// Account balance after the transaction (餘額)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<f64>,
+    /// Currency code as written in the file, upper-cased (幣別)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Memo or description text (摘要)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
     /// Matched IP address(es) after analysis
     pub matched_ip: Option<String>,
+    /// Device, user agent, channel, result and session of the matched logins;
+    /// several distinct values are joined with " | "
+    #[serde(default, skip_serializing_if = "LoginDetails::is_empty")]
+    pub matched_login: LoginDetails,
     /// Country from Whois lookup
     pub ip_country: Option<String>,
     /// ISP from Whois lookup
@@ -41,6 +304,12 @@ pub struct Transaction {
     pub raw_columns: Vec<String>,
     /// Row index in original file (1-based)
     pub row_index: usize,
+    /// Worksheet the row came from (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// File the row came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
 }
 
 impl Transaction {
@@ -60,12 +329,84 @@ impl Transaction {
             account,
             income,
             expense,
+            counterparty_account: None,
+            counterparty_name: None,
+            balance: None,
+            currency: None,
+            memo: None,
             matched_ip: None,
+            matched_login: LoginDetails::default(),
             ip_country: None,
             ip_isp: None,
             raw_columns,
             row_index,
+            sheet: None,
+            source_file: None,
+        }
+    }
+
+    /// File, sheet and row the record was read from
+    pub fn source(&self) -> RowSource {
+        RowSource {
+            file: self.source_file.clone(),
+            sheet: self.sheet.clone(),
+            row: self.row_index,
+        }
+    }
+}
+
+/// Optional columns of a login log that attribute a login to a device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginDetails {
+    /// Device identifier (裝置編號)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Browser or app user agent string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Login channel as written, e.g. "APP" or "網銀"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Login result as written, e.g. "成功" or "failed"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_result: Option<String>,
+    /// Session identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+impl LoginDetails {
+    pub fn is_empty(&self) -> bool {
+        self.fields().iter().all(|f| f.is_none())
+    }
+
+    /// Fields in export column order: device, user agent, channel, result, session
+    pub fn fields(&self) -> [&Option<String>; 5] {
+        [&self.device_id, &self.user_agent, &self.channel, &self.login_result, &self.session_id]
+    }
+
+    fn fields_mut(&mut self) -> [&mut Option<String>; 5] {
+        [&mut self.device_id, &mut self.user_agent, &mut self.channel, &mut self.login_result, &mut self.session_id]
+    }
+
+    /// Combine the details of several logins field by field, keeping each
+    /// distinct value once in order and joining them with " | "
+    pub fn merge<'a>(logins: impl IntoIterator<Item = &'a LoginDetails>) -> LoginDetails {
+        let mut values: [Vec<&str>; 5] = Default::default();
+        for login in logins {
+            for (seen, field) in values.iter_mut().zip(login.fields()) {
+                if let Some(value) = field.as_deref() {
+                    if !seen.contains(&value) {
+                        seen.push(value);
+                    }
+                }
+            }
+        }
+        let mut merged = LoginDetails::default();
+        for (field, seen) in merged.fields_mut().into_iter().zip(values) {
+            *field = (!seen.is_empty()).then(|| seen.join(" | "));
         }
+        merged
     }
 }
 
@@ -79,67 +420,75 @@ pub struct IpRecord {
     pub timestamp: String,
     /// Account identifier
     pub account: String,
-    /// IP address
+    /// IP address in canonical form ("1.2.3.4", "2001:db8::1"), or the
+    /// cell text when it holds no valid address
     pub ip_address: String,
+    /// Parsed address; `None` when the cell holds no valid address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    /// IP cell as written in the file
+    #[serde(default)]
+    pub ip_text: String,
+    /// Optional login columns of the log
+    #[serde(default, skip_serializing_if = "LoginDetails::is_empty")]
+    pub login: LoginDetails,
     /// Row index in original file (1-based)
     pub row_index: usize,
+    /// Worksheet the row came from (workbook input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    /// File the row came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
 }
 
 impl IpRecord {
-    /// Create a new IP record with parsed datetime
+    /// Create a new IP record with parsed datetime and IP address. A port,
+    /// brackets or IPv6 zone around the address are dropped.
     pub fn new(timestamp: String, account: String, ip_address: String, row_index: usize) -> Self {
         let datetime = parse_datetime_string(&timestamp);
+        let ip = crate::ip::parse_ip(&ip_address);
         Self {
             datetime,
             timestamp,
             account,
-            ip_address,
+            ip_address: ip.map_or_else(|| ip_address.trim().to_string(), |ip| ip.to_string()),
+            ip,
+            ip_text: ip_address,
+            login: LoginDetails::default(),
             row_index,
+            sheet: None,
+            source_file: None,
+        }
+    }
+
+    /// File, sheet and row the record was read from
+    pub fn source(&self) -> RowSource {
+        RowSource {
+            file: self.source_file.clone(),
+            sheet: self.sheet.clone(),
+            row: self.row_index,
         }
     }
 }
 
 /// Parse datetime from various string formats
 fn parse_datetime_string(s: &str) -> Option<NaiveDateTime> {
-    let formats = [
-        "%Y-%m-%d %H:%M:%S",
-        "%Y/%m/%d %H:%M:%S",
-        "%d/%m/%Y %H:%M:%S",
-        "%Y-%m-%d %H:%M",
-        "%Y/%m/%d %H:%M",
-        "%m/%d/%Y %H:%M:%S",
-        "%Y-%m-%dT%H:%M:%S",
-    ];
-
-    for fmt in formats {
-        if let Ok(naive) = NaiveDateTime::parse_from_str(s.trim(), fmt) {
-            return Some(naive);
-        }
-    }
-    None
+    crate::timestamp::parse_timestamp(s, None)
 }
 
-/// Excel serial date to NaiveDateTime conversion
+/// Excel serial date to NaiveDateTime conversion, to the millisecond
 pub fn excel_date_to_datetime(serial: f64) -> Option<NaiveDateTime> {
     // Excel epoch is 1899-12-30 (accounting for the 1900 leap year bug)
     let days = serial.floor() as i64;
     let time_fraction = serial - serial.floor();
 
-    // Seconds in a day
-    let seconds = (time_fraction * 86400.0).round() as u32;
-    let hours = seconds / 3600;
-    let minutes = (seconds % 3600) / 60;
-    let secs = seconds % 60;
+    // Milliseconds in a day; finer digits are floating-point noise
+    let millis = (time_fraction * 86_400_000.0).round() as i64;
 
     // Excel epoch: 1899-12-30
     let epoch = NaiveDateTime::parse_from_str("1899-12-30 00:00:00", "%Y-%m-%d %H:%M:%S").ok()?;
-    Some(
-        epoch
-            + chrono::Duration::days(days)
-            + chrono::Duration::hours(hours as i64)
-            + chrono::Duration::minutes(minutes as i64)
-            + chrono::Duration::seconds(secs as i64),
-    )
+    Some(epoch + chrono::Duration::days(days) + chrono::Duration::milliseconds(millis))
 }
 
 /// Analysis result summary
@@ -150,6 +499,12 @@ pub struct AnalysisResult {
     pub multi_ip_count: usize,
     pub whois_queried: usize,
     pub settings: AnalysisSettings,
+    /// Declared time zones of the File A and File B sources (comma-separated
+    /// when the merged files differ), when matched on UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_a_timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_b_timezone: Option<String>,
 }
 
 /// Analysis settings
@@ -159,6 +514,9 @@ pub struct AnalysisSettings {
     pub split_income_expense: bool,
     pub ip_cross_reference: bool,
     pub whois_lookup: bool,
+    /// Account normalization used to join the two files; exact match when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_normalizer: Option<AccountNormalizer>,
 }
 
 impl Default for AnalysisSettings {
@@ -168,6 +526,7 @@ impl Default for AnalysisSettings {
             split_income_expense: true,
             ip_cross_reference: true,
             whois_lookup: false,
+            account_normalizer: None,
         }
     }
 }
@@ -199,4 +558,15 @@ mod tests {
         let dt = excel_date_to_datetime(45306.5);
         assert!(dt.is_some());
     }
+
+    #[test]
+    fn test_excel_date_keeps_milliseconds() {
+        let serial = 45306.5 + 0.125 / 86400.0;
+        let dt = excel_date_to_datetime(serial).unwrap();
+        assert_eq!(dt.format("%H:%M:%S%.3f").to_string(), "12:00:00.125");
+
+        // Whole seconds do not pick up floating-point noise
+        let dt = excel_date_to_datetime(45306.0 + 37.0 / 86400.0).unwrap();
+        assert_eq!(dt.format("%H:%M:%S%.f").to_string(), "00:00:37");
+    }
 }
@@ -1,13 +1,105 @@
 //! Excel file parser using calamine
 //!
 //! Supports both file path (native) and byte array (WASM) inputs.
-
+//! Workbooks (xlsx/xls/xlsb/ods) are read with calamine; CSV/TSV text,
+//! JSON/NDJSON records and HTML or SpreadsheetML ".xls" files are loaded
+//! into the same cell grid so header mapping works identically.
+//! Password-encrypted workbooks are decrypted in memory when
+//! `ParseOptions::password` is given. Text PDF statements are read as
+//! one table rebuilt from the page layout, each page a sheet ("Page 1", ...)
+//! whose rows are numbered by their line on the page. Fixed-width text
+//! reports are cut into fields at the positions of the profile's
+//! `fixed_width` layout.
+
+mod delimited;
+mod encoding;
+mod encrypted;
+mod fixed_width;
+mod html;
+mod json;
+mod markup;
+mod pdf;
+mod pdf_table;
+mod spreadsheetml;
+mod stream;
+
+use crate::amount::{self, Direction};
 use crate::error::CoreError;
-use crate::models::{excel_date_to_datetime, FileMetadata, IpRecord, Transaction};
-use calamine::{open_workbook_auto_from_rs, Data, DataType, Reader, Sheets};
+use crate::ip;
+use crate::merge::SourcedRecord;
+use crate::models::{
+    excel_date_to_datetime, EncodingInfo, FileMetadata, IpRecord, LoginDetails, MissingColumn, ParseProgress,
+    ParseReport, SheetHeaders, SkipReason, SkippedRow, Transaction, UncertainRow, UnparsedCell,
+};
+use crate::profile::{self, FixedWidthLayout, FormatProfile, ProfileKind};
+use crate::timestamp;
+use calamine::{open_workbook_auto_from_rs, Data, DataType, Range, Reader, Sheets};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Cursor;
 
+/// Per-file parsing options beyond the column mapping
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseOptions {
+    /// Text encoding label for text input (CSV/TSV, JSON, HTML, SpreadsheetML),
+    /// e.g. "big5", "cp950", "utf-16le". When unset the encoding is detected
+    /// automatically.
+    pub encoding: Option<String>,
+    /// Workbook sheets to read, by 0-based index or name. Several sheets are
+    /// concatenated and must share one header layout. Empty means the first
+    /// sheet. The tables of an HTML file count as sheets named "Table 1",
+    /// "Table 2", ...; ignored for other text input. Every page of a PDF is
+    /// read.
+    pub sheets: Vec<SheetSelector>,
+    /// Read every sheet of the workbook (overrides `sheets`)
+    pub all_sheets: bool,
+    /// 1-based row holding the column headers. When unset the header row is
+    /// detected by matching the first rows against the known header aliases.
+    /// For a PDF this is the line of the first page with the column titles.
+    pub header_row: Option<usize>,
+    /// Timestamp layout for this file as a chrono strftime pattern, with `ROC`
+    /// standing for a Minguo year (e.g. "ROC/%m/%d %H:%M:%S"). Rows that do
+    /// not match fall back to the built-in layouts.
+    pub timestamp_format: Option<String>,
+    /// Time zone the file's timestamps are written in, as a UTC offset
+    /// ("+08:00", "UTC+8") or an IANA name ("Asia/Taipei"). Parsed times are
+    /// normalized to UTC; when unset they are compared as written.
+    pub timezone: Option<String>,
+    /// Format profile describing the file's layout. Its column aliases extend
+    /// the mapping, and its encoding, header row and timestamp format apply
+    /// where these options leave them unset. A profile with a `fixed_width`
+    /// layout makes the file be read as a fixed-width report.
+    pub profile: Option<FormatProfile>,
+    /// Password of an encrypted workbook or PDF. The file is decrypted in
+    /// memory; the password is never serialized back out with the options.
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+}
+
+impl ParseOptions {
+    /// Copy of the options with the profile's settings filled in
+    fn with_profile_defaults(&self) -> ParseOptions {
+        let mut options = self.clone();
+        if let Some(profile) = &self.profile {
+            options.encoding = options.encoding.or_else(|| profile.encoding.clone());
+            options.header_row = options.header_row.or(profile.header_row);
+            options.timestamp_format = options.timestamp_format.or_else(|| profile.timestamp_format.clone());
+            options.timezone = options.timezone.or_else(|| profile.timezone.clone());
+        }
+        options
+    }
+}
+
+/// Reference to a workbook sheet by position or name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SheetSelector {
+    Index(usize),
+    Name(String),
+}
+
 /// Column indices for File A (Transaction file)
 mod file_a_columns {
     pub const _TIMESTAMP: usize = 0;
@@ -26,38 +118,184 @@ mod file_b_columns {
 /// Header-based column mapping helpers
 /// Header-based column mapping helpers
 pub mod header_map {
+    use crate::models::MissingColumn;
+    use crate::profile::AmountMode;
+    use crate::timestamp::to_half_width;
     use std::collections::HashMap;
 
     #[derive(Debug, Clone, Copy)]
     pub struct FileAColumns {
         pub timestamp: usize,
+        /// Separate time-of-day column when `timestamp` only holds the date
+        pub time: Option<usize>,
         pub account: usize,
-        pub expense: usize,
-        pub income: usize,
+        pub amounts: AmountColumns,
+        pub details: DetailColumns,
+    }
+
+    /// Optional File A columns read into typed `Transaction` fields, so they
+    /// do not depend on a column position and survive column masking
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct DetailColumns {
+        pub counterparty_account: Option<usize>,
+        pub counterparty_name: Option<usize>,
+        pub balance: Option<usize>,
+        pub currency: Option<usize>,
+        pub memo: Option<usize>,
+    }
+
+    /// How a File A layout records money going out and coming in
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum AmountColumns {
+        /// Separate expense (支出) and income (存入) columns
+        Split { expense: usize, income: usize },
+        /// One signed amount: positive is income, negative is expense
+        Signed { amount: usize },
+        /// One amount plus a 借/貸 (debit/credit) indicator column
+        Indicator { amount: usize, indicator: usize },
+    }
+
+    impl AmountColumns {
+        fn indices(&self) -> Vec<usize> {
+            match *self {
+                AmountColumns::Split { expense, income } => vec![expense, income],
+                AmountColumns::Signed { amount } => vec![amount],
+                AmountColumns::Indicator { amount, indicator } => vec![amount, indicator],
+            }
+        }
     }
 
     #[derive(Debug, Clone, Copy)]
     pub struct FileBColumns {
         pub timestamp: usize,
+        /// Separate time-of-day column when `timestamp` only holds the date
+        pub time: Option<usize>,
         pub account: usize,
         pub ip_address: usize,
+        pub login: LoginColumns,
+    }
+
+    /// Optional File B columns read into `IpRecord::login`
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct LoginColumns {
+        pub device_id: Option<usize>,
+        pub user_agent: Option<usize>,
+        pub channel: Option<usize>,
+        pub login_result: Option<usize>,
+        pub session_id: Option<usize>,
     }
 
+    /// Header text for comparison: half-width, lower-case, without whitespace
     fn normalize_header(value: &str) -> String {
-        value.trim().to_lowercase()
+        value
+            .chars()
+            .map(to_half_width)
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase()
+    }
+
+    /// Similarity at or above which a header is used for a field with no exact match
+    const FUZZY_ACCEPT: f64 = 0.8;
+    /// Similarity at or above which a header is offered as a suggestion
+    const FUZZY_SUGGEST: f64 = 0.4;
+    /// Suggestions listed per missing field
+    const MAX_SUGGESTIONS: usize = 3;
+
+    /// Similarity (0.0-1.0) of a header to an alias, both normalized.
+    ///
+    /// A header starting with the alias ("交易時間(台北)") scores higher than
+    /// one merely containing it ("對方帳號"); otherwise the edit distance
+    /// relative to the longer text is used.
+    fn similarity(header: &str, alias: &str) -> f64 {
+        if header == alias {
+            return 1.0;
+        }
+        let header_len = header.chars().count();
+        let alias_len = alias.chars().count();
+        if header_len == 0 || alias_len == 0 {
+            return 0.0;
+        }
+
+        let containment = if alias_len >= 2 && header.starts_with(alias) {
+            0.65 + 0.35 * alias_len as f64 / header_len as f64
+        } else if alias_len >= 2 && header.contains(alias) {
+            0.5 + 0.3 * alias_len as f64 / header_len as f64
+        } else if header_len >= 2 && alias.contains(header) {
+            0.4 + 0.4 * header_len as f64 / alias_len as f64
+        } else {
+            0.0
+        };
+        let distance = levenshtein(header, alias) as f64;
+        let edit = 1.0 - distance / header_len.max(alias_len) as f64;
+        containment.max(edit)
+    }
+
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut previous: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut current = vec![i + 1; b.len() + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let substitution = previous[j] + usize::from(ca != *cb);
+                current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            }
+            previous = current;
+        }
+        previous[b.len()]
+    }
+
+    /// Headers not in `taken`, with their best similarity to any candidate, best first
+    fn ranked_headers(headers: &[String], candidates: &[&str], taken: &[usize]) -> Vec<(usize, f64)> {
+        let aliases: Vec<String> = candidates.iter().map(|c| normalize_header(c)).collect();
+        let mut ranked: Vec<(usize, f64)> = headers
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !taken.contains(idx))
+            .map(|(idx, header)| {
+                let header = normalize_header(header);
+                let score = aliases.iter().map(|a| similarity(&header, a)).fold(0.0, f64::max);
+                (idx, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+
+    /// Closest header for a field with no exact match, if it is similar
+    /// enough and no other free header scores the same
+    fn fuzzy_index(headers: &[String], candidates: &[&str], taken: &[usize]) -> Option<usize> {
+        match ranked_headers(headers, candidates, taken).as_slice() {
+            [(idx, best), rest @ ..] if *best >= FUZZY_ACCEPT => {
+                rest.first().is_none_or(|(_, next)| next < best).then_some(*idx)
+            }
+            _ => None,
+        }
+    }
+
+    fn missing_column(headers: &[String], candidates: &[&str], taken: &[usize], key: &str, label: &str) -> MissingColumn {
+        MissingColumn {
+            key: key.to_string(),
+            label: label.to_string(),
+            suggestions: ranked_headers(headers, candidates, taken)
+                .into_iter()
+                .filter(|(_, score)| *score >= FUZZY_SUGGEST)
+                .take(MAX_SUGGESTIONS)
+                .map(|(idx, _)| headers[idx].clone())
+                .collect(),
+        }
+    }
+
+    fn mapped_index(headers: &[String], mapping: Option<&HashMap<String, String>>, map_key: &str) -> Option<usize> {
+        let target_header = mapping?.get(map_key)?;
+        let norm_target = normalize_header(target_header);
+        headers.iter().position(|header| normalize_header(header) == norm_target)
     }
 
     fn find_index(headers: &[String], candidates: &[&str], mapping: Option<&HashMap<String, String>>, map_key: &str) -> Option<usize> {
         // 1. Check Custom Mapping
-        if let Some(map) = mapping {
-            if let Some(target_header) = map.get(map_key) {
-                let norm_target = normalize_header(target_header);
-                for (idx, header) in headers.iter().enumerate() {
-                    if normalize_header(header) == norm_target {
-                        return Some(idx);
-                    }
-                }
-            }
+        if let Some(idx) = mapped_index(headers, mapping, map_key) {
+            return Some(idx);
         }
 
         // 2. Fallback to Default Candidates
@@ -70,20 +308,182 @@ pub mod header_map {
         None
     }
 
-    pub fn validate_file_a_headers(headers: &[String], mapping: Option<&HashMap<String, String>>) -> Result<FileAColumns, Vec<String>> {
-        let mut missing = Vec::new();
+    const FILE_A_TIMESTAMP: &[&str] = &["交易時間", "時間", "timestamp", "交易日期", "transaction time", "txn time"];
+    const FILE_A_ACCOUNT: &[&str] = &["帳號", "account", "account_id"];
+    const FILE_A_EXPENSE: &[&str] = &["支出金額", "expense", "支出"];
+    const FILE_A_INCOME: &[&str] = &["存入金額", "收入金額", "income", "存入"];
+    const FILE_A_AMOUNT: &[&str] = &["交易金額", "金額", "amount"];
+    const FILE_A_INDICATOR: &[&str] = &["借貸", "借/貸", "借貸別", "收支", "存提", "dr/cr", "debit/credit", "direction"];
+    const FILE_A_COUNTERPARTY_ACCOUNT: &[&str] = &["對方帳號", "對方帳戶", "轉出入帳號", "counterparty account", "counterparty_account"];
+    const FILE_A_COUNTERPARTY_NAME: &[&str] = &["對方戶名", "對方名稱", "counterparty name", "counterparty_name"];
+    const FILE_A_BALANCE: &[&str] = &["餘額", "帳戶餘額", "結餘", "balance"];
+    const FILE_A_CURRENCY: &[&str] = &["幣別", "幣種", "currency", "ccy"];
+    const FILE_A_MEMO: &[&str] = &["摘要", "備註", "附言", "memo", "description", "remarks"];
+
+    const FILE_B_TIMESTAMP: &[&str] = &["登入時間", "時間", "timestamp", "login time"];
+    const FILE_B_ACCOUNT: &[&str] = &["帳號", "account", "account_id"];
+    const FILE_B_IP_ADDRESS: &[&str] = &["ip位址", "ip地址", "ip", "ip address"];
+    const FILE_B_DEVICE_ID: &[&str] = &["裝置編號", "裝置識別碼", "設備編號", "device id", "device_id", "device"];
+    const FILE_B_USER_AGENT: &[&str] = &["user agent", "user_agent", "瀏覽器資訊", "ua"];
+    const FILE_B_CHANNEL: &[&str] = &["登入管道", "通路", "管道", "channel"];
+    const FILE_B_LOGIN_RESULT: &[&str] = &["登入結果", "登入狀態", "login result", "login_result", "result", "status"];
+    const FILE_B_SESSION_ID: &[&str] = &["session id", "session_id", "session", "工作階段"];
+
+    const DATE_COLUMN: &[&str] = &["交易日期", "登入日期", "日期", "date"];
+    const TIME_COLUMN: &[&str] = &["交易時間", "登入時間", "時間", "time"];
+
+    /// Resolve the timestamp column, or a date column plus a separate time column.
+    ///
+    /// A mapped `time` column pairs with the `date` (or `timestamp`) column; an
+    /// explicit `timestamp` mapping keeps a single column; otherwise distinct
+    /// date and time headers are combined.
+    fn find_timestamp(headers: &[String], candidates: &[&str], mapping: Option<&HashMap<String, String>>) -> (Option<usize>, Option<usize>) {
+        if let Some(time) = mapped_index(headers, mapping, "time") {
+            let date = find_index(headers, DATE_COLUMN, mapping, "date")
+                .filter(|&date| date != time)
+                .or_else(|| find_index(headers, candidates, mapping, "timestamp"));
+            return (date, Some(time));
+        }
 
-        let timestamp = find_index(headers, &["交易時間", "時間", "timestamp", "交易日期"], mapping, "timestamp");
-        if timestamp.is_none() { missing.push("交易時間/timestamp".to_string()); }
+        if mapped_index(headers, mapping, "timestamp").is_none() {
+            let date = find_index(headers, DATE_COLUMN, mapping, "date");
+            let time = find_index(headers, TIME_COLUMN, None, "time");
+            if let (Some(date), Some(time)) = (date, time) {
+                if date != time {
+                    return (Some(date), Some(time));
+                }
+            }
+        }
 
-        let account = find_index(headers, &["帳號", "account", "account_id"], mapping, "account");
-        if account.is_none() { missing.push("帳號/account".to_string()); }
+        (find_index(headers, candidates, mapping, "timestamp"), None)
+    }
 
-        let expense = find_index(headers, &["支出金額", "expense", "支出"], mapping, "expense");
-        if expense.is_none() { missing.push("支出金額/expense".to_string()); }
+    /// Resolve the amount layout. With a profile's `mode` only that layout
+    /// is accepted. Otherwise separate expense/income columns are used
+    /// unless an `amount` column is mapped explicitly; failing that a single
+    /// amount column is signed, or paired with a 借/貸 indicator if present.
+    fn find_amounts(
+        headers: &[String],
+        mapping: Option<&HashMap<String, String>>,
+        mode: Option<AmountMode>,
+    ) -> Option<AmountColumns> {
+        let split = || {
+            let expense = find_index(headers, FILE_A_EXPENSE, mapping, "expense")?;
+            let income = find_index(headers, FILE_A_INCOME, mapping, "income")?;
+            Some(AmountColumns::Split { expense, income })
+        };
+        let indicator = || find_index(headers, FILE_A_INDICATOR, mapping, "direction");
+        match mode {
+            Some(AmountMode::Split) => split(),
+            Some(AmountMode::Signed) => Some(AmountColumns::Signed { amount: find_amount(headers, mapping)? }),
+            Some(AmountMode::Indicator) => Some(AmountColumns::Indicator {
+                amount: find_amount(headers, mapping)?,
+                indicator: indicator()?,
+            }),
+            None => {
+                if mapped_index(headers, mapping, "amount").is_none() {
+                    if let Some(amounts) = split() {
+                        return Some(amounts);
+                    }
+                }
+                let amount = find_amount(headers, mapping)?;
+                match indicator() {
+                    Some(indicator) => Some(AmountColumns::Indicator { amount, indicator }),
+                    None => Some(AmountColumns::Signed { amount }),
+                }
+            }
+        }
+    }
 
-        let income = find_index(headers, &["存入金額", "收入金額", "income", "存入"], mapping, "income");
-        if income.is_none() { missing.push("存入金額/income".to_string()); }
+    /// The single amount column, by mapping or exact alias
+    fn find_amount(headers: &[String], mapping: Option<&HashMap<String, String>>) -> Option<usize> {
+        mapped_index(headers, mapping, "amount").or_else(|| find_index(headers, FILE_A_AMOUNT, None, "amount"))
+    }
+
+    /// Resolve the optional detail columns by mapping or exact alias, leaving
+    /// out columns already used for a required field
+    fn find_details(headers: &[String], mapping: Option<&HashMap<String, String>>, taken: &[usize]) -> DetailColumns {
+        let find = |candidates: &[&str], key: &str| {
+            find_index(headers, candidates, mapping, key).filter(|idx| !taken.contains(idx))
+        };
+        DetailColumns {
+            counterparty_account: find(FILE_A_COUNTERPARTY_ACCOUNT, "counterparty_account"),
+            counterparty_name: find(FILE_A_COUNTERPARTY_NAME, "counterparty_name"),
+            balance: find(FILE_A_BALANCE, "balance"),
+            currency: find(FILE_A_CURRENCY, "currency"),
+            memo: find(FILE_A_MEMO, "memo"),
+        }
+    }
+
+    /// Resolve the optional File B login columns, as `find_details` does for File A
+    fn find_login(headers: &[String], mapping: Option<&HashMap<String, String>>, taken: &[usize]) -> LoginColumns {
+        let find = |candidates: &[&str], key: &str| {
+            find_index(headers, candidates, mapping, key).filter(|idx| !taken.contains(idx))
+        };
+        LoginColumns {
+            device_id: find(FILE_B_DEVICE_ID, "device_id"),
+            user_agent: find(FILE_B_USER_AGENT, "user_agent"),
+            channel: find(FILE_B_CHANNEL, "channel"),
+            login_result: find(FILE_B_LOGIN_RESULT, "login_result"),
+            session_id: find(FILE_B_SESSION_ID, "session_id"),
+        }
+    }
+
+    /// Resolve the File A columns. Fields without an exact (or mapped) header
+    /// fall back to the closest similar header; fields still unresolved are
+    /// returned with ranked suggestions.
+    pub fn validate_file_a_headers(headers: &[String], mapping: Option<&HashMap<String, String>>) -> Result<FileAColumns, Vec<MissingColumn>> {
+        validate_file_a_layout(headers, mapping, None)
+    }
+
+    /// `validate_file_a_headers` for a profile whose `amount_mode` is set:
+    /// the amount columns must follow that layout, and those missing from
+    /// it are reported rather than another layout being detected
+    pub fn validate_file_a_layout(
+        headers: &[String],
+        mapping: Option<&HashMap<String, String>>,
+        amount_mode: Option<AmountMode>,
+    ) -> Result<FileAColumns, Vec<MissingColumn>> {
+        let (mut timestamp, time) = find_timestamp(headers, FILE_A_TIMESTAMP, mapping);
+        let mut account = find_index(headers, FILE_A_ACCOUNT, mapping, "account");
+        let mut amounts = find_amounts(headers, mapping, amount_mode);
+        let single_amount = matches!(amount_mode, Some(AmountMode::Signed | AmountMode::Indicator));
+
+        let mut taken: Vec<usize> = [timestamp, time, account].into_iter().flatten().collect();
+        taken.extend(amounts.iter().flat_map(AmountColumns::indices));
+
+        let mut resolve = |index: &mut Option<usize>, candidates: &[&str]| {
+            if index.is_none() {
+                *index = fuzzy_index(headers, candidates, &taken);
+                taken.extend(*index);
+            }
+        };
+        resolve(&mut timestamp, FILE_A_TIMESTAMP);
+        resolve(&mut account, FILE_A_ACCOUNT);
+        let mut expense = None;
+        let mut income = None;
+        if amounts.is_none() && !single_amount {
+            expense = find_index(headers, FILE_A_EXPENSE, mapping, "expense");
+            income = find_index(headers, FILE_A_INCOME, mapping, "income");
+            resolve(&mut expense, FILE_A_EXPENSE);
+            resolve(&mut income, FILE_A_INCOME);
+            if let (Some(expense), Some(income)) = (expense, income) {
+                amounts = Some(AmountColumns::Split { expense, income });
+            }
+        }
+
+        let mut missing = Vec::new();
+        if timestamp.is_none() { missing.push(missing_column(headers, FILE_A_TIMESTAMP, &taken, "timestamp", "交易時間/timestamp")); }
+        if account.is_none() { missing.push(missing_column(headers, FILE_A_ACCOUNT, &taken, "account", "帳號/account")); }
+        if amounts.is_none() && single_amount {
+            if find_amount(headers, mapping).is_none() { missing.push(missing_column(headers, FILE_A_AMOUNT, &taken, "amount", "交易金額/amount")); }
+            if amount_mode == Some(AmountMode::Indicator) && find_index(headers, FILE_A_INDICATOR, mapping, "direction").is_none() {
+                missing.push(missing_column(headers, FILE_A_INDICATOR, &taken, "direction", "借貸別/direction"));
+            }
+        } else if amounts.is_none() {
+            if expense.is_none() { missing.push(missing_column(headers, FILE_A_EXPENSE, &taken, "expense", "支出金額/expense")); }
+            if income.is_none() { missing.push(missing_column(headers, FILE_A_INCOME, &taken, "income", "存入金額/income")); }
+        }
 
         if !missing.is_empty() {
             return Err(missing);
@@ -91,23 +491,35 @@ pub mod header_map {
 
         Ok(FileAColumns {
             timestamp: timestamp.unwrap(),
+            time,
             account: account.unwrap(),
-            expense: expense.unwrap(),
-            income: income.unwrap(),
+            amounts: amounts.unwrap(),
+            details: find_details(headers, mapping, &taken),
         })
     }
 
-    pub fn validate_file_b_headers(headers: &[String], mapping: Option<&HashMap<String, String>>) -> Result<FileBColumns, Vec<String>> {
-        let mut missing = Vec::new();
-
-        let timestamp = find_index(headers, &["登入時間", "時間", "timestamp"], mapping, "timestamp");
-        if timestamp.is_none() { missing.push("登入時間/timestamp".to_string()); }
-
-        let account = find_index(headers, &["帳號", "account", "account_id"], mapping, "account");
-        if account.is_none() { missing.push("帳號/account".to_string()); }
+    /// Resolve the File B columns, with the same fallback and suggestions
+    /// as `validate_file_a_headers`
+    pub fn validate_file_b_headers(headers: &[String], mapping: Option<&HashMap<String, String>>) -> Result<FileBColumns, Vec<MissingColumn>> {
+        let (mut timestamp, time) = find_timestamp(headers, FILE_B_TIMESTAMP, mapping);
+        let mut account = find_index(headers, FILE_B_ACCOUNT, mapping, "account");
+        let mut ip_address = find_index(headers, FILE_B_IP_ADDRESS, mapping, "ip_address");
+
+        let mut taken: Vec<usize> = [timestamp, time, account, ip_address].into_iter().flatten().collect();
+        let mut resolve = |index: &mut Option<usize>, candidates: &[&str]| {
+            if index.is_none() {
+                *index = fuzzy_index(headers, candidates, &taken);
+                taken.extend(*index);
+            }
+        };
+        resolve(&mut timestamp, FILE_B_TIMESTAMP);
+        resolve(&mut account, FILE_B_ACCOUNT);
+        resolve(&mut ip_address, FILE_B_IP_ADDRESS);
 
-        let ip_address = find_index(headers, &["ip位址", "ip地址", "ip", "ip address"], mapping, "ip_address");
-        if ip_address.is_none() { missing.push("IP位址/address".to_string()); }
+        let mut missing = Vec::new();
+        if timestamp.is_none() { missing.push(missing_column(headers, FILE_B_TIMESTAMP, &taken, "timestamp", "登入時間/timestamp")); }
+        if account.is_none() { missing.push(missing_column(headers, FILE_B_ACCOUNT, &taken, "account", "帳號/account")); }
+        if ip_address.is_none() { missing.push(missing_column(headers, FILE_B_IP_ADDRESS, &taken, "ip_address", "IP位址/address")); }
 
         if !missing.is_empty() {
             return Err(missing);
@@ -115,203 +527,1036 @@ pub mod header_map {
 
         Ok(FileBColumns {
             timestamp: timestamp.unwrap(),
+            time,
             account: account.unwrap(),
             ip_address: ip_address.unwrap(),
+            login: find_login(headers, mapping, &taken),
         })
     }
+
+    /// Number of File A fields a candidate header row resolves (0-4); any
+    /// complete amount layout counts as both expense and income
+    pub fn file_a_header_score(headers: &[String], mapping: Option<&HashMap<String, String>>) -> usize {
+        let amounts = if find_amounts(headers, mapping, None).is_some() {
+            2
+        } else {
+            count_resolved(headers, &[(FILE_A_EXPENSE, "expense"), (FILE_A_INCOME, "income")], mapping)
+        };
+        count_resolved(headers, &[(FILE_A_TIMESTAMP, "timestamp"), (FILE_A_ACCOUNT, "account")], mapping) + amounts
+    }
+
+    /// Number of File B fields a candidate header row resolves (0-3)
+    pub fn file_b_header_score(headers: &[String], mapping: Option<&HashMap<String, String>>) -> usize {
+        count_resolved(
+            headers,
+            &[
+                (FILE_B_TIMESTAMP, "timestamp"),
+                (FILE_B_ACCOUNT, "account"),
+                (FILE_B_IP_ADDRESS, "ip_address"),
+            ],
+            mapping,
+        )
+    }
+
+    fn count_resolved(headers: &[String], fields: &[(&[&str], &str)], mapping: Option<&HashMap<String, String>>) -> usize {
+        fields
+            .iter()
+            .filter(|(candidates, key)| find_index(headers, candidates, mapping, key).is_some())
+            .count()
+    }
+}
+
+/// Input formats recognised by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    /// Binary workbook handled by calamine (xlsx/xls/xlsb/ods)
+    Workbook,
+    /// Delimited text (CSV/TSV)
+    Delimited,
+    /// JSON array of records or NDJSON
+    Json,
+    /// HTML tables or SpreadsheetML, often named .xls
+    Markup(markup::MarkupKind),
+    /// Text layer of a PDF statement
+    Pdf,
+    /// Fixed-width text report laid out by the profile
+    FixedWidth,
+}
+
+/// ZIP container signature (xlsx, xlsb, ods)
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// OLE compound document signature (legacy xls, encrypted xlsx)
+const OLE_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Sniff the input format from the leading bytes
+fn detect_input_format(data: &[u8]) -> InputFormat {
+    if data.starts_with(ZIP_MAGIC) || data.starts_with(OLE_MAGIC) {
+        InputFormat::Workbook
+    } else if pdf::is_pdf(data) {
+        InputFormat::Pdf
+    } else if let Some(kind) = markup::sniff(data) {
+        InputFormat::Markup(kind)
+    } else if json::looks_like_json(data) {
+        InputFormat::Json
+    } else {
+        InputFormat::Delimited
+    }
+}
+
+/// Input format of the file as sniffed. Plain text is a fixed-width report
+/// when the profile gives its layout; a workbook, PDF or JSON file matched
+/// to such a profile by its headers is still read as what it is.
+fn input_format(data: &[u8], options: Option<&ParseOptions>) -> InputFormat {
+    match detect_input_format(data) {
+        InputFormat::Delimited if fixed_width_layout(options).is_some() => InputFormat::FixedWidth,
+        format => format,
+    }
+}
+
+fn fixed_width_layout(options: Option<&ParseOptions>) -> Option<&FixedWidthLayout> {
+    options.and_then(|o| o.profile.as_ref()).and_then(|p| p.fixed_width.as_ref())
+}
+
+/// A single worksheet (or the whole of a text file) as a cell grid
+struct LoadedSheet {
+    /// Worksheet name; `None` for single-table text input
+    name: Option<String>,
+    range: Range<Data>,
+    /// Position of the header row within `range.rows()`
+    header_idx: usize,
+    /// Row number of each row of `range` where rows are not numbered by
+    /// position (PDF and fixed-width lines); empty otherwise
+    row_numbers: Vec<usize>,
+}
+
+impl LoadedSheet {
+    fn new(name: Option<String>, range: Range<Data>) -> Self {
+        Self { name, range, header_idx: 0, row_numbers: Vec::new() }
+    }
+
+    fn numbered(name: Option<String>, range: Range<Data>, row_numbers: Vec<usize>) -> Self {
+        Self { name, range, header_idx: 0, row_numbers }
+    }
+
+    /// 1-based row number of the row at `idx` in `range.rows()`
+    fn row_number(&self, idx: usize) -> usize {
+        match self.row_numbers.get(idx) {
+            Some(&number) => number,
+            None => self.first_row() + idx + 1,
+        }
+    }
+
+    /// 0-based sheet row of the first row in `range` (calamine trims leading empty rows)
+    fn first_row(&self) -> usize {
+        self.range.start().map_or(0, |(row, _)| row as usize)
+    }
+
+    /// 1-based sheet row number of the header row
+    fn header_row(&self) -> usize {
+        self.row_number(self.header_idx)
+    }
+
+    fn headers(&self) -> Vec<String> {
+        self.range
+            .rows()
+            .nth(self.header_idx)
+            .map(|row| row.iter().map(cell_to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Rows below the header, paired with their 1-based sheet row number
+    fn data_rows(&self) -> impl Iterator<Item = (usize, &[Data])> {
+        self.range
+            .rows()
+            .enumerate()
+            .skip(self.header_idx + 1)
+            .map(move |(idx, row)| (self.row_number(idx), row))
+    }
+}
+
+/// Worksheets loaded from an input file, whatever its format
+struct LoadedInput {
+    sheets: Vec<LoadedSheet>,
+    file_type: String,
+    encoding: Option<EncodingInfo>,
+    /// The header is the first row of every sheet: the field names of JSON
+    /// records or a fixed-width layout, or the titles a PDF table was
+    /// rebuilt from
+    header_first: bool,
+    /// PDF rows whose layout was unclear
+    uncertain_rows: Vec<UncertainRow>,
+}
+
+/// Which worksheets of a workbook to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SheetScope {
+    /// The sheets chosen in `ParseOptions` (first sheet by default)
+    Selected,
+    /// Every sheet, for listing
+    All,
+}
+
+impl LoadedInput {
+    /// Pick the header row of every sheet: the `header_row` option if set,
+    /// otherwise the best-scoring of the first `HEADER_SCAN_ROWS` rows, so
+    /// title blocks (bank name, account holder, period) are skipped.
+    fn locate_headers(&mut self, options: Option<&ParseOptions>, score: impl Fn(&[String]) -> usize) {
+        let forced = if self.header_first { Some(1) } else { options.and_then(|o| o.header_row) };
+        for sheet in &mut self.sheets {
+            sheet.header_idx = match forced {
+                Some(row) => row.saturating_sub(1).saturating_sub(sheet.first_row()),
+                None => detect_header_idx(&sheet.range, &score),
+            };
+        }
+    }
+
+    /// Header row of the first sheet, after checking that every other
+    /// selected sheet shares the same layout
+    fn common_headers(&self) -> Result<Vec<String>, CoreError> {
+        let mut sheets = self.sheets.iter();
+        let first = match sheets.next() {
+            Some(sheet) => sheet,
+            None => return Ok(Vec::new()),
+        };
+        let headers = first.headers();
+
+        for sheet in sheets {
+            if !same_layout(&headers, &sheet.headers()) {
+                return Err(CoreError::ExcelParseError(format!(
+                    "Sheet '{}' has a different header layout from '{}'",
+                    sheet.name.as_deref().unwrap_or_default(),
+                    first.name.as_deref().unwrap_or_default(),
+                )));
+            }
+        }
+        Ok(headers)
+    }
+
+    fn metadata(&self, filename: &str) -> FileMetadata {
+        FileMetadata {
+            path: None,
+            filename: filename.to_string(),
+            row_count: self.sheets.iter().map(|s| s.range.height()).sum(),
+            column_count: self.sheets.iter().map(|s| s.range.width()).max().unwrap_or(0),
+            file_type: self.file_type.clone(),
+            encoding: self.encoding.clone(),
+            sheets: self.sheets.iter().filter_map(|s| s.name.clone()).collect(),
+            header_row: self.sheets.first().map(LoadedSheet::header_row),
+            headers: self.sheets.first().map(LoadedSheet::headers).unwrap_or_default(),
+            timezone: None,
+            report: ParseReport::default(),
+            profile: None,
+            sensitive_columns: Vec::new(),
+        }
+    }
+}
+
+/// Number of leading rows searched for the header row
+const HEADER_SCAN_ROWS: usize = 20;
+
+/// Index of the best-scoring row among the first `HEADER_SCAN_ROWS`
+/// (the earliest wins ties; row 0 if nothing scores)
+fn detect_header_idx(range: &Range<Data>, score: &impl Fn(&[String]) -> usize) -> usize {
+    best_header_idx(range.rows(), score)
+}
+
+/// `detect_header_idx` over rows that are not in a `Range` (streaming input)
+fn best_header_idx<'r>(rows: impl Iterator<Item = &'r [Data]>, score: &impl Fn(&[String]) -> usize) -> usize {
+    let mut best = (0, 0);
+    for (idx, row) in rows.take(HEADER_SCAN_ROWS).enumerate() {
+        let candidate: Vec<String> = row.iter().map(cell_to_string).collect();
+        let row_score = score(&candidate);
+        if row_score > best.1 {
+            best = (idx, row_score);
+        }
+    }
+    best.0
+}
+
+/// Header score used when the file role is unknown (metadata, header listing)
+fn any_role_header_score(headers: &[String]) -> usize {
+    header_map::file_a_header_score(headers, None).max(header_map::file_b_header_score(headers, None))
+}
+
+/// Compare two header rows, ignoring case, surrounding whitespace and trailing blanks
+pub(crate) fn same_layout(a: &[String], b: &[String]) -> bool {
+    let normalize = |headers: &[String]| -> Vec<String> {
+        let mut out: Vec<String> = headers.iter().map(|h| h.trim().to_lowercase()).collect();
+        while out.last().is_some_and(|h| h.is_empty()) {
+            out.pop();
+        }
+        out
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Load the requested worksheets (or the whole text file) into cell grids
+fn load_input(data: &[u8], options: Option<&ParseOptions>, scope: SheetScope) -> Result<LoadedInput, CoreError> {
+    let data = &*encrypted::unlock(data, options.and_then(|o| o.password.as_deref()))?;
+    match input_format(data, options) {
+        InputFormat::Workbook => load_workbook(data, options, scope),
+        InputFormat::Delimited => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let sheet = delimited::read_range(data, encoding_override)?;
+            Ok(LoadedInput {
+                sheets: vec![LoadedSheet::new(None, sheet.range)],
+                file_type: sheet.dialect.file_type().to_string(),
+                encoding: Some(sheet.encoding),
+                header_first: false,
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Json => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let sheet = json::read_range(data, encoding_override)?;
+            Ok(LoadedInput {
+                sheets: vec![LoadedSheet::new(None, sheet.range)],
+                file_type: sheet.file_type.to_string(),
+                encoding: Some(sheet.encoding),
+                header_first: true,
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Markup(kind) => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let document = markup::read(data, kind, encoding_override)?;
+            let tables = markup::select(document.tables, options, scope == SheetScope::All)?;
+            Ok(LoadedInput {
+                sheets: tables.into_iter().map(|(name, range)| LoadedSheet::new(Some(name), range)).collect(),
+                file_type: document.file_type.to_string(),
+                encoding: Some(document.encoding),
+                header_first: false,
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Pdf => {
+            let table = pdf_table::read(data, options)?;
+            Ok(LoadedInput {
+                sheets: table
+                    .pages
+                    .into_iter()
+                    .map(|page| LoadedSheet::numbered(Some(page.name), rows_to_range(page.rows), page.lines))
+                    .collect(),
+                file_type: "pdf".to_string(),
+                encoding: None,
+                header_first: true,
+                uncertain_rows: table.uncertain_rows,
+            })
+        }
+        InputFormat::FixedWidth => {
+            let layout = fixed_width_layout(options).expect("fixed-width input has a layout");
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let table = fixed_width::read(data, layout, encoding_override)?;
+            Ok(LoadedInput {
+                sheets: vec![LoadedSheet::numbered(None, rows_to_range(table.rows), table.lines)],
+                file_type: "fixed_width".to_string(),
+                encoding: Some(table.encoding),
+                header_first: true,
+                uncertain_rows: Vec::new(),
+            })
+        }
+    }
+}
+
+fn load_workbook(data: &[u8], options: Option<&ParseOptions>, scope: SheetScope) -> Result<LoadedInput, CoreError> {
+    let cursor = Cursor::new(data);
+    let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor)
+        .map_err(|e| CoreError::ExcelParseError(format!("Failed to open file: {}", e)))?;
+
+    let file_type = match &workbook {
+        Sheets::Xls(_) => "xls",
+        Sheets::Xlsx(_) => "xlsx",
+        Sheets::Xlsb(_) => "xlsb",
+        Sheets::Ods(_) => "ods",
+    };
+
+    let sheet_names = workbook.sheet_names();
+    if sheet_names.is_empty() {
+        return Err(CoreError::ExcelParseError("No sheets found".to_string()));
+    }
+    let selected = match scope {
+        SheetScope::All => sheet_names,
+        SheetScope::Selected => select_sheets(&sheet_names, options)?,
+    };
+
+    let mut sheets = Vec::with_capacity(selected.len());
+    for name in selected {
+        let range = workbook.worksheet_range(&name).map_err(|e| {
+            CoreError::ExcelParseError(format!("Failed to read sheet '{}': {}", name, e))
+        })?;
+        sheets.push(LoadedSheet::new(Some(name), range));
+    }
+
+    Ok(LoadedInput {
+        sheets,
+        file_type: file_type.to_string(),
+        encoding: None,
+        header_first: false,
+        uncertain_rows: Vec::new(),
+    })
+}
+
+/// Resolve the sheet selection in `options` against the workbook's sheet names
+fn select_sheets(sheet_names: &[String], options: Option<&ParseOptions>) -> Result<Vec<String>, CoreError> {
+    let options = match options {
+        Some(o) => o,
+        None => return Ok(sheet_names[..1].to_vec()),
+    };
+    if options.all_sheets {
+        return Ok(sheet_names.to_vec());
+    }
+    if options.sheets.is_empty() {
+        return Ok(sheet_names[..1].to_vec());
+    }
+
+    let mut selected: Vec<String> = Vec::with_capacity(options.sheets.len());
+    for selector in &options.sheets {
+        let name = match selector {
+            SheetSelector::Index(idx) => sheet_names.get(*idx).cloned().ok_or_else(|| {
+                CoreError::ExcelParseError(format!(
+                    "Sheet index {} out of range ({} sheets)",
+                    idx,
+                    sheet_names.len()
+                ))
+            })?,
+            SheetSelector::Name(name) => sheet_names
+                .iter()
+                .find(|n| n.trim() == name.trim())
+                .cloned()
+                .ok_or_else(|| CoreError::ExcelParseError(format!("Sheet not found: {}", name)))?,
+        };
+        if !selected.contains(&name) {
+            selected.push(name);
+        }
+    }
+    Ok(selected)
+}
+
+/// Build a calamine range from row-major cells (used by text formats)
+fn rows_to_range(rows: Vec<Vec<Data>>) -> Range<Data> {
+    let height = rows.len();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if height == 0 || width == 0 {
+        return Range::empty();
+    }
+
+    let mut range = Range::new((0, 0), ((height - 1) as u32, (width - 1) as u32));
+    for (row_idx, row) in rows.into_iter().enumerate() {
+        for (col_idx, value) in row.into_iter().enumerate() {
+            range.set_value((row_idx as u32, col_idx as u32), value);
+        }
+    }
+    range
 }
 
 /// Excel parser that works with both native and WASM
 pub struct Parser;
 
 impl Parser {
-    /// Parse transactions from File A bytes
+    /// Parse transactions from File A bytes (workbook, CSV/TSV, JSON/NDJSON,
+    /// HTML table, SpreadsheetML, text PDF, or a fixed-width report laid out
+    /// by the profile).
+    ///
+    /// When several sheets are selected they must share one header layout;
+    /// their rows are concatenated and tagged with the sheet name.
     pub fn parse_transactions_from_bytes(
         data: &[u8],
         filename: &str,
         mapping: Option<&HashMap<String, String>>,
+        options: Option<&ParseOptions>,
     ) -> Result<(Vec<Transaction>, FileMetadata), CoreError> {
-        let cursor = Cursor::new(data);
-        let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor).map_err(|e| {
-            CoreError::ExcelParseError(format!("Failed to open file: {}", e))
-        })?;
+        parse_records::<TransactionReader>(data, filename, mapping, options)
+    }
 
-        let sheet_name = workbook
-            .sheet_names()
-            .first()
-            .cloned()
-            .ok_or_else(|| CoreError::ExcelParseError("No sheets found".to_string()))?;
+    /// Parse IP records from File B bytes (workbook, CSV/TSV, JSON/NDJSON,
+    /// HTML table, SpreadsheetML, text PDF, or a fixed-width report laid out
+    /// by the profile).
+    ///
+    /// Multi-sheet selection behaves as for `parse_transactions_from_bytes`.
+    pub fn parse_ip_records_from_bytes(
+        data: &[u8],
+        filename: &str,
+        mapping: Option<&HashMap<String, String>>,
+        options: Option<&ParseOptions>,
+    ) -> Result<(Vec<IpRecord>, FileMetadata), CoreError> {
+        parse_records::<IpRecordReader>(data, filename, mapping, options)
+    }
 
-        let range = workbook
-            .worksheet_range(&sheet_name)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to read sheet: {}", e)))?;
+    /// Stream transactions from File A bytes without loading whole sheets.
+    ///
+    /// Records are handed to `on_chunk` in batches of `chunk_size` together
+    /// with the progress so far; the last call carries the remaining records
+    /// (possibly none) at a fraction of 1.0. The records and report match
+    /// `parse_transactions_from_bytes`, except that CSV rows are padded only
+    /// to the header width and a header mismatch in a later sheet is
+    /// reported after the earlier sheets' records were delivered.
+    pub fn stream_transactions_from_bytes(
+        data: &[u8],
+        filename: &str,
+        mapping: Option<&HashMap<String, String>>,
+        options: Option<&ParseOptions>,
+        chunk_size: usize,
+        on_chunk: impl FnMut(Vec<Transaction>, &ParseProgress),
+    ) -> Result<FileMetadata, CoreError> {
+        stream::stream_records::<TransactionReader>(data, filename, mapping, options, chunk_size, on_chunk)
+    }
 
-        let row_count = range.height();
-        let col_count = range.width();
+    /// Stream IP records from File B bytes without loading whole sheets, so
+    /// logs with millions of rows can be indexed chunk by chunk. Behaves as
+    /// `stream_transactions_from_bytes`.
+    pub fn stream_ip_records_from_bytes(
+        data: &[u8],
+        filename: &str,
+        mapping: Option<&HashMap<String, String>>,
+        options: Option<&ParseOptions>,
+        chunk_size: usize,
+        on_chunk: impl FnMut(Vec<IpRecord>, &ParseProgress),
+    ) -> Result<FileMetadata, CoreError> {
+        stream::stream_records::<IpRecordReader>(data, filename, mapping, options, chunk_size, on_chunk)
+    }
 
-        let mut transactions = Vec::new();
-        let headers: Vec<String> = range
-            .rows()
-            .next()
-            .map(|row| row.iter().map(cell_to_string).collect())
-            .unwrap_or_default();
-        
-        let columns = match header_map::validate_file_a_headers(&headers, mapping) {
-            Ok(cols) => cols,
-            Err(missing) => return Err(CoreError::ExcelParseError(format!("Missing required columns: {}", missing.join(", ")))),
+    /// Pick the stored profile of `kind` whose header fingerprint best matches
+    /// the file. The first `HEADER_SCAN_ROWS` rows of the first selected sheet
+    /// are tried, so a title block above the headers does not prevent a match.
+    pub fn detect_profile<'a>(
+        data: &[u8],
+        profiles: &'a [FormatProfile],
+        kind: ProfileKind,
+        options: Option<&ParseOptions>,
+    ) -> Result<Option<&'a FormatProfile>, CoreError> {
+        let input = load_input(data, options, SheetScope::Selected)?;
+        let sheet = match input.sheets.first() {
+            Some(sheet) => sheet,
+            None => return Ok(None),
         };
 
-        for (row_idx, row) in range.rows().enumerate().skip(1) {
-            if row.is_empty() || row.iter().all(|c| c.is_empty()) {
-                continue;
+        let mut best: Option<(&FormatProfile, usize)> = None;
+        for row in sheet.range.rows().take(HEADER_SCAN_ROWS) {
+            let candidate: Vec<String> = row.iter().map(cell_to_string).collect();
+            if let Some((profile, score)) = profile::best_match(profiles, kind, &candidate) {
+                if best.is_none_or(|(_, s)| score > s) {
+                    best = Some((profile, score));
+                }
             }
+        }
+        Ok(best.map(|(profile, _)| profile))
+    }
 
-            let timestamp = extract_cell_as_string(row.get(columns.timestamp));
-            let account = extract_cell_as_string(row.get(columns.account));
+    /// Required columns of a File A or File B that cannot be resolved, each
+    /// with the most similar headers as suggestions. Empty when the file
+    /// would parse with the given mapping.
+    pub fn missing_columns_from_bytes(
+        data: &[u8],
+        kind: ProfileKind,
+        mapping: Option<&HashMap<String, String>>,
+        options: Option<&ParseOptions>,
+    ) -> Result<Vec<MissingColumn>, CoreError> {
+        let options = options.map(ParseOptions::with_profile_defaults);
+        let options = options.as_ref();
+        let profile = profile_of(options, kind)?;
+        let mut input = load_input(data, options, SheetScope::Selected)?;
+        input.locate_headers(options, |h| {
+            let mapping = profile_mapping(profile, mapping, h);
+            match kind {
+                ProfileKind::Transactions => header_map::file_a_header_score(h, mapping.as_ref()),
+                ProfileKind::IpLog => header_map::file_b_header_score(h, mapping.as_ref()),
+            }
+        });
+        let headers = input.common_headers()?;
+        let mapping = profile_mapping(profile, mapping, &headers);
 
-            if timestamp.is_empty() || account.is_empty() {
-                continue;
+        let missing = match kind {
+            ProfileKind::Transactions => {
+                header_map::validate_file_a_layout(&headers, mapping.as_ref(), profile.and_then(|p| p.amount_mode)).err()
             }
+            ProfileKind::IpLog => header_map::validate_file_b_headers(&headers, mapping.as_ref()).err(),
+        };
+        Ok(missing.unwrap_or_default())
+    }
 
-            let expense = extract_cell_as_f64(row.get(columns.expense));
-            let income = extract_cell_as_f64(row.get(columns.income));
-            let raw_columns: Vec<String> = row.iter().map(cell_to_string).collect();
+    /// Get file metadata from bytes
+    pub fn get_metadata_from_bytes(
+        data: &[u8],
+        filename: &str,
+        options: Option<&ParseOptions>,
+    ) -> Result<FileMetadata, CoreError> {
+        let options = options.map(ParseOptions::with_profile_defaults);
+        let options = options.as_ref();
+        let mut input = load_input(data, options, SheetScope::Selected)?;
+        input.locate_headers(options, any_role_header_score);
+        Ok(input.metadata(filename))
+    }
 
-            let transaction = Transaction::new(
-                timestamp,
-                account,
-                income,
-                expense,
-                raw_columns,
-                row_idx + 1,
-            );
+    /// Get raw headers of every sheet, so the caller can choose which to parse.
+    ///
+    /// Text input has a single table, reported under the file name.
+    pub fn get_headers_from_bytes(
+        data: &[u8],
+        filename: &str,
+        options: Option<&ParseOptions>,
+    ) -> Result<Vec<SheetHeaders>, CoreError> {
+        let options = options.map(ParseOptions::with_profile_defaults);
+        let options = options.as_ref();
+        let mut input = load_input(data, options, SheetScope::All)?;
+        input.locate_headers(options, any_role_header_score);
+
+        Ok(input
+            .sheets
+            .iter()
+            .enumerate()
+            .map(|(index, sheet)| SheetHeaders {
+                index,
+                name: sheet.name.clone().unwrap_or_else(|| filename.to_string()),
+                header_row: sheet.header_row(),
+                headers: sheet.headers(),
+            })
+            .collect())
+    }
+}
 
-            transactions.push(transaction);
+/// Load the selected sheets and read every data row with `R`
+fn parse_records<R: RecordReader>(
+    data: &[u8],
+    filename: &str,
+    mapping: Option<&HashMap<String, String>>,
+    options: Option<&ParseOptions>,
+) -> Result<(Vec<R::Record>, FileMetadata), CoreError> {
+    let options = options.map(ParseOptions::with_profile_defaults);
+    let options = options.as_ref();
+    let profile = profile_of(options, R::KIND)?;
+    let mut input = load_input(data, options, SheetScope::Selected)?;
+    input.locate_headers(options, |h| R::header_score(h, profile_mapping(profile, mapping, h).as_ref()));
+    let headers = input.common_headers()?;
+    let mapping = profile_mapping(profile, mapping, &headers);
+    let timestamps = TimestampRule::from_options(options)?;
+    let timezone = timestamps.zone.map(|z| z.to_string());
+    let reader = R::new(headers, mapping.as_ref(), profile, timestamps)?;
+
+    let mut records = Vec::new();
+    let mut report = ParseReport {
+        uncertain_rows: std::mem::take(&mut input.uncertain_rows),
+        ..Default::default()
+    };
+    for sheet in &input.sheets {
+        for (row_number, row) in sheet.data_rows() {
+            for mut record in reader.read(&sheet.name, row_number, row, &mut report) {
+                record.set_source_file(filename);
+                records.push(record);
+            }
         }
+    }
 
-        let metadata = FileMetadata {
-            path: None,
-            filename: filename.to_string(),
-            row_count,
-            column_count: col_count,
-            file_type: "xlsx".to_string(),
-        };
+    let mut metadata = input.metadata(filename);
+    metadata.report = report;
+    metadata.timezone = timezone;
+    apply_profile(&mut metadata, profile, reader.headers());
+    Ok((records, metadata))
+}
 
-        Ok((transactions, metadata))
-    }
+/// Turns the data rows of one kind of file into records, once its header
+/// row is known. Shared by the loading and the streaming parser.
+trait RecordReader: Sized {
+    type Record: SourcedRecord;
+    const KIND: ProfileKind;
 
-    /// Parse IP records from File B bytes
-    pub fn parse_ip_records_from_bytes(
-        data: &[u8],
-        filename: &str,
+    /// How well a candidate header row fits this kind of file
+    fn header_score(headers: &[String], mapping: Option<&HashMap<String, String>>) -> usize;
+
+    /// Resolve the required columns against the header row, following the
+    /// profile's layout where it fixes one
+    fn new(
+        headers: Vec<String>,
         mapping: Option<&HashMap<String, String>>,
-    ) -> Result<(Vec<IpRecord>, FileMetadata), CoreError> {
-        let cursor = Cursor::new(data);
-        let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor).map_err(|e| {
-            CoreError::ExcelParseError(format!("Failed to open file: {}", e))
-        })?;
+        profile: Option<&FormatProfile>,
+        timestamps: TimestampRule,
+    ) -> Result<Self, CoreError>;
 
-        let sheet_name = workbook
-            .sheet_names()
-            .first()
-            .cloned()
-            .ok_or_else(|| CoreError::ExcelParseError("No sheets found".to_string()))?;
+    fn headers(&self) -> &[String];
 
-        let range = workbook
-            .worksheet_range(&sheet_name)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to read sheet: {}", e)))?;
+    /// Build the records of a non-blank data row (one per IP address of a
+    /// multi-address IP cell), or record why there are none
+    fn record(&self, sheet: &Option<String>, row_number: usize, row: &[Data], report: &mut ParseReport) -> Vec<Self::Record>;
+
+    /// Read a data row: blank rows are ignored, others are counted and
+    /// checked for error cells before the records are built
+    fn read(&self, sheet: &Option<String>, row_number: usize, row: &[Data], report: &mut ParseReport) -> Vec<Self::Record> {
+        if row.iter().all(|c| c.is_empty()) {
+            return Vec::new();
+        }
+        report.rows_read += 1;
+        report_error_cells(report, sheet, row_number, row, self.headers());
+        self.record(sheet, row_number, row, report)
+    }
+}
 
-        let row_count = range.height();
-        let col_count = range.width();
+/// Reads File A rows into `Transaction`s
+struct TransactionReader {
+    headers: Vec<String>,
+    columns: header_map::FileAColumns,
+    timestamps: TimestampRule,
+}
 
-        let mut records = Vec::new();
-        let headers: Vec<String> = range
-            .rows()
-            .next()
-            .map(|row| row.iter().map(cell_to_string).collect())
-            .unwrap_or_default();
-        
-        let columns = match header_map::validate_file_b_headers(&headers, mapping) {
-            Ok(cols) => cols,
-            Err(missing) => return Err(CoreError::ExcelParseError(format!("Missing required columns: {}", missing.join(", ")))),
+impl RecordReader for TransactionReader {
+    type Record = Transaction;
+    const KIND: ProfileKind = ProfileKind::Transactions;
+
+    fn header_score(headers: &[String], mapping: Option<&HashMap<String, String>>) -> usize {
+        header_map::file_a_header_score(headers, mapping)
+    }
+
+    fn new(
+        headers: Vec<String>,
+        mapping: Option<&HashMap<String, String>>,
+        profile: Option<&FormatProfile>,
+        timestamps: TimestampRule,
+    ) -> Result<Self, CoreError> {
+        let amount_mode = profile.and_then(|p| p.amount_mode);
+        let columns = header_map::validate_file_a_layout(&headers, mapping, amount_mode)
+            .map_err(CoreError::MissingColumns)?;
+        Ok(Self { headers, columns, timestamps })
+    }
+
+    fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    fn record(&self, sheet: &Option<String>, row_number: usize, row: &[Data], report: &mut ParseReport) -> Vec<Transaction> {
+        let columns = &self.columns;
+        let timestamp = extract_timestamp(row, columns.timestamp, columns.time);
+        let account = extract_cell_as_string(row.get(columns.account));
+
+        let missing = if timestamp.is_empty() {
+            Some(SkipReason::MissingTimestamp)
+        } else if account.is_empty() {
+            Some(SkipReason::MissingAccount)
+        } else {
+            None
         };
+        if let Some(reason) = missing {
+            report.skipped_rows.push(SkippedRow { sheet: sheet.clone(), row: row_number, reason });
+            return Vec::new();
+        }
 
-        for (row_idx, row) in range.rows().enumerate().skip(1) {
-            if row.is_empty() || row.iter().all(|c| c.is_empty()) {
-                continue;
+        let mut unparsed = |col: usize| {
+            report.unparsed_amounts.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: self.headers.get(col).cloned().unwrap_or_default(),
+                value: extract_cell_as_string(row.get(col)),
+            });
+        };
+        let mut amount = |col: usize| {
+            extract_cell_as_f64(row.get(col)).unwrap_or_else(|_| {
+                unparsed(col);
+                None
+            })
+        };
+        let mut unknown_direction = None;
+        let (expense, income) = match columns.amounts {
+            // The column already says which side the money is on; a sign or
+            // accounting brackets ("(3,000)") only repeat it
+            header_map::AmountColumns::Split { expense, income } => {
+                (amount(expense).map(f64::abs), amount(income).map(f64::abs))
+            }
+            header_map::AmountColumns::Signed { amount: col } => amount::split_signed(amount(col)),
+            header_map::AmountColumns::Indicator { amount: col, indicator } => {
+                let value = amount(col);
+                let marker = extract_cell_as_string(row.get(indicator));
+                match amount::parse_direction(&marker) {
+                    Some(Direction::Debit) => (value.map(f64::abs), None),
+                    Some(Direction::Credit) => (None, value.map(f64::abs)),
+                    None => {
+                        // Unknown marker: fall back to the amount's own sign
+                        if !marker.trim().is_empty() {
+                            unknown_direction = Some((indicator, marker));
+                        }
+                        amount::split_signed(value)
+                    }
+                }
             }
+        };
+        let details = &columns.details;
+        let balance = details.balance.and_then(|col| {
+            extract_cell_as_number(row.get(col)).unwrap_or_else(|_| {
+                unparsed(col);
+                None
+            })
+        });
+        if let Some((col, marker)) = unknown_direction {
+            report.unknown_directions.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: self.headers.get(col).cloned().unwrap_or_default(),
+                value: marker,
+            });
+        }
+        let text = |col: Option<usize>| {
+            col.map(|col| extract_cell_as_string(row.get(col))).filter(|v| !v.is_empty())
+        };
+        let raw_columns: Vec<String> = row.iter().map(cell_to_string).collect();
+
+        let mut transaction = Transaction::new(
+            timestamp,
+            account,
+            income,
+            expense,
+            raw_columns,
+            row_number,
+        );
+        transaction.sheet = sheet.clone();
+        transaction.counterparty_account = text(details.counterparty_account);
+        transaction.counterparty_name = text(details.counterparty_name);
+        transaction.balance = balance;
+        transaction.currency = text(details.currency).map(|c| c.to_uppercase());
+        transaction.memo = text(details.memo);
+        transaction.datetime = self.timestamps.resolve(&transaction.timestamp, transaction.datetime);
+        if transaction.datetime.is_none() {
+            report.unparsed_timestamps.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: self.headers.get(columns.timestamp).cloned().unwrap_or_default(),
+                value: transaction.timestamp.clone(),
+            });
+        }
+        vec![transaction]
+    }
+}
 
-            let timestamp = extract_cell_as_string(row.get(columns.timestamp));
-            let account = extract_cell_as_string(row.get(columns.account));
-            let ip_address = extract_cell_as_string(row.get(columns.ip_address));
+/// Reads File B rows into `IpRecord`s
+struct IpRecordReader {
+    headers: Vec<String>,
+    columns: header_map::FileBColumns,
+    timestamps: TimestampRule,
+}
 
-            if timestamp.is_empty() || account.is_empty() || ip_address.is_empty() {
-                continue;
-            }
+impl RecordReader for IpRecordReader {
+    type Record = IpRecord;
+    const KIND: ProfileKind = ProfileKind::IpLog;
+
+    fn header_score(headers: &[String], mapping: Option<&HashMap<String, String>>) -> usize {
+        header_map::file_b_header_score(headers, mapping)
+    }
+
+    fn new(
+        headers: Vec<String>,
+        mapping: Option<&HashMap<String, String>>,
+        _profile: Option<&FormatProfile>,
+        timestamps: TimestampRule,
+    ) -> Result<Self, CoreError> {
+        let columns = header_map::validate_file_b_headers(&headers, mapping).map_err(CoreError::MissingColumns)?;
+        Ok(Self { headers, columns, timestamps })
+    }
+
+    fn headers(&self) -> &[String] {
+        &self.headers
+    }
 
-            let record = IpRecord::new(timestamp, account, ip_address, row_idx + 1);
-            records.push(record);
+    fn record(&self, sheet: &Option<String>, row_number: usize, row: &[Data], report: &mut ParseReport) -> Vec<IpRecord> {
+        let columns = &self.columns;
+        let timestamp = extract_timestamp(row, columns.timestamp, columns.time);
+        let account = extract_cell_as_string(row.get(columns.account));
+        let ip_address = extract_cell_as_string(row.get(columns.ip_address));
+
+        let missing = if timestamp.is_empty() {
+            Some(SkipReason::MissingTimestamp)
+        } else if account.is_empty() {
+            Some(SkipReason::MissingAccount)
+        } else if ip_address.is_empty() {
+            Some(SkipReason::MissingIpAddress)
+        } else {
+            None
+        };
+        if let Some(reason) = missing {
+            report.skipped_rows.push(SkippedRow { sheet: sheet.clone(), row: row_number, reason });
+            return Vec::new();
         }
 
-        let metadata = FileMetadata {
-            path: None,
-            filename: filename.to_string(),
-            row_count,
-            column_count: col_count,
-            file_type: "xlsx".to_string(),
+        let cell = ip::parse_ip_cell(&ip_address);
+        if !cell.invalid.is_empty() {
+            report.invalid_ips.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: self.headers.get(columns.ip_address).cloned().unwrap_or_default(),
+                value: ip_address.clone(),
+            });
+        }
+
+        let text = |col: Option<usize>| {
+            col.map(|col| extract_cell_as_string(row.get(col))).filter(|v| !v.is_empty())
         };
+        let login = &columns.login;
+        let mut record = IpRecord::new(timestamp, account, ip_address, row_number);
+        record.sheet = sheet.clone();
+        record.login = LoginDetails {
+            device_id: text(login.device_id),
+            user_agent: text(login.user_agent),
+            channel: text(login.channel),
+            login_result: text(login.login_result),
+            session_id: text(login.session_id),
+        };
+        record.datetime = self.timestamps.resolve(&record.timestamp, record.datetime);
+        if record.datetime.is_none() {
+            // IpMatcher ignores records without a parsed time
+            report.unparsed_timestamps.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: self.headers.get(columns.timestamp).cloned().unwrap_or_default(),
+                value: record.timestamp.clone(),
+            });
+        }
+        if cell.addresses.is_empty() {
+            // Kept with the text as written, like a row with an unparsed time
+            return vec![record];
+        }
+        // One record per address of the cell
+        cell.addresses
+            .into_iter()
+            .map(|address| {
+                let mut record = record.clone();
+                record.ip = Some(address);
+                record.ip_address = address.to_string();
+                record
+            })
+            .collect()
+    }
+}
 
-        Ok((records, metadata))
+/// Profile from the parse options, checked against the kind of file being parsed
+fn profile_of(options: Option<&ParseOptions>, kind: ProfileKind) -> Result<Option<&FormatProfile>, CoreError> {
+    match options.and_then(|o| o.profile.as_ref()) {
+        Some(profile) if profile.kind != kind => Err(CoreError::InvalidData(format!(
+            "Profile '{}' describes {:?} files, not {:?}",
+            profile.name, profile.kind, kind
+        ))),
+        Some(profile) => {
+            profile.validate()?;
+            Ok(Some(profile))
+        }
+        None => Ok(None),
     }
+}
 
-    /// Get file metadata from bytes
-    pub fn get_metadata_from_bytes(data: &[u8], filename: &str) -> Result<FileMetadata, CoreError> {
-        let cursor = Cursor::new(data);
-        let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to open file: {}", e)))?;
-
-        let sheet_name = workbook
-            .sheet_names()
-            .first()
-            .cloned()
-            .ok_or_else(|| CoreError::ExcelParseError("No sheets found".to_string()))?;
-
-        let range = workbook
-            .worksheet_range(&sheet_name)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to read sheet: {}", e)))?;
-
-        Ok(FileMetadata {
-            path: None,
-            filename: filename.to_string(),
-            row_count: range.height(),
-            column_count: range.width(),
-            file_type: "xlsx".to_string(),
-        })
+/// Column mapping for a header row: the profile's aliases resolved against
+/// `headers`, with explicit mapping entries taking precedence
+fn profile_mapping(
+    profile: Option<&FormatProfile>,
+    mapping: Option<&HashMap<String, String>>,
+    headers: &[String],
+) -> Option<HashMap<String, String>> {
+    let profile = match profile {
+        Some(profile) => profile,
+        None => return mapping.cloned(),
+    };
+    let mut merged = profile.mapping(headers);
+    if let Some(mapping) = mapping {
+        merged.extend(mapping.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    Some(merged)
+}
+
+/// Record the applied profile and its sensitive columns in the metadata
+fn apply_profile(metadata: &mut FileMetadata, profile: Option<&FormatProfile>, headers: &[String]) {
+    if let Some(profile) = profile {
+        metadata.profile = Some(profile.name.clone());
+        metadata.sensitive_columns = profile.sensitive_indices(headers);
+    }
+}
+
+/// How a file's timestamp text becomes a comparable time: the format
+/// override and the declared zone from the parse options
+#[derive(Debug, Clone, Default)]
+struct TimestampRule {
+    format: Option<String>,
+    zone: Option<timestamp::FileTimeZone>,
+}
+
+impl TimestampRule {
+    /// Validated rule from the parse options
+    fn from_options(options: Option<&ParseOptions>) -> Result<Self, CoreError> {
+        let format = options.and_then(|o| o.timestamp_format.clone());
+        if let Some(format) = &format {
+            timestamp::validate_format(format)?;
+        }
+        let zone = match options.and_then(|o| o.timezone.as_deref()) {
+            Some(declared) => Some(timestamp::FileTimeZone::parse(declared)?),
+            None => None,
+        };
+        Ok(Self { format, zone })
     }
 
-    /// Get raw headers from bytes
-    pub fn get_headers_from_bytes(data: &[u8], _filename: &str) -> Result<Vec<String>, CoreError> {
-        let cursor = Cursor::new(data);
-        let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to open file: {}", e)))?;
+    /// Time of a record whose text parsed to `parsed` with the built-in
+    /// layouts: re-read with the format override, then normalized to UTC
+    fn resolve(&self, text: &str, parsed: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+        let datetime = match &self.format {
+            Some(format) => timestamp::parse_timestamp(text, Some(format)),
+            None => parsed,
+        };
+        match self.zone {
+            Some(zone) => datetime.map(|dt| zone.to_utc(dt)),
+            None => datetime,
+        }
+    }
+}
 
-        let sheet_name = workbook
-            .sheet_names()
-            .first()
-            .cloned()
-            .ok_or_else(|| CoreError::ExcelParseError("No sheets found".to_string()))?;
+/// Timestamp text of a row, joining separate date and time cells when the
+/// file splits them
+fn extract_timestamp(row: &[Data], timestamp: usize, time: Option<usize>) -> String {
+    let time = match time {
+        Some(idx) => row.get(idx).map(cell_to_time_string).unwrap_or_default(),
+        None => return extract_cell_as_string(row.get(timestamp)),
+    };
+    let date = row.get(timestamp).map(cell_to_date_string).unwrap_or_default();
+
+    if date.is_empty() || time.is_empty() {
+        return date;
+    }
+    // Some exports repeat the full timestamp in the time column
+    if timestamp::parse_timestamp(&time, None).is_some() {
+        return time;
+    }
+    format!("{} {}", date, time)
+}
 
-        let range = workbook
-            .worksheet_range(&sheet_name)
-            .map_err(|e| CoreError::ExcelParseError(format!("Failed to read sheet: {}", e)))?;
+/// Largest number treated as an Excel date serial in a date column (year 2173);
+/// bigger numbers are compact dates such as 20240115
+const MAX_DATE_SERIAL: f64 = 100_000.0;
 
-        let headers: Vec<String> = range
-            .rows()
-            .next()
-            .map(|row| row.iter().map(cell_to_string).collect())
-            .unwrap_or_default();
-            
-        Ok(headers)
+/// Date cell as text; Excel serials are rendered as `YYYY-MM-DD`
+fn cell_to_date_string(data: &Data) -> String {
+    let serial = match data {
+        Data::DateTime(dt) => Some(dt.as_f64()),
+        Data::Float(f) if *f >= 1.0 && *f < MAX_DATE_SERIAL => Some(*f),
+        Data::Int(i) if *i >= 1 && (*i as f64) < MAX_DATE_SERIAL => Some(*i as f64),
+        _ => None,
+    };
+    match serial.and_then(|s| excel_date_to_datetime(s.floor())) {
+        Some(dt) => dt.format("%Y-%m-%d").to_string(),
+        None => cell_to_string(data),
+    }
+}
+
+/// Time cell as text; Excel day fractions are rendered as `HH:MM:SS` (plus
+/// milliseconds when present) and compact numbers such as 90322 are
+/// zero-padded to `090322`
+fn cell_to_time_string(data: &Data) -> String {
+    let fraction = match data {
+        Data::DateTime(dt) => Some(dt.as_f64().fract()),
+        Data::Float(f) if f.fract() != 0.0 || *f == 0.0 => Some(f.fract()),
+        _ => None,
+    };
+    if let Some(fraction) = fraction {
+        let millis = ((fraction * 86_400_000.0).round() as u32) % 86_400_000;
+        let time = chrono::NaiveTime::from_num_seconds_from_midnight_opt(millis / 1000, millis % 1000 * 1_000_000);
+        if let Some(time) = time {
+            return timestamp::format_time(&time);
+        }
+    }
+
+    let text = cell_to_string(data).trim().to_string();
+    if text.bytes().all(|b| b.is_ascii_digit()) && matches!(text.len(), 3 | 5) {
+        format!("0{}", text)
+    } else {
+        text
     }
 }
 
@@ -328,7 +1573,7 @@ fn cell_to_string(data: &Data) -> String {
         Data::Float(f) => {
             if *f > 1.0 && *f < 2958466.0 && f.fract() != 0.0 {
                 if let Some(dt) = excel_date_to_datetime(*f) {
-                    return dt.format("%Y-%m-%d %H:%M:%S").to_string();
+                    return timestamp::format_datetime(&dt);
                 }
             }
             f.to_string()
@@ -338,7 +1583,7 @@ fn cell_to_string(data: &Data) -> String {
         Data::DateTime(excel_dt) => {
             let serial = excel_dt.as_f64();
             if let Some(dt) = excel_date_to_datetime(serial) {
-                dt.format("%Y-%m-%d %H:%M:%S").to_string()
+                timestamp::format_datetime(&dt)
             } else {
                 excel_dt.to_string()
             }
@@ -349,24 +1594,40 @@ fn cell_to_string(data: &Data) -> String {
     }
 }
 
-fn extract_cell_as_f64(cell: Option<&Data>) -> Option<f64> {
-    match cell {
-        Some(Data::Float(f)) => {
-            if *f != 0.0 {
-                Some(*f)
-            } else {
-                None
-            }
-        }
-        Some(Data::Int(i)) => {
-            if *i != 0 {
-                Some(*i as f64)
-            } else {
-                None
-            }
-        }
-        Some(Data::String(s)) => s.trim().parse().ok().filter(|v: &f64| *v != 0.0),
+/// Amount in a cell, with zero read as no amount. Text that is not a
+/// readable amount is returned as an error; error cells read as no amount
+/// and are reported by `report_error_cells`.
+fn extract_cell_as_f64(cell: Option<&Data>) -> Result<Option<f64>, CoreError> {
+    Ok(extract_cell_as_number(cell)?.filter(|v| *v != 0.0))
+}
+
+/// Number in a cell, keeping zero (e.g. an emptied account balance)
+fn extract_cell_as_number(cell: Option<&Data>) -> Result<Option<f64>, CoreError> {
+    Ok(match cell {
+        Some(Data::Float(f)) => Some(*f),
+        Some(Data::Int(i)) => Some(*i as f64),
+        Some(Data::String(s)) => amount::parse_amount(s)?,
         _ => None,
+    })
+}
+
+/// Add the spreadsheet error cells (`#N/A`, `#VALUE!`, ...) of a data row to the report
+fn report_error_cells(
+    report: &mut ParseReport,
+    sheet: &Option<String>,
+    row_number: usize,
+    row: &[Data],
+    headers: &[String],
+) {
+    for (col, cell) in row.iter().enumerate() {
+        if let Data::Error(_) = cell {
+            report.error_cells.push(UnparsedCell {
+                sheet: sheet.clone(),
+                row: row_number,
+                column: headers.get(col).cloned().unwrap_or_default(),
+                value: cell_to_string(cell),
+            });
+        }
     }
 }
 
@@ -379,50 +1640,50 @@ mod native {
 
     impl Parser {
         /// Parse transactions from file path (native only)
-        pub fn parse_transactions(path: &Path, mapping: Option<&std::collections::HashMap<String, String>>) -> Result<Vec<Transaction>, CoreError> {
+        pub fn parse_transactions(path: &Path, mapping: Option<&std::collections::HashMap<String, String>>, options: Option<&ParseOptions>) -> Result<Vec<Transaction>, CoreError> {
             let data = fs::read(path).map_err(|e| {
                 CoreError::ExcelParseError(format!("Failed to read file: {}", e))
             })?;
             let filename = path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
-            let (transactions, _) = Parser::parse_transactions_from_bytes(&data, &filename, mapping)?;
+            let (transactions, _) = Parser::parse_transactions_from_bytes(&data, &filename, mapping, options)?;
             Ok(transactions)
         }
 
         /// Parse IP records from file path (native only)
-        pub fn parse_ip_records(path: &Path, mapping: Option<&std::collections::HashMap<String, String>>) -> Result<Vec<IpRecord>, CoreError> {
+        pub fn parse_ip_records(path: &Path, mapping: Option<&std::collections::HashMap<String, String>>, options: Option<&ParseOptions>) -> Result<Vec<IpRecord>, CoreError> {
             let data = fs::read(path).map_err(|e| {
                 CoreError::ExcelParseError(format!("Failed to read file: {}", e))
             })?;
             let filename = path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
-            let (records, _) = Parser::parse_ip_records_from_bytes(&data, &filename, mapping)?;
+            let (records, _) = Parser::parse_ip_records_from_bytes(&data, &filename, mapping, options)?;
             Ok(records)
         }
 
         /// Get file metadata from path (native only)
-        pub fn get_file_metadata(path: &Path) -> Result<(usize, usize), CoreError> {
+        pub fn get_file_metadata(path: &Path, options: Option<&ParseOptions>) -> Result<(usize, usize), CoreError> {
             let data = fs::read(path).map_err(|e| {
                 CoreError::ExcelParseError(format!("Failed to read file: {}", e))
             })?;
             let filename = path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
-            let metadata = Parser::get_metadata_from_bytes(&data, &filename)?;
+            let metadata = Parser::get_metadata_from_bytes(&data, &filename, options)?;
             Ok((metadata.row_count, metadata.column_count))
         }
 
         /// Get headers from file path (native only)
-        pub fn get_file_headers(path: &Path) -> Result<Vec<String>, CoreError> {
+        pub fn get_file_headers(path: &Path, options: Option<&ParseOptions>) -> Result<Vec<SheetHeaders>, CoreError> {
             let data = fs::read(path).map_err(|e| {
                 CoreError::ExcelParseError(format!("Failed to read file: {}", e))
             })?;
             let filename = path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
-            Parser::get_headers_from_bytes(&data, &filename)
+            Parser::get_headers_from_bytes(&data, &filename, options)
         }
     }
 }
@@ -441,4 +1702,25 @@ mod tests {
         assert_eq!(cell_to_string(&Data::String("test".to_string())), "test");
         assert_eq!(cell_to_string(&Data::Empty), "");
     }
+
+    #[test]
+    fn test_error_cells_are_reported_not_parsed() {
+        use calamine::CellErrorType;
+
+        let row = vec![
+            Data::String("2024-01-15 10:30:00".to_string()),
+            Data::Error(CellErrorType::NA),
+            Data::Float(100.0),
+        ];
+        assert_eq!(extract_cell_as_f64(row.get(1)).unwrap(), None);
+
+        let sheet = Some("Sheet1".to_string());
+        let headers = vec!["交易時間".to_string(), "支出金額".to_string(), "存入金額".to_string()];
+        let mut report = ParseReport::default();
+        report_error_cells(&mut report, &sheet, 2, &row, &headers);
+
+        assert_eq!(report.error_cells.len(), 1);
+        assert_eq!(report.error_cells[0].column, "支出金額");
+        assert_eq!(report.error_cells[0].sheet.as_deref(), Some("Sheet1"));
+    }
 }
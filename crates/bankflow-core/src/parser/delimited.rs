@@ -0,0 +1,245 @@
+//! Delimited text (CSV/TSV) input
+//!
+//! Sniffs the delimiter and quote character from the first lines of the
+//! file, then loads every record into a calamine `Range` so the rest of the
+//! parser can treat it exactly like a worksheet.
+
+use super::{encoding, rows_to_range};
+use crate::error::CoreError;
+use crate::models::EncodingInfo;
+use calamine::{Data, Range};
+
+/// Delimiters tried during sniffing, in order of preference on ties
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Quote characters tried during sniffing
+const QUOTE_CANDIDATES: [u8; 2] = [b'"', b'\''];
+
+/// Number of non-empty lines inspected when sniffing
+const SNIFF_LINES: usize = 20;
+
+/// Delimiter and quote character of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Dialect {
+    /// File type reported in `FileMetadata`
+    pub fn file_type(&self) -> &'static str {
+        if self.delimiter == b'\t' {
+            "tsv"
+        } else {
+            "csv"
+        }
+    }
+}
+
+/// Delimited text loaded into a cell grid
+pub struct DelimitedSheet {
+    pub range: Range<Data>,
+    pub dialect: Dialect,
+    pub encoding: EncodingInfo,
+}
+
+/// Decode, sniff and read delimited text into a cell grid
+pub fn read_range(data: &[u8], encoding_override: Option<&str>) -> Result<DelimitedSheet, CoreError> {
+    let (text, encoding) = encoding::decode(data, encoding_override)?;
+    let dialect = sniff_dialect(&text);
+
+    let mut rows = Vec::new();
+    for_each_row(&text, dialect, |_, row, _| {
+        rows.push(row);
+        Ok(())
+    })?;
+
+    Ok(DelimitedSheet {
+        range: rows_to_range(rows),
+        dialect,
+        encoding,
+    })
+}
+
+/// Read delimited text one record at a time, passing each record's 1-based
+/// line number, its cells and the byte offset reached. Blank lines are
+/// passed on as empty rows so row numbers match file lines.
+pub fn for_each_row(
+    text: &str,
+    dialect: Dialect,
+    mut visit: impl FnMut(usize, Vec<Data>, usize) -> Result<(), CoreError>,
+) -> Result<(), CoreError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .from_reader(text.as_bytes());
+
+    let bytes = text.as_bytes();
+    let (mut line, mut scanned, mut emitted) = (0, 0, 0);
+    let mut record = csv::StringRecord::new();
+    loop {
+        let more = reader.read_record(&mut record).map_err(|e| {
+            CoreError::ExcelParseError(format!("Failed to read delimited text: {}", e))
+        })?;
+        if !more {
+            return Ok(());
+        }
+        let offset = reader.position().byte() as usize;
+        // The reader skips blank lines (and reports the position before
+        // them); emit them as empty rows
+        if let Some(position) = record.position() {
+            let mut start = position.byte() as usize;
+            while matches!(bytes.get(start), Some(b'\r' | b'\n')) {
+                start += 1;
+            }
+            line += bytes[scanned..start].iter().filter(|&&b| b == b'\n').count();
+            scanned = start;
+            while emitted < line {
+                emitted += 1;
+                visit(emitted, Vec::new(), offset)?;
+            }
+        }
+        emitted += 1;
+        visit(emitted, record.iter().map(text_cell).collect(), offset)?;
+    }
+}
+
+fn text_cell(value: &str) -> Data {
+    let value = value.trim();
+    if value.is_empty() {
+        Data::Empty
+    } else {
+        Data::String(value.to_string())
+    }
+}
+
+/// Guess the delimiter and quote character from the first lines
+pub fn sniff_dialect(text: &str) -> Dialect {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    let quote = sniff_quote(&lines);
+
+    let mut best = (b',', 0);
+    for delimiter in DELIMITER_CANDIDATES {
+        let score = delimiter_score(&lines, delimiter, quote);
+        if score > best.1 {
+            best = (delimiter, score);
+        }
+    }
+
+    Dialect {
+        delimiter: best.0,
+        quote,
+    }
+}
+
+/// Score a delimiter by how consistently it splits the sampled lines.
+///
+/// Lines are grouped by their field count; the most common non-trivial count
+/// wins, weighted first by how many lines share it and then by its size.
+fn delimiter_score(lines: &[&str], delimiter: u8, quote: u8) -> usize {
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+    for line in lines {
+        let n = count_outside_quotes(line, delimiter, quote);
+        if n == 0 {
+            continue;
+        }
+        match counts.iter_mut().find(|(count, _)| *count == n) {
+            Some((_, lines_with_count)) => *lines_with_count += 1,
+            None => counts.push((n, 1)),
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(count, lines_with_count)| lines_with_count * 1000 + count)
+        .max()
+        .unwrap_or(0)
+}
+
+fn count_outside_quotes(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for &b in line.as_bytes() {
+        if b == quote {
+            in_quotes = !in_quotes;
+        } else if b == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Pick the quote character that most often wraps a whole field
+fn sniff_quote(lines: &[&str]) -> u8 {
+    let mut best = (b'"', 0);
+    for quote in QUOTE_CANDIDATES {
+        let score: usize = lines.iter().map(|line| boundary_quotes(line, quote)).sum();
+        if score > best.1 {
+            best = (quote, score);
+        }
+    }
+    best.0
+}
+
+/// Count quote characters that sit at a field boundary (line edge or next to a delimiter)
+fn boundary_quotes(line: &str, quote: u8) -> usize {
+    let bytes = line.trim().as_bytes();
+    let is_boundary = |b: Option<&u8>| match b {
+        None => true,
+        Some(c) => DELIMITER_CANDIDATES.contains(c),
+    };
+
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(i, &b)| {
+            b == quote
+                && (is_boundary(i.checked_sub(1).and_then(|p| bytes.get(p)))
+                    || is_boundary(bytes.get(i + 1)))
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_comma() {
+        let text = "交易時間,帳號,支出金額\n2024-01-15 10:30:00,ACC1,100\n";
+        assert_eq!(sniff_dialect(text).delimiter, b',');
+    }
+
+    #[test]
+    fn test_sniff_tab_with_commas_in_values() {
+        let text = "時間\t帳號\t摘要\n2024-01-15\tACC1\t1,000 轉帳\n2024-01-16\tACC2\t2,500 轉帳\n";
+        let dialect = sniff_dialect(text);
+        assert_eq!(dialect.delimiter, b'\t');
+        assert_eq!(dialect.file_type(), "tsv");
+    }
+
+    #[test]
+    fn test_sniff_semicolon_and_single_quote() {
+        let text = "'time';'account'\n'2024-01-15 10:30:00';'A;1'\n";
+        let dialect = sniff_dialect(text);
+        assert_eq!(dialect.delimiter, b';');
+        assert_eq!(dialect.quote, b'\'');
+    }
+
+    #[test]
+    fn test_read_range_strips_bom_and_quotes() {
+        let data = "\u{feff}帳號,摘要\n\"ACC1\",\"a, b\"\n".as_bytes();
+        let sheet = read_range(data, None).unwrap();
+        assert_eq!(sheet.dialect.file_type(), "csv");
+        assert_eq!(sheet.range.height(), 2);
+        assert_eq!(sheet.range.get((0, 0)), Some(&Data::String("帳號".to_string())));
+        assert_eq!(sheet.range.get((1, 1)), Some(&Data::String("a, b".to_string())));
+    }
+}
@@ -0,0 +1,146 @@
+//! Text encoding detection for text-based inputs
+//!
+//! Taiwanese bank systems still export Big5/CP950 and some log systems emit
+//! UTF-16, so text is decoded by (in order): an explicit override, a byte
+//! order mark, a UTF-16 zero-byte heuristic, strict UTF-8, and finally
+//! chardetng with a Taiwan hint.
+
+use crate::error::CoreError;
+use crate::models::{EncodingInfo, EncodingSource};
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, BIG5, UTF_16BE, UTF_16LE, UTF_8};
+use std::borrow::Cow;
+
+/// Bytes inspected by the UTF-16 heuristic
+const UTF16_SNIFF_BYTES: usize = 1024;
+
+/// Decode text bytes, honouring an optional encoding label override
+pub fn decode<'a>(
+    data: &'a [u8],
+    override_label: Option<&str>,
+) -> Result<(Cow<'a, str>, EncodingInfo), CoreError> {
+    if let Some(label) = override_label {
+        let encoding = encoding_for_label(label)?;
+        // A BOM for the requested encoding is still stripped
+        let body = match Encoding::for_bom(data) {
+            Some((bom_encoding, bom_len)) if bom_encoding == encoding => &data[bom_len..],
+            _ => data,
+        };
+        return Ok(decode_with(body, encoding, EncodingSource::Override));
+    }
+
+    if let Some((encoding, bom_len)) = Encoding::for_bom(data) {
+        return Ok(decode_with(&data[bom_len..], encoding, EncodingSource::Bom));
+    }
+
+    let encoding = sniff_utf16(data).unwrap_or_else(|| {
+        if std::str::from_utf8(data).is_ok() {
+            UTF_8
+        } else {
+            let mut detector = EncodingDetector::new();
+            detector.feed(data, true);
+            detector.guess(Some(b"tw"), true)
+        }
+    });
+    Ok(decode_with(data, encoding, EncodingSource::Detected))
+}
+
+/// Resolve a user-supplied label, accepting Windows code page names
+fn encoding_for_label(label: &str) -> Result<&'static Encoding, CoreError> {
+    let normalized = label.trim().to_lowercase();
+    match normalized.as_str() {
+        "cp950" | "ms950" | "windows-950" | "950" => Ok(BIG5),
+        _ => Encoding::for_label(normalized.as_bytes())
+            .ok_or_else(|| CoreError::InvalidData(format!("Unknown encoding: {}", label))),
+    }
+}
+
+fn decode_with<'a>(
+    data: &'a [u8],
+    encoding: &'static Encoding,
+    source: EncodingSource,
+) -> (Cow<'a, str>, EncodingInfo) {
+    let (text, had_errors) = encoding.decode_without_bom_handling(data);
+    let info = EncodingInfo {
+        encoding: encoding.name().to_string(),
+        source,
+        had_errors,
+    };
+    (text, info)
+}
+
+/// Detect BOM-less UTF-16 from the pattern of zero bytes in ASCII-heavy text
+fn sniff_utf16(data: &[u8]) -> Option<&'static Encoding> {
+    let sample = &data[..data.len().min(UTF16_SNIFF_BYTES)];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+
+    let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_zeros = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+
+    if odd_zeros * 10 >= pairs * 4 && even_zeros * 20 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 4 && odd_zeros * 20 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "交易時間,帳號,IP位址\n2024-01-15 10:30:00,ACC1,1.2.3.4\n";
+
+    fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+        let mut bytes = if bom { vec![0xFF, 0xFE] } else { vec![] };
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        let (text, info) = decode(HEADER.as_bytes(), None).unwrap();
+        assert_eq!(text, HEADER);
+        assert_eq!(info.encoding, "UTF-8");
+        assert_eq!(info.source, EncodingSource::Detected);
+    }
+
+    #[test]
+    fn test_decode_big5() {
+        let (bytes, _, _) = BIG5.encode(HEADER);
+        let (text, info) = decode(&bytes, None).unwrap();
+        assert_eq!(text, HEADER);
+        assert_eq!(info.encoding, "Big5");
+        assert!(!info.had_errors);
+    }
+
+    #[test]
+    fn test_decode_utf16_with_and_without_bom() {
+        let with_bom = utf16le(HEADER, true);
+        let (text, info) = decode(&with_bom, None).unwrap();
+        assert_eq!(text, HEADER);
+        assert_eq!(info.source, EncodingSource::Bom);
+
+        let without_bom = utf16le(HEADER, false);
+        let (text, info) = decode(&without_bom, None).unwrap();
+        assert_eq!(text, HEADER);
+        assert_eq!(info.encoding, "UTF-16LE");
+        assert_eq!(info.source, EncodingSource::Detected);
+    }
+
+    #[test]
+    fn test_decode_override() {
+        let (bytes, _, _) = BIG5.encode(HEADER);
+        let (text, info) = decode(&bytes, Some("CP950")).unwrap();
+        assert_eq!(text, HEADER);
+        assert_eq!(info.source, EncodingSource::Override);
+
+        assert!(decode(&bytes, Some("not-an-encoding")).is_err());
+    }
+}
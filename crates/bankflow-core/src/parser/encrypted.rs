@@ -0,0 +1,363 @@
+//! Password-encrypted Office workbooks
+//!
+//! Excel saves a password-protected workbook as an OLE compound file with
+//! two streams: `EncryptionInfo`, describing how the key is derived from the
+//! password, and `EncryptedPackage`, the AES-encrypted xlsx itself. The agile
+//! encryption written since Office 2010 (MS-OFFCRYPTO 2.3.4.10) is decrypted
+//! here into memory, so no plaintext copy of the statement touches the disk.
+
+use super::OLE_MAGIC;
+use crate::error::CoreError;
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::{BlockCipherDecrypt, BlockModeDecrypt, KeyIvInit};
+use aes::{Aes128, Aes192, Aes256};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cfb::CompoundFile;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+
+const ENCRYPTION_INFO: &str = "EncryptionInfo";
+const ENCRYPTED_PACKAGE: &str = "EncryptedPackage";
+
+/// The package is encrypted in segments of this many bytes
+const SEGMENT: usize = 4096;
+
+/// Largest password hash iteration count MS-OFFCRYPTO allows
+const MAX_SPIN_COUNT: u32 = 10_000_000;
+
+/// Block keys mixed into the password hash for each key-encryptor value
+const VERIFIER_INPUT_BLOCK: [u8; 8] = [0xFE, 0xA7, 0xD2, 0x76, 0x3B, 0x4B, 0x9E, 0x79];
+const VERIFIER_HASH_BLOCK: [u8; 8] = [0xD7, 0xAA, 0x0F, 0x6D, 0x30, 0x61, 0x34, 0x4E];
+const KEY_VALUE_BLOCK: [u8; 8] = [0x14, 0x6E, 0x0B, 0xE7, 0xAB, 0xAC, 0xD0, 0xD6];
+
+/// Whether the bytes are an encrypted Office package
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(OLE_MAGIC)
+        && CompoundFile::open(Cursor::new(data))
+            .is_ok_and(|file| file.is_stream(ENCRYPTION_INFO) && file.is_stream(ENCRYPTED_PACKAGE))
+}
+
+/// The input itself, or the decrypted workbook when it is an encrypted
+/// package. An encrypted package without a password is an error.
+pub fn unlock<'a>(data: &'a [u8], password: Option<&str>) -> Result<Cow<'a, [u8]>, CoreError> {
+    if !is_encrypted(data) {
+        return Ok(Cow::Borrowed(data));
+    }
+    let password = password.ok_or(CoreError::PasswordRequired)?;
+    decrypt(data, password).map(Cow::Owned)
+}
+
+/// Decrypt an encrypted package with its password
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, CoreError> {
+    let mut file = CompoundFile::open(Cursor::new(data)).map_err(|e| damaged(&e.to_string()))?;
+    let info = read_stream(&mut file, ENCRYPTION_INFO)?;
+    let package = read_stream(&mut file, ENCRYPTED_PACKAGE)?;
+
+    let agile = AgileInfo::parse(&info)?;
+    let key = agile.secret_key(password)?;
+    agile.decrypt_package(&key, &package)
+}
+
+fn read_stream<F: Read + Seek>(file: &mut CompoundFile<F>, name: &str) -> Result<Vec<u8>, CoreError> {
+    let mut bytes = Vec::new();
+    file.open_stream(name)
+        .and_then(|mut stream| stream.read_to_end(&mut bytes))
+        .map_err(|e| damaged(&format!("{}: {}", name, e)))?;
+    Ok(bytes)
+}
+
+fn damaged(detail: &str) -> CoreError {
+    CoreError::ExcelParseError(format!("Encrypted file is damaged: {}", detail))
+}
+
+fn unsupported(kind: &str) -> CoreError {
+    CoreError::ExcelParseError(format!(
+        "Unsupported encryption ({}); open the file in Excel and save it again to use current encryption",
+        kind
+    ))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HashAlgorithm {
+    /// Written by Office 2010 and some third-party tools
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().replace('-', "").as_str() {
+            "SHA1" => Some(Self::Sha1),
+            "SHA256" => Some(Self::Sha256),
+            "SHA384" => Some(Self::Sha384),
+            "SHA512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Hash of the concatenated parts
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+        match self {
+            Self::Sha1 => run::<Sha1>(parts),
+            Self::Sha256 => run::<Sha256>(parts),
+            Self::Sha384 => run::<Sha384>(parts),
+            Self::Sha512 => run::<Sha512>(parts),
+        }
+    }
+}
+
+/// Truncate or pad (with 0x36) to `len` bytes
+fn fit(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes.resize(len, 0x36);
+    bytes
+}
+
+type Attributes = HashMap<String, String>;
+
+/// Cipher settings shared by `keyData` and the password `encryptedKey`
+struct Cipher {
+    salt: Vec<u8>,
+    block_size: usize,
+    key_bytes: usize,
+    hash: HashAlgorithm,
+}
+
+impl Cipher {
+    fn from_attributes(attributes: &Attributes) -> Result<Self, CoreError> {
+        let algorithm = attribute(attributes, "cipherAlgorithm")?;
+        let chaining = attribute(attributes, "cipherChaining")?;
+        if algorithm != "AES" || chaining != "ChainingModeCBC" {
+            return Err(unsupported(&format!("{} {}", algorithm, chaining)));
+        }
+        let hash_name = attribute(attributes, "hashAlgorithm")?;
+        Ok(Self {
+            salt: base64_attribute(attributes, "saltValue")?,
+            block_size: number(attributes, "blockSize")?,
+            key_bytes: number(attributes, "keyBits")? / 8,
+            hash: HashAlgorithm::from_name(hash_name).ok_or_else(|| unsupported(hash_name))?,
+        })
+    }
+
+    fn aes_cbc(&self, key: Vec<u8>, iv: Vec<u8>, data: &[u8]) -> Result<Vec<u8>, CoreError> {
+        let (key, iv) = (fit(key, self.key_bytes), fit(iv, self.block_size));
+        match self.key_bytes {
+            16 => cbc_decrypt::<Aes128>(&key, &iv, data),
+            24 => cbc_decrypt::<Aes192>(&key, &iv, data),
+            32 => cbc_decrypt::<Aes256>(&key, &iv, data),
+            _ => Err(unsupported("key size")),
+        }
+    }
+}
+
+/// Decrypt whole AES-CBC blocks; the package pads its segments itself
+fn cbc_decrypt<C: BlockCipherDecrypt>(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, CoreError>
+where
+    cbc::Decryptor<C>: KeyIvInit + BlockModeDecrypt,
+{
+    cbc::Decryptor::<C>::new_from_slices(key, iv)
+        .map_err(|_| unsupported("block size"))?
+        .decrypt_padded_vec::<NoPadding>(data)
+        .map_err(|_| damaged("cipher text is not a whole number of blocks"))
+}
+
+fn attribute<'a>(attributes: &'a Attributes, name: &str) -> Result<&'a str, CoreError> {
+    attributes
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| damaged(&format!("missing {}", name)))
+}
+
+fn number(attributes: &Attributes, name: &str) -> Result<usize, CoreError> {
+    attribute(attributes, name)?
+        .parse()
+        .map_err(|_| damaged(&format!("invalid {}", name)))
+}
+
+/// Password hash iterations, bounded so a crafted file cannot stall the load
+fn spin_count(attributes: &Attributes) -> Result<u32, CoreError> {
+    attribute(attributes, "spinCount")?
+        .parse()
+        .ok()
+        .filter(|count| *count <= MAX_SPIN_COUNT)
+        .ok_or_else(|| damaged("invalid spinCount"))
+}
+
+fn base64_attribute(attributes: &Attributes, name: &str) -> Result<Vec<u8>, CoreError> {
+    BASE64
+        .decode(attribute(attributes, name)?)
+        .map_err(|_| damaged(&format!("invalid {}", name)))
+}
+
+/// Agile `EncryptionInfo`: the package cipher and the password key encryptor
+struct AgileInfo {
+    data: Cipher,
+    password: Cipher,
+    spin_count: u32,
+    verifier_input: Vec<u8>,
+    verifier_hash: Vec<u8>,
+    encrypted_key: Vec<u8>,
+}
+
+impl AgileInfo {
+    fn parse(info: &[u8]) -> Result<Self, CoreError> {
+        let version = info
+            .get(..4)
+            .map(|v| (u16::from_le_bytes([v[0], v[1]]), u16::from_le_bytes([v[2], v[3]])))
+            .ok_or_else(|| damaged("EncryptionInfo is truncated"))?;
+        match version {
+            (4, 4) => {}
+            (2..=4, 2) => return Err(unsupported("Office 2007 standard encryption")),
+            (3 | 4, 3) => return Err(unsupported("extensible encryption")),
+            (major, minor) => return Err(unsupported(&format!("version {}.{}", major, minor))),
+        }
+        // Version, then 4 reserved bytes before the XML descriptor
+        let xml = info
+            .get(8..)
+            .and_then(|xml| std::str::from_utf8(xml).ok())
+            .ok_or_else(|| damaged("EncryptionInfo has no XML descriptor"))?;
+
+        let mut key_data = None;
+        let mut encrypted_key = None;
+        let mut reader = Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(element)) | Ok(Event::Empty(element)) => match element.local_name().as_ref() {
+                    b"keyData" => key_data = Some(attributes_of(&element, &reader)?),
+                    // Certificate encryptors carry no spin count
+                    b"encryptedKey" => {
+                        let attributes = attributes_of(&element, &reader)?;
+                        if encrypted_key.is_none() && attributes.contains_key("spinCount") {
+                            encrypted_key = Some(attributes);
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => return Err(damaged(&e.to_string())),
+            }
+        }
+        let key_data = key_data.ok_or_else(|| damaged("missing keyData"))?;
+        let encrypted_key = encrypted_key.ok_or_else(|| unsupported("no password key encryptor"))?;
+
+        Ok(Self {
+            data: Cipher::from_attributes(&key_data)?,
+            password: Cipher::from_attributes(&encrypted_key)?,
+            spin_count: spin_count(&encrypted_key)?,
+            verifier_input: base64_attribute(&encrypted_key, "encryptedVerifierHashInput")?,
+            verifier_hash: base64_attribute(&encrypted_key, "encryptedVerifierHashValue")?,
+            encrypted_key: base64_attribute(&encrypted_key, "encryptedKeyValue")?,
+        })
+    }
+
+    /// Derive the package key from the password, checking it against the
+    /// stored verifier
+    fn secret_key(&self, password: &str) -> Result<Vec<u8>, CoreError> {
+        let cipher = &self.password;
+        let utf16: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut hash = cipher.hash.digest(&[&cipher.salt, &utf16]);
+        for i in 0..self.spin_count {
+            hash = cipher.hash.digest(&[&i.to_le_bytes(), &hash]);
+        }
+        let decrypt = |block: &[u8], value: &[u8]| {
+            cipher.aes_cbc(cipher.hash.digest(&[&hash, block]), cipher.salt.clone(), value)
+        };
+
+        let input = decrypt(&VERIFIER_INPUT_BLOCK, &self.verifier_input)?;
+        let expected = decrypt(&VERIFIER_HASH_BLOCK, &self.verifier_hash)?;
+        let actual = cipher.hash.digest(&[&input[..cipher.salt.len().min(input.len())]]);
+        if expected.get(..actual.len()) != Some(actual.as_slice()) {
+            return Err(CoreError::WrongPassword);
+        }
+
+        let mut key = decrypt(&KEY_VALUE_BLOCK, &self.encrypted_key)?;
+        if key.len() < self.data.key_bytes {
+            return Err(damaged("encrypted key is too short"));
+        }
+        key.truncate(self.data.key_bytes);
+        Ok(key)
+    }
+
+    fn decrypt_package(&self, key: &[u8], package: &[u8]) -> Result<Vec<u8>, CoreError> {
+        let (size, body) = package
+            .split_first_chunk::<8>()
+            .ok_or_else(|| damaged("EncryptedPackage is truncated"))?;
+        let size = u64::from_le_bytes(*size) as usize;
+
+        let mut plain = Vec::with_capacity(body.len());
+        for (i, segment) in body.chunks(SEGMENT).enumerate() {
+            let iv = self.data.hash.digest(&[&self.data.salt, &(i as u32).to_le_bytes()]);
+            // Anything past the last whole cipher block is padding
+            let whole = segment.len() - segment.len() % self.data.block_size.max(1);
+            plain.extend(self.data.aes_cbc(key.to_vec(), iv, &segment[..whole])?);
+        }
+        if plain.len() < size {
+            return Err(damaged("EncryptedPackage is shorter than its declared size"));
+        }
+        plain.truncate(size);
+        Ok(plain)
+    }
+}
+
+fn attributes_of(element: &BytesStart, reader: &Reader<&[u8]>) -> Result<Attributes, CoreError> {
+    element
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute.map_err(|e| damaged(&e.to_string()))?;
+            let value = attribute.decode_and_unescape_value(reader).map_err(|e| damaged(&e.to_string()))?;
+            Ok((String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(), value.into_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_input_passes_through() {
+        let data = b"account,ip\n";
+        assert!(matches!(unlock(data, None), Ok(Cow::Borrowed(_))));
+        assert!(!is_encrypted(OLE_MAGIC));
+    }
+
+    #[test]
+    fn test_rejects_standard_encryption() {
+        let mut info = vec![4, 0, 2, 0];
+        info.extend([0; 8]);
+        let err = AgileInfo::parse(&info).err().expect("unsupported");
+        assert!(err.to_string().contains("standard encryption"), "{}", err);
+    }
+
+    #[test]
+    fn test_spin_count_is_bounded() {
+        let count = |value: &str| spin_count(&HashMap::from([("spinCount".to_string(), value.to_string())])).ok();
+        assert_eq!(count("100000"), Some(100_000));
+        assert_eq!(count("10000000"), Some(MAX_SPIN_COUNT));
+        assert_eq!(count("10000001"), None);
+        assert_eq!(count("4294967295"), None);
+        assert_eq!(count("4294967296"), None);
+        assert_eq!(count("-1"), None);
+    }
+
+    #[test]
+    fn test_fit_pads_and_truncates() {
+        assert_eq!(fit(vec![1, 2], 4), [1, 2, 0x36, 0x36]);
+        assert_eq!(fit(vec![1, 2, 3], 2), [1, 2]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Fixed-width text reports
+//!
+//! Older core-banking systems print their returns as mainframe-style text:
+//! every field at a fixed character position, pages separated by form feeds
+//! (or a fixed number of lines) and each page opening with a title block and
+//! column headings and closing with page totals. Nothing in the text marks
+//! the columns, so the layout comes from the format profile
+//! (`FormatProfile::fixed_width`), whose field names become the header row.
+//!
+//! Positions count display columns: a full-width (CJK) character takes two,
+//! as it does in the double-byte encoding the report was printed in. Rows
+//! are numbered by their line in the file; the header row is line 0.
+
+use super::encoding;
+use crate::error::CoreError;
+use crate::models::EncodingInfo;
+use crate::profile::{FixedWidthField, FixedWidthLayout};
+use calamine::Data;
+
+/// Data records of a report, with the field names as the first row
+pub struct FixedWidthTable {
+    pub rows: Vec<Vec<Data>>,
+    /// 1-based file line of each row (0 for the header row)
+    pub lines: Vec<usize>,
+    pub encoding: EncodingInfo,
+}
+
+/// Decode the report and cut its data records into fields
+pub fn read(
+    data: &[u8],
+    layout: &FixedWidthLayout,
+    encoding_override: Option<&str>,
+) -> Result<FixedWidthTable, CoreError> {
+    let (text, encoding) = encoding::decode(data, encoding_override)?;
+
+    let mut rows = vec![layout.fields.iter().map(|f| Data::String(f.name.clone())).collect()];
+    let mut lines = vec![0];
+    for page in pages(&text, layout.page_length) {
+        let body = page.len().saturating_sub(layout.page_footer_lines);
+        for &(number, line) in page.iter().take(body).skip(layout.page_header_lines) {
+            if is_record(line, layout) {
+                rows.push(layout.fields.iter().map(|field| cell(line, field)).collect());
+                lines.push(number);
+            }
+        }
+    }
+    if rows.len() == 1 {
+        return Err(CoreError::ExcelParseError(
+            "No records of the fixed-width layout were found in the file".to_string(),
+        ));
+    }
+    Ok(FixedWidthTable { rows, lines, encoding })
+}
+
+/// Non-blank lines of each page with their 1-based file line number. A page
+/// ends at a form feed, or after `page_length` lines.
+fn pages(text: &str, page_length: Option<usize>) -> Vec<Vec<(usize, &str)>> {
+    let mut pages = Vec::new();
+    let mut page = Vec::new();
+    let mut page_lines = 0;
+    for (idx, line) in text.lines().enumerate() {
+        let starts_page = line.contains('\u{c}') || page_length.is_some_and(|length| page_lines == length);
+        if starts_page && page_lines > 0 {
+            pages.push(std::mem::take(&mut page));
+            page_lines = 0;
+        }
+        page_lines += 1;
+        // A form feed normally opens the line; anything before it belongs
+        // to no record
+        let line = line.rsplit('\u{c}').next().unwrap_or_default();
+        if !line.trim().is_empty() {
+            page.push((idx + 1, line));
+        }
+    }
+    pages.push(page);
+    pages
+}
+
+/// Whether a line inside the page body is a data record: it has some
+/// letters or digits (not a ruling line), contains none of the skip texts
+/// and carries one of the record types
+fn is_record(line: &str, layout: &FixedWidthLayout) -> bool {
+    if !line.chars().any(char::is_alphanumeric) {
+        return false;
+    }
+    if layout.skip_lines.iter().any(|text| !text.is_empty() && line.contains(text.as_str())) {
+        return false;
+    }
+    match &layout.record_type {
+        Some(filter) => {
+            let code = slice(line, filter.start, filter.end);
+            filter.values.iter().any(|value| value.trim() == code.trim())
+        }
+        None => true,
+    }
+}
+
+/// Trimmed text of a field, empty when the line is blank there
+fn cell(line: &str, field: &FixedWidthField) -> Data {
+    let text = slice(line, field.start, field.end);
+    let text = text.trim();
+    if text.is_empty() {
+        Data::Empty
+    } else {
+        Data::String(text.to_string())
+    }
+}
+
+/// Characters starting at display columns `start..end`
+fn slice(line: &str, start: usize, end: usize) -> String {
+    let mut column = 0;
+    let mut out = String::new();
+    for ch in line.chars() {
+        if column >= end {
+            break;
+        }
+        if column >= start {
+            out.push(ch);
+        }
+        column += if ch == '\t' { 1 } else { char_width(ch) };
+    }
+    out
+}
+
+/// Display columns of a character: two for East Asian wide and full-width
+/// forms, one otherwise
+fn char_width(ch: char) -> usize {
+    match u32::from(ch) {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::RecordTypeFilter;
+
+    fn field(name: &str, start: usize, end: usize) -> FixedWidthField {
+        FixedWidthField { name: name.to_string(), start, end }
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(slice("王小明  ACC1", 0, 8), "王小明  ");
+        assert_eq!(slice("王小明  ACC1", 8, 12), "ACC1");
+        assert_eq!(slice("AB", 1, 10), "B");
+    }
+
+    #[test]
+    fn page_headers_footers_and_record_types_are_skipped() {
+        let layout = FixedWidthLayout {
+            fields: vec![field("Type", 0, 1), field("Account", 2, 8)],
+            record_type: Some(RecordTypeFilter { start: 0, end: 1, values: vec!["D".to_string()] }),
+            page_length: None,
+            page_header_lines: 1,
+            page_footer_lines: 1,
+            skip_lines: vec!["CONTINUED".to_string()],
+        };
+        let text = "TITLE\nD ACC1\n------\nH HEAD\nD ACC2 CONTINUED\nPAGE 1\n\u{c}TITLE\n\nD ACC3\nPAGE 2\n";
+        let table = read(text.as_bytes(), &layout, None).expect("read");
+        assert_eq!(table.lines, [0, 2, 9]);
+        assert_eq!(table.rows[2][1], Data::String("ACC3".to_string()));
+    }
+
+    #[test]
+    fn pages_split_by_length() {
+        let text = "h\na\nb\nh\nc\n";
+        let pages = pages(text, Some(3));
+        let numbers: Vec<Vec<usize>> = pages.iter().map(|p| p.iter().map(|(n, _)| *n).collect()).collect();
+        assert_eq!(numbers, [vec![1, 2, 3], vec![4, 5]]);
+    }
+}
@@ -0,0 +1,348 @@
+//! HTML tables saved as ".xls"
+//!
+//! Many online banking portals "export to Excel" by serving an HTML page
+//! with an .xls extension. Every `<table>` holding text becomes one table,
+//! rows in `<tr>` order. The markup is rarely well formed, so this is a
+//! forgiving scan rather than an HTML parser: a new `<tr>` or `<td>`
+//! closes the previous one, `colspan`/`rowspan` leave empty cells behind
+//! so columns stay aligned, and `<br>` separates lines within a cell.
+
+use calamine::Data;
+
+/// Cell rows of every table with text, in document order. Nested tables
+/// are read as tables of their own.
+pub fn read_tables(html: &str) -> Vec<Vec<Vec<Data>>> {
+    let mut tables: Vec<TableBuilder> = Vec::new();
+    // Indices into `tables` of the tables currently open, innermost last
+    let mut open: Vec<usize> = Vec::new();
+
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        if let Some(&idx) = open.last() {
+            tables[idx].text(&rest[..lt]);
+        }
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(tag) = Tag::parse(rest) else {
+            // A stray '<' is text
+            if let Some(&idx) = open.last() {
+                tables[idx].text("<");
+            }
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[tag.len..];
+
+        match (tag.name.as_str(), tag.closing) {
+            ("script" | "style", false) => {
+                let close = format!("</{}", tag.name);
+                rest = find_ignore_case(rest, &close).map_or("", |end| &rest[end..]);
+            }
+            ("table", false) => {
+                open.push(tables.len());
+                tables.push(TableBuilder::default());
+            }
+            ("table", true) => {
+                if let Some(idx) = open.pop() {
+                    tables[idx].end_row();
+                }
+            }
+            (name, closing) => {
+                if let Some(&idx) = open.last() {
+                    let table = &mut tables[idx];
+                    match (name, closing) {
+                        ("tr", false) => {
+                            table.end_row();
+                            table.row = Some(Vec::new());
+                        }
+                        ("tr", true) => table.end_row(),
+                        ("td" | "th", false) => table.start_cell(&tag),
+                        ("td" | "th", true) => table.end_cell(),
+                        ("br", _) => table.line_break(),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    if let Some(&idx) = open.last() {
+        tables[idx].text(rest);
+    }
+    for idx in open {
+        tables[idx].end_row();
+    }
+
+    tables
+        .into_iter()
+        .map(|table| table.rows)
+        .filter(|rows| rows.iter().flatten().any(|cell| *cell != Data::Empty))
+        .collect()
+}
+
+#[derive(Default)]
+struct TableBuilder {
+    rows: Vec<Vec<Data>>,
+    row: Option<Vec<Data>>,
+    /// Text of the open cell with its column and row span
+    cell: Option<(String, usize, usize)>,
+    /// Rows still covered by a cell spanning down from above, per column
+    spans: Vec<usize>,
+}
+
+impl TableBuilder {
+    /// Append markup text to the open cell; line breaks in the source are
+    /// only whitespace
+    fn text(&mut self, text: &str) {
+        if let Some((cell, _, _)) = &mut self.cell {
+            cell.extend(text.chars().map(|c| if c == '\n' || c == '\r' { ' ' } else { c }));
+        }
+    }
+
+    fn line_break(&mut self) {
+        if let Some((cell, _, _)) = &mut self.cell {
+            cell.push('\n');
+        }
+    }
+
+    fn start_cell(&mut self, tag: &Tag) {
+        self.end_cell();
+        let span = |name: &str| {
+            tag.attribute(name)
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .map_or(1, |span| span.clamp(1, 1000))
+        };
+        self.cell = Some((String::new(), span("colspan"), span("rowspan")));
+    }
+
+    fn end_cell(&mut self) {
+        let Some((text, colspan, rowspan)) = self.cell.take() else { return };
+        let row = self.row.get_or_insert_with(Vec::new);
+        skip_spanned(row, &mut self.spans);
+
+        let text = cell_text(&text);
+        let col = row.len();
+        row.push(if text.is_empty() { Data::Empty } else { Data::String(text) });
+        row.resize(col + colspan, Data::Empty);
+        if rowspan > 1 {
+            if self.spans.len() < col + colspan {
+                self.spans.resize(col + colspan, 0);
+            }
+            for span in &mut self.spans[col..col + colspan] {
+                *span = rowspan - 1;
+            }
+        }
+    }
+
+    fn end_row(&mut self) {
+        self.end_cell();
+        if let Some(mut row) = self.row.take() {
+            // Columns past the last cell still covered from above
+            while let Some(col) = (row.len()..self.spans.len()).find(|&col| self.spans[col] > 0) {
+                row.resize(col, Data::Empty);
+                skip_spanned(&mut row, &mut self.spans);
+            }
+            self.rows.push(row);
+        }
+    }
+}
+
+/// Leave an empty cell in each column covered by a row span from above
+fn skip_spanned(row: &mut Vec<Data>, spans: &mut [usize]) {
+    while let Some(span) = spans.get_mut(row.len()).filter(|span| **span > 0) {
+        *span -= 1;
+        row.push(Data::Empty);
+    }
+}
+
+/// Cell text with entities decoded, whitespace collapsed and `<br>` lines
+/// kept apart
+fn cell_text(raw: &str) -> String {
+    let decoded = decode_entities(raw);
+    decoded
+        .split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decode character references
+fn decode_entities(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let value = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix('#') {
+                        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok(),
+                        Some(dec) => dec.parse().ok(),
+                        None => None,
+                    };
+                    char::from_u32(code?)?
+                }
+            };
+            Some((value, end + 1))
+        });
+        match decoded {
+            Some((value, len)) => {
+                out.push(value);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .find(|&idx| haystack.get(idx..idx + needle.len()).is_some_and(|s| s.eq_ignore_ascii_case(needle)))
+}
+
+/// A start or end tag
+struct Tag<'a> {
+    /// Lowercased element name
+    name: String,
+    closing: bool,
+    /// Text between the name and the closing '>'
+    attributes: &'a str,
+    /// Bytes taken by the tag, including '<' and '>'
+    len: usize,
+}
+
+impl<'a> Tag<'a> {
+    /// Parse the tag at the start of `text`; `None` when `text` does not
+    /// start with one (a '<' in plain text, or an unterminated tag)
+    fn parse(text: &'a str) -> Option<Self> {
+        let body = text.strip_prefix('<')?;
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, body),
+        };
+        if body.starts_with(['!', '?']) {
+            let end = body.find('>')?;
+            return Some(Tag { name: String::new(), closing, attributes: "", len: text.len() - body.len() + end + 1 });
+        }
+        let name_len = body.find(|c: char| !c.is_ascii_alphanumeric() && c != ':').unwrap_or(body.len());
+        if name_len == 0 {
+            return None;
+        }
+
+        // Find the closing '>' outside quoted attribute values
+        let mut quote = None;
+        let end = body[name_len..].char_indices().find_map(|(idx, c)| match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                None
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                None
+            }
+            (None, '>') => Some(name_len + idx),
+            _ => None,
+        })?;
+
+        let name = body[..name_len].to_ascii_lowercase();
+        // Namespaced Office elements ("o:p") are never table structure
+        let name = if name.contains(':') { String::new() } else { name };
+        Some(Tag {
+            name,
+            closing,
+            attributes: body[name_len..end].trim_end_matches('/'),
+            len: text.len() - body.len() + end + 1,
+        })
+    }
+
+    /// Value of an attribute, quoted or not
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        let mut rest = self.attributes;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                return None;
+            }
+            let key_len = rest.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+            let key = &rest[..key_len];
+            rest = rest[key_len..].trim_start();
+            let value = match rest.strip_prefix('=') {
+                Some(after) => {
+                    let after = after.trim_start();
+                    let (value, remaining) = match after.chars().next() {
+                        Some(q @ ('"' | '\'')) => {
+                            let inner = &after[1..];
+                            let end = inner.find(q).unwrap_or(inner.len());
+                            (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+                        }
+                        _ => {
+                            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                            (&after[..end], &after[end..])
+                        }
+                    };
+                    rest = remaining;
+                    value
+                }
+                None => "",
+            };
+            if key.eq_ignore_ascii_case(name) {
+                return Some(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(rows: &[Vec<Data>]) -> Vec<Vec<String>> {
+        rows.iter().map(|row| row.iter().map(|c| c.to_string()).collect()).collect()
+    }
+
+    #[test]
+    fn test_unclosed_cells_and_entities() {
+        let html = "<html><body><p>Statement</p><TABLE border=1>\
+            <tr><th>帳號<th>摘要\n<tr><td>ACC1<td>A&amp;B&nbsp;&#x4E2D;\n</table>";
+        let tables = read_tables(html);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(strings(&tables[0]), [["帳號", "摘要"], ["ACC1", "A&B 中"]]);
+    }
+
+    #[test]
+    fn test_spans_keep_columns_aligned() {
+        let html = "<table><tr><td rowspan='2'>D1</td><td colspan=\"2\">x</td><td>y</td></tr>\
+            <tr><td>a</td><td>b</td><td>c</td></tr></table>";
+        let tables = read_tables(html);
+        assert_eq!(strings(&tables[0]), [["D1", "x", "", "y"], ["", "a", "b", "c"]]);
+    }
+
+    #[test]
+    fn test_nested_and_empty_tables() {
+        let html = "<table><tr><td><table><tr><td>inner</td></tr></table></td></tr></table>\
+            <table><tr><td>second<br/>line</td><td><!-- <td>no --></td></tr></table>\
+            <script>var t = '<table><tr><td>x';</script>";
+        let tables = read_tables(html);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(strings(&tables[0]), [["inner"]]);
+        assert_eq!(strings(&tables[1]), [["second\nline", ""]]);
+    }
+}
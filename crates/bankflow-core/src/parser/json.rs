@@ -0,0 +1,216 @@
+//! JSON and newline-delimited JSON input
+//!
+//! Platform API dumps and case-system exports are already structured. Each
+//! record object becomes a row and its field names the header row, so field
+//! names are mapped to columns exactly as spreadsheet headers are. Nested
+//! objects are flattened to dotted names ("device.id") and arrays of plain
+//! values are joined with ", ", which the IP column splits again.
+//!
+//! Accepted shapes: an array of objects, an object holding such an array
+//! (e.g. `{"data": [...]}`), or one object per line. The header row is
+//! row 1 and the n-th record is row n + 1.
+
+use super::{encoding, rows_to_range};
+use crate::error::CoreError;
+use crate::models::EncodingInfo;
+use calamine::{Data, Range};
+use serde_json::{Map, Value};
+
+/// JSON records loaded into a cell grid
+pub struct JsonSheet {
+    pub range: Range<Data>,
+    /// "json" or "ndjson"
+    pub file_type: &'static str,
+    pub encoding: EncodingInfo,
+}
+
+/// Whether the bytes start like a JSON document, after any byte order mark
+/// and whitespace
+pub fn looks_like_json(data: &[u8]) -> bool {
+    // Code units of the text as far as the BOM tells
+    let units: Box<dyn Iterator<Item = u16>> = if let Some(rest) = data.strip_prefix(&[0xFF, 0xFE]) {
+        Box::new(rest.chunks_exact(2).map(|u| u16::from_le_bytes([u[0], u[1]])))
+    } else if let Some(rest) = data.strip_prefix(&[0xFE, 0xFF]) {
+        Box::new(rest.chunks_exact(2).map(|u| u16::from_be_bytes([u[0], u[1]])))
+    } else {
+        let rest = data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data);
+        Box::new(rest.iter().map(|&b| u16::from(b)))
+    };
+    let mut units = units.skip_while(|&u| matches!(u, 0x20 | 0x09 | 0x0A | 0x0D));
+    matches!(units.next(), Some(0x5B | 0x7B))
+}
+
+/// Decode and read JSON or NDJSON records into a cell grid
+pub fn read_range(data: &[u8], encoding_override: Option<&str>) -> Result<JsonSheet, CoreError> {
+    let (text, encoding) = encoding::decode(data, encoding_override)?;
+    let (records, file_type) = read_records(&text)?;
+    Ok(JsonSheet {
+        range: rows_to_range(to_rows(&records)),
+        file_type,
+        encoding,
+    })
+}
+
+/// A record object with nested fields flattened
+type Record = Map<String, Value>;
+
+/// Record objects of the text, with the file type they came as
+fn read_records(text: &str) -> Result<(Vec<Record>, &'static str), CoreError> {
+    let mut documents = Vec::new();
+    for document in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        documents.push(document.map_err(|e| CoreError::ExcelParseError(format!("Failed to read JSON: {}", e)))?);
+    }
+
+    let (values, file_type) = match documents.len() {
+        1 => match documents.pop() {
+            Some(Value::Array(items)) => (items, "json"),
+            Some(Value::Object(mut object)) => match record_array(&object).and_then(|key| object.remove(&key)) {
+                Some(Value::Array(items)) => (items, "json"),
+                _ => (vec![Value::Object(object)], "json"),
+            },
+            _ => return Err(not_records()),
+        },
+        _ => (documents, "ndjson"),
+    };
+
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::Object(object) => {
+                let mut record = Map::new();
+                flatten("", object, &mut record);
+                Ok(record)
+            }
+            _ => Err(not_records()),
+        })
+        .collect::<Result<_, _>>()
+        .map(|records| (records, file_type))
+}
+
+fn not_records() -> CoreError {
+    CoreError::InvalidData("JSON input must hold record objects (an array of objects or one object per line)".to_string())
+}
+
+/// Name of the first field of a wrapper object that holds an array of objects
+fn record_array(object: &Map<String, Value>) -> Option<String> {
+    object.iter().find_map(|(key, value)| match value {
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => Some(key.clone()),
+        _ => None,
+    })
+}
+
+/// Copy the fields of `object` into `out`, nested objects under dotted names
+fn flatten(prefix: &str, object: Map<String, Value>, out: &mut Record) {
+    for (key, value) in object {
+        let name = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(inner) => flatten(&name, inner, out),
+            value => {
+                out.insert(name, value);
+            }
+        }
+    }
+}
+
+/// Header row (field names in first-seen order) followed by one row per record
+fn to_rows(records: &[Record]) -> Vec<Vec<Data>> {
+    let mut names: Vec<&str> = Vec::new();
+    for record in records {
+        for key in record.keys() {
+            if !names.contains(&key.as_str()) {
+                names.push(key);
+            }
+        }
+    }
+
+    let mut rows = Vec::with_capacity(records.len() + 1);
+    rows.push(names.iter().map(|name| Data::String(name.to_string())).collect());
+    for record in records {
+        rows.push(names.iter().map(|name| record.get(*name).map_or(Data::Empty, cell)).collect());
+    }
+    rows
+}
+
+fn cell(value: &Value) -> Data {
+    match value {
+        Value::Null => Data::Empty,
+        Value::Bool(b) => Data::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Data::Int(i),
+            None => n.as_f64().map_or(Data::Empty, Data::Float),
+        },
+        Value::String(s) => text(s),
+        Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+            let parts: Vec<String> = items.iter().filter_map(scalar_text).collect();
+            text(&parts.join(", "))
+        }
+        // Arrays of objects are kept as their JSON text
+        other => text(&other.to_string()),
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.trim().to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn text(value: &str) -> Data {
+    let value = value.trim();
+    if value.is_empty() {
+        Data::Empty
+    } else {
+        Data::String(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(text: &str) -> (Vec<Vec<String>>, &'static str) {
+        let (records, file_type) = read_records(text).expect("records");
+        let rows = to_rows(&records)
+            .iter()
+            .map(|row| row.iter().map(|c| c.to_string()).collect())
+            .collect();
+        (rows, file_type)
+    }
+
+    #[test]
+    fn test_array_and_wrapper_object() {
+        let array = r#"[{"account": "A1", "amount": 10}, {"account": "A2", "memo": "x"}]"#;
+        let (table, file_type) = rows(array);
+        assert_eq!(file_type, "json");
+        assert_eq!(table[0], ["account", "amount", "memo"]);
+        assert_eq!(table[2], ["A2", "", "x"]);
+
+        let wrapped = format!(r#"{{"count": 2, "data": {}}}"#, array);
+        assert_eq!(rows(&wrapped).0, table);
+    }
+
+    #[test]
+    fn test_ndjson_flattens_nested_fields() {
+        let text = "{\"ip\": [\"1.1.1.1\", \"2.2.2.2\"], \"device\": {\"id\": \"D1\"}}\n\n{\"ip\": \"3.3.3.3\", \"ok\": true}\n";
+        let (table, file_type) = rows(text);
+        assert_eq!(file_type, "ndjson");
+        assert_eq!(table[0], ["ip", "device.id", "ok"]);
+        assert_eq!(table[1], ["1.1.1.1, 2.2.2.2", "D1", ""]);
+        assert_eq!(table[2], ["3.3.3.3", "", "true"]);
+    }
+
+    #[test]
+    fn test_rejects_non_records() {
+        assert!(read_records("[1, 2]").is_err());
+        assert!(read_records("{\"a\": 1} oops").is_err());
+    }
+
+    #[test]
+    fn test_looks_like_json() {
+        assert!(looks_like_json(b"\xEF\xBB\xBF  [{}]"));
+        assert!(looks_like_json(b"\xFF\xFE{\x00"));
+        assert!(!looks_like_json(b"account,ip\n"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Markup documents saved as ".xls"
+//!
+//! Banking portals often serve an HTML table or an Excel 2003 XML
+//! (SpreadsheetML) document under an .xls name, which calamine cannot open.
+//! The content is sniffed rather than trusting the extension, and the tables
+//! of these documents are offered like worksheets: chosen through
+//! `ParseOptions::sheets`, the first one by default.
+
+use super::{encoding, html, rows_to_range, select_sheets, spreadsheetml, ParseOptions};
+use crate::error::CoreError;
+use crate::models::EncodingInfo;
+use calamine::{Data, Range};
+use encoding_rs::Encoding;
+
+/// Leading bytes inspected when sniffing
+const SNIFF_BYTES: usize = 8192;
+
+/// Markup dialects read as tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupKind {
+    Html,
+    SpreadsheetMl,
+}
+
+/// Named tables of a markup document ("Table 1", ... for HTML, worksheet
+/// names for SpreadsheetML)
+pub type Tables = Vec<(String, Range<Data>)>;
+
+/// A markup document loaded into cell grids
+pub struct MarkupDocument {
+    pub tables: Tables,
+    /// "html" or "spreadsheetml"
+    pub file_type: &'static str,
+    pub encoding: EncodingInfo,
+}
+
+/// The markup dialect of a document starting with an element, if any
+pub fn sniff(data: &[u8]) -> Option<MarkupKind> {
+    let head = &data[..data.len().min(SNIFF_BYTES)];
+    let text = match Encoding::for_bom(head) {
+        Some((encoding, bom_len)) => encoding.decode_without_bom_handling(&head[bom_len..]).0,
+        None => String::from_utf8_lossy(head),
+    };
+    let name = root_element(&text)?;
+    if name == "workbook" || name.ends_with(":workbook") {
+        Some(MarkupKind::SpreadsheetMl)
+    } else {
+        Some(MarkupKind::Html)
+    }
+}
+
+/// Lowercased name of the first element, past any XML declaration,
+/// processing instructions, comments and doctype
+fn root_element(text: &str) -> Option<String> {
+    let mut rest = text.trim_start();
+    loop {
+        rest = rest.strip_prefix('<')?;
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.split_once("-->")?.1.trim_start();
+        } else if rest.starts_with(['?', '!']) {
+            rest = rest.split_once('>')?.1.trim_start();
+        } else {
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '_'))
+                .collect();
+            return (!name.is_empty() && name.starts_with(|c: char| c.is_ascii_alphabetic()))
+                .then(|| name.to_ascii_lowercase());
+        }
+    }
+}
+
+/// Decode a markup document and read its tables
+pub fn read(data: &[u8], kind: MarkupKind, encoding_override: Option<&str>) -> Result<MarkupDocument, CoreError> {
+    let (text, encoding) = encoding::decode(data, encoding_override)?;
+    let (tables, file_type): (Tables, _) = match kind {
+        MarkupKind::Html => (
+            html::read_tables(&text)
+                .into_iter()
+                .enumerate()
+                .map(|(idx, rows)| (format!("Table {}", idx + 1), rows_to_range(rows)))
+                .collect(),
+            "html",
+        ),
+        MarkupKind::SpreadsheetMl => (
+            spreadsheetml::read_worksheets(&text)?
+                .into_iter()
+                .map(|(name, rows)| (name, rows_to_range(rows)))
+                .collect(),
+            "spreadsheetml",
+        ),
+    };
+    if tables.is_empty() {
+        return Err(CoreError::ExcelParseError("No tables found".to_string()));
+    }
+    Ok(MarkupDocument { tables, file_type, encoding })
+}
+
+/// The tables chosen by the sheet selection in `options`, or all of them
+pub fn select(mut tables: Tables, options: Option<&ParseOptions>, all: bool) -> Result<Tables, CoreError> {
+    if all {
+        return Ok(tables);
+    }
+    let names: Vec<String> = tables.iter().map(|(name, _)| name.clone()).collect();
+    let selected = select_sheets(&names, options)?;
+    Ok(selected
+        .into_iter()
+        .filter_map(|name| {
+            let idx = tables.iter().position(|(n, _)| *n == name)?;
+            Some(tables.swap_remove(idx))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_root_element() {
+        let spreadsheet = "\u{FEFF}<?xml version=\"1.0\"?>\n<?mso-application progid=\"Excel.Sheet\"?>\n\
+            <Workbook xmlns=\"urn:schemas-microsoft-com:office:spreadsheet\">";
+        assert_eq!(sniff(spreadsheet.as_bytes()), Some(MarkupKind::SpreadsheetMl));
+        assert_eq!(sniff(b"<ss:Workbook>"), Some(MarkupKind::SpreadsheetMl));
+        assert_eq!(sniff(b"<!DOCTYPE html>\n<!-- export --><html>"), Some(MarkupKind::Html));
+        assert_eq!(sniff(b"  <table><tr><td>"), Some(MarkupKind::Html));
+        assert_eq!(sniff(b"account,ip\n"), None);
+        assert_eq!(sniff(b"<1,2>"), None);
+    }
+}
@@ -0,0 +1,463 @@
+//! Text PDF statements
+//!
+//! Only the text layer is read: every string a page shows becomes a
+//! `TextRun` at the position the content stream draws it. Glyph widths come
+//! from the font's `/Widths` (`/W` for CID fonts) and are estimated for fonts
+//! without them. Scanned statements have no text layer and are rejected;
+//! text inside form XObjects is not read.
+
+use crate::error::CoreError;
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Encoding, LoadOptions, Object, ObjectId};
+use std::collections::HashMap;
+
+/// Leading bytes searched for the "%PDF-" signature, which readers accept
+/// after some junk
+const SIGNATURE_WINDOW: usize = 1024;
+/// Largest decompressed stream accepted (page content, font CMaps)
+const MAX_STREAM_BYTES: usize = 64 * 1024 * 1024;
+/// Glyph width in thousandths of an em assumed for fonts without widths
+const DEFAULT_WIDTH: f64 = 500.0;
+/// Same for two-byte codes, which are mostly CJK ideographs
+const DEFAULT_CID_WIDTH: f64 = 1000.0;
+/// Largest character code of a CID font
+const MAX_CID: i64 = 0xFFFF;
+
+/// True if the data carries the PDF signature
+pub fn is_pdf(data: &[u8]) -> bool {
+    data[..data.len().min(SIGNATURE_WINDOW)].windows(5).any(|w| w == b"%PDF-")
+}
+
+/// A string shown on a page, in user space units (points, y upwards)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    /// Left and right edge
+    pub x0: f64,
+    pub x1: f64,
+    /// Baseline
+    pub y: f64,
+    /// Font size as drawn
+    pub size: f64,
+}
+
+/// Text runs of every page, in page order. An encrypted PDF is opened with
+/// `password`, or with the empty user password most banks use to only
+/// restrict printing.
+pub fn read_pages(data: &[u8], password: Option<&str>) -> Result<Vec<Vec<TextRun>>, CoreError> {
+    let options = LoadOptions {
+        password: password.map(str::to_string),
+        max_decompressed_size: Some(MAX_STREAM_BYTES),
+        ..Default::default()
+    };
+    let document = Document::load_mem_with_options(data, options).map_err(|e| match e {
+        lopdf::Error::InvalidPassword => CoreError::WrongPassword,
+        e => read_error(e),
+    })?;
+    if document.is_encrypted() {
+        return Err(CoreError::PasswordRequired);
+    }
+
+    let pages = document
+        .get_pages()
+        .into_values()
+        .map(|page| page_runs(&document, page))
+        .collect::<Result<Vec<_>, _>>()?;
+    if pages.iter().all(Vec::is_empty) {
+        return Err(CoreError::ExcelParseError(
+            "The PDF has no text layer (a scanned statement cannot be read)".to_string(),
+        ));
+    }
+    Ok(pages)
+}
+
+fn read_error(e: impl std::fmt::Display) -> CoreError {
+    CoreError::ExcelParseError(format!("Failed to read PDF: {}", e))
+}
+
+/// Follow a reference, leaving other objects as they are
+fn resolve<'a>(document: &'a Document, object: &'a Object) -> &'a Object {
+    document.dereference(object).map_or(object, |(_, object)| object)
+}
+
+fn number(object: &Object) -> Option<f64> {
+    object.as_float().ok().map(f64::from)
+}
+
+/// The text runs of one page, in drawing order
+fn page_runs(document: &Document, page: ObjectId) -> Result<Vec<TextRun>, CoreError> {
+    let fonts: HashMap<Vec<u8>, Font> = document
+        .get_page_fonts(page)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, font)| (name, Font::new(document, font)))
+        .collect();
+    let content = document.get_page_content_with_limit(page, MAX_STREAM_BYTES).map_err(read_error)?;
+    let content = Content::decode(&content).map_err(read_error)?;
+
+    let mut state = TextState::default();
+    let mut runs = Vec::new();
+    for operation in &content.operations {
+        let operands = &operation.operands;
+        let float = |idx: usize| operands.get(idx).and_then(number).unwrap_or(0.0);
+        match operation.operator.as_str() {
+            "q" => state.saved.push(state.ctm),
+            "Q" => state.ctm = state.saved.pop().unwrap_or(Matrix::IDENTITY),
+            "cm" => state.ctm = Matrix::from_operands(operands).multiply(&state.ctm),
+            "BT" => {
+                state.tm = Matrix::IDENTITY;
+                state.tlm = Matrix::IDENTITY;
+            }
+            "Tf" => {
+                state.font = operands.first().and_then(|o| o.as_name().ok()).map(<[u8]>::to_vec);
+                state.size = float(1);
+            }
+            "Tc" => state.char_spacing = float(0),
+            "Tw" => state.word_spacing = float(0),
+            "Tz" => state.scale = float(0) / 100.0,
+            "TL" => state.leading = float(0),
+            "Ts" => state.rise = float(0),
+            "Td" => state.next_line(float(0), float(1)),
+            "TD" => {
+                state.leading = -float(1);
+                state.next_line(float(0), float(1));
+            }
+            "Tm" => {
+                state.tm = Matrix::from_operands(operands);
+                state.tlm = state.tm;
+            }
+            "T*" => state.next_line(0.0, -state.leading),
+            "Tj" => {
+                if let Some(text) = operands.first().and_then(|o| o.as_str().ok()) {
+                    runs.extend(state.show(&fonts, text));
+                }
+            }
+            "'" => {
+                state.next_line(0.0, -state.leading);
+                if let Some(text) = operands.first().and_then(|o| o.as_str().ok()) {
+                    runs.extend(state.show(&fonts, text));
+                }
+            }
+            "\"" => {
+                state.word_spacing = float(0);
+                state.char_spacing = float(1);
+                state.next_line(0.0, -state.leading);
+                if let Some(text) = operands.get(2).and_then(|o| o.as_str().ok()) {
+                    runs.extend(state.show(&fonts, text));
+                }
+            }
+            "TJ" => {
+                for item in operands.first().and_then(|o| o.as_array().ok()).into_iter().flatten() {
+                    match item {
+                        Object::String(text, _) => runs.extend(state.show(&fonts, text)),
+                        // Kerning in thousandths of an em, positive moving left
+                        item => {
+                            let shift = -number(item).unwrap_or(0.0) / 1000.0 * state.size * state.scale;
+                            state.tm = Matrix::translate(shift, 0.0).multiply(&state.tm);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(runs)
+}
+
+/// An affine transform `[a b c d e f]`, applied to row vectors as PDF does
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Matrix([f64; 6]);
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn from_operands(operands: &[Object]) -> Matrix {
+        let mut values = Self::IDENTITY.0;
+        if operands.len() == 6 {
+            for (value, operand) in values.iter_mut().zip(operands) {
+                *value = number(operand).unwrap_or(*value);
+            }
+        }
+        Matrix(values)
+    }
+
+    fn translate(x: f64, y: f64) -> Matrix {
+        Matrix([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    /// `self` followed by `other`
+    fn multiply(&self, other: &Matrix) -> Matrix {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Matrix([
+            a1 * a2 + b1 * c2,
+            a1 * b2 + b1 * d2,
+            c1 * a2 + d1 * c2,
+            c1 * b2 + d1 * d2,
+            e1 * a2 + f1 * c2 + e2,
+            e1 * b2 + f1 * d2 + f2,
+        ])
+    }
+
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+}
+
+/// Graphics and text state needed to place text
+struct TextState {
+    ctm: Matrix,
+    saved: Vec<Matrix>,
+    tm: Matrix,
+    tlm: Matrix,
+    font: Option<Vec<u8>>,
+    size: f64,
+    char_spacing: f64,
+    word_spacing: f64,
+    /// Horizontal scaling as a factor
+    scale: f64,
+    leading: f64,
+    rise: f64,
+}
+
+impl Default for TextState {
+    fn default() -> Self {
+        Self {
+            ctm: Matrix::IDENTITY,
+            saved: Vec::new(),
+            tm: Matrix::IDENTITY,
+            tlm: Matrix::IDENTITY,
+            font: None,
+            size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+        }
+    }
+}
+
+impl TextState {
+    fn next_line(&mut self, x: f64, y: f64) {
+        self.tlm = Matrix::translate(x, y).multiply(&self.tlm);
+        self.tm = self.tlm;
+    }
+
+    /// Place a shown string and advance past it; `None` for blank text
+    fn show(&mut self, fonts: &HashMap<Vec<u8>, Font>, bytes: &[u8]) -> Option<TextRun> {
+        let font = self.font.as_ref().and_then(|name| fonts.get(name));
+        let (text, metrics) = match font {
+            Some(font) => (font.decode(bytes), font.measure(bytes)),
+            None => (
+                bytes.iter().map(|&b| char::from(b)).collect(),
+                Metrics {
+                    width: bytes.len() as f64 * DEFAULT_WIDTH,
+                    codes: bytes.len(),
+                    spaces: bytes.iter().filter(|&&b| b == b' ').count(),
+                },
+            ),
+        };
+        let advance = (metrics.width / 1000.0 * self.size
+            + self.char_spacing * metrics.codes as f64
+            + self.word_spacing * metrics.spaces as f64)
+            * self.scale;
+
+        let rendering = self.tm.multiply(&self.ctm);
+        let (start, y) = rendering.apply(0.0, self.rise);
+        let (end, _) = rendering.apply(advance, self.rise);
+        let [_, _, c, d, _, _] = rendering.0;
+        self.tm = Matrix::translate(advance, 0.0).multiply(&self.tm);
+
+        if text.trim().is_empty() {
+            return None;
+        }
+        Some(TextRun {
+            text,
+            x0: start.min(end),
+            x1: start.max(end),
+            y,
+            size: (self.size * c.hypot(d)).abs(),
+        })
+    }
+}
+
+/// Width of a shown string in glyph space
+struct Metrics {
+    /// Thousandths of an em
+    width: f64,
+    codes: usize,
+    /// Single-byte space codes, which word spacing applies to
+    spaces: usize,
+}
+
+/// What is needed of a font to decode and measure its strings
+struct Font<'a> {
+    encoding: Option<Encoding<'a>>,
+    /// Bytes per character code: 2 for composite (Type0) fonts
+    code_len: usize,
+    /// Glyph widths in thousandths of an em, by character code
+    widths: HashMap<u32, f64>,
+    default_width: f64,
+}
+
+impl<'a> Font<'a> {
+    fn new(document: &'a Document, font: &'a Dictionary) -> Self {
+        let encoding = font.get_font_encoding_with_limit(document, MAX_STREAM_BYTES).ok();
+        let composite = font.get(b"Subtype").and_then(Object::as_name).is_ok_and(|s| s == b"Type0");
+        let mut widths = HashMap::new();
+
+        if !composite {
+            let first = font.get(b"FirstChar").and_then(Object::as_i64).unwrap_or(0);
+            let listed = font.get_deref(b"Widths", document).and_then(Object::as_array);
+            for (idx, width) in listed.into_iter().flatten().enumerate() {
+                if let Some(width) = number(resolve(document, width)) {
+                    widths.insert(first as u32 + idx as u32, width);
+                }
+            }
+            return Font { encoding, code_len: 1, widths, default_width: DEFAULT_WIDTH };
+        }
+
+        let descendant = font
+            .get_deref(b"DescendantFonts", document)
+            .and_then(Object::as_array)
+            .ok()
+            .and_then(|fonts| fonts.first())
+            .and_then(|font| resolve(document, font).as_dict().ok());
+        let default_width = descendant
+            .and_then(|font| font.get(b"DW").ok())
+            .and_then(number)
+            .unwrap_or(DEFAULT_CID_WIDTH);
+        if let Some(listed) = descendant.and_then(|font| font.get_deref(b"W", document).ok()?.as_array().ok()) {
+            read_cid_widths(document, listed, &mut widths);
+        }
+        Font { encoding, code_len: 2, widths, default_width }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        self.encoding
+            .as_ref()
+            .and_then(|encoding| Document::decode_text(encoding, bytes).ok())
+            .unwrap_or_else(|| bytes.iter().map(|&b| char::from(b)).collect())
+    }
+
+    fn measure(&self, bytes: &[u8]) -> Metrics {
+        let mut metrics = Metrics { width: 0.0, codes: 0, spaces: 0 };
+        for code in bytes.chunks(self.code_len) {
+            let code = code.iter().fold(0u32, |code, &b| code << 8 | u32::from(b));
+            metrics.width += self.widths.get(&code).copied().unwrap_or(self.default_width);
+            metrics.codes += 1;
+            if self.code_len == 1 && code == u32::from(b' ') {
+                metrics.spaces += 1;
+            }
+        }
+        metrics
+    }
+}
+
+/// Read a CID font's `/W` array: `c [w1 w2 ...]` lists widths from code
+/// `c` on, `c_first c_last w` gives a range one width
+fn read_cid_widths(document: &Document, listed: &[Object], widths: &mut HashMap<u32, f64>) {
+    let mut items = listed.iter().map(|item| resolve(document, item));
+    while let Some(first) = items.next().and_then(|item| item.as_i64().ok()) {
+        if !(0..=MAX_CID).contains(&first) {
+            break;
+        }
+        match items.next() {
+            Some(Object::Array(list)) => {
+                for (idx, width) in list.iter().enumerate().take((MAX_CID - first + 1) as usize) {
+                    if let Some(width) = number(resolve(document, width)) {
+                        widths.insert(first as u32 + idx as u32, width);
+                    }
+                }
+            }
+            Some(last) => {
+                let (Ok(last), Some(width)) = (last.as_i64(), items.next().and_then(number)) else { break };
+                for code in first..=last.min(MAX_CID) {
+                    widths.insert(code as u32, width);
+                }
+            }
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    /// Runs of the first page, rounded to hundredths of a point
+    fn placed(document: &Document) -> Vec<(String, f64, f64, f64, f64)> {
+        let page = document.get_pages()[&1];
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        page_runs(document, page)
+            .expect("runs")
+            .into_iter()
+            .map(|r| (r.text, round(r.x0), round(r.x1), round(r.y), round(r.size)))
+            .collect()
+    }
+
+    /// One-page document showing `content` with a Helvetica font as /F1,
+    /// whose digits and space have their real widths
+    fn document(content: &str) -> Document {
+        let mut document = Document::with_version("1.5");
+        let mut widths = vec![Object::Integer(500); 95];
+        widths[0] = Object::Integer(278);
+        for digit in &mut widths[16..26] {
+            *digit = Object::Integer(556);
+        }
+        let font = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+            "FirstChar" => 32,
+            "Widths" => widths,
+        });
+        let pages = document.new_object_id();
+        let content = document.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+        let page = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages,
+            "Contents" => content,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        document.objects.insert(
+            pages,
+            Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => vec![page.into()], "Count" => 1 }),
+        );
+        let catalog = document.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages });
+        document.trailer.set("Root", catalog);
+        document
+    }
+
+    #[test]
+    fn test_runs_are_placed_by_text_and_graphics_matrices() {
+        let document = document(
+            "q 1 0 0 1 20 0 cm BT /F1 10 Tf 50 700 Td (2024) Tj 12 TL T* [(A) -2000 (B)] TJ ET Q \
+             BT /F1 10 Tf 2 0 0 2 100 500 Tm (  ) Tj (12) Tj ET",
+        );
+        let expected = [
+            ("2024", 70.0, 92.24, 700.0, 10.0),
+            ("A", 70.0, 75.0, 688.0, 10.0),
+            ("B", 95.0, 100.0, 688.0, 10.0),
+            ("12", 111.12, 133.36, 500.0, 20.0),
+        ];
+        // "2024" is 4 × 556/1000 × 10 wide; a -2000 kern moves 20 right
+        let expected: Vec<_> = expected.iter().map(|&(t, x0, x1, y, size)| (t.to_string(), x0, x1, y, size)).collect();
+        assert_eq!(placed(&document), expected);
+    }
+
+    #[test]
+    fn test_signature_and_missing_text_layer() {
+        assert!(is_pdf(b"\xEF\xBB\xBF%PDF-1.7\n"));
+        assert!(!is_pdf(b"account,ip\n"));
+
+        let mut bytes = Vec::new();
+        document("0 0 m 100 100 l S").save_to(&mut bytes).expect("save");
+        assert!(matches!(read_pages(&bytes, None), Err(CoreError::ExcelParseError(_))));
+    }
+}
@@ -0,0 +1,482 @@
+//! Statement tables rebuilt from the text layout of PDF pages
+//!
+//! Runs on one baseline make a line, and runs less than half an em apart
+//! make a segment, the text of one cell. The header line fixes the
+//! columns: each title's column reaches halfway to the next title, so
+//! left-, centre- and right-aligned values all land under their title.
+//! Every line below the header becomes a row of its page, numbered by its
+//! line on the page.
+//!
+//! Lines repeated across pages are left out: the header itself, the title
+//! block above it and single-text lines such as "Page 1 of 3". Rows the
+//! layout does not settle are kept and reported as uncertain.
+
+use super::pdf::{self, TextRun};
+use super::{header_map, profile_mapping, same_layout, ParseOptions, HEADER_SCAN_ROWS};
+use crate::error::CoreError;
+use crate::models::{UncertainReason, UncertainRow};
+use calamine::Data;
+use std::collections::{HashMap, HashSet};
+
+/// Baselines closer than this share a line, in ems
+const SAME_LINE: f64 = 0.4;
+/// Runs closer than this belong to one segment, in ems
+const SEGMENT_GAP: f64 = 0.5;
+/// Gap within a segment read as a space, in ems
+const WORD_GAP: f64 = 0.15;
+/// How far text may reach past the outer titles and still be in a column, in ems
+const OUTER_MARGIN: f64 = 4.0;
+/// Share of a segment's width that must lie in its column
+const CLEAR_SHARE: f64 = 0.75;
+
+/// A page of the rebuilt table
+pub struct TablePage {
+    /// "Page N"
+    pub name: String,
+    /// The header row, then the data rows
+    pub rows: Vec<Vec<Data>>,
+    /// Line of each row on the page; 0 for a header the page does not repeat
+    pub lines: Vec<usize>,
+}
+
+/// The statement table of a PDF, from the header's page on
+pub struct Table {
+    /// Pages holding rows, and always the header's page
+    pub pages: Vec<TablePage>,
+    pub uncertain_rows: Vec<UncertainRow>,
+}
+
+/// Read the table of a text PDF. The header line is the `header_row`
+/// option's line of the first page with text, or else the line of the
+/// first pages that best matches the known (and profile) header aliases
+/// of either file role.
+pub fn read(data: &[u8], options: Option<&ParseOptions>) -> Result<Table, CoreError> {
+    let pages = pdf::read_pages(data, options.and_then(|o| o.password.as_deref()))?;
+    let profile = options.and_then(|o| o.profile.as_ref());
+    let score = |headers: &[String]| {
+        let mapping = profile_mapping(profile, None, headers);
+        header_map::file_a_header_score(headers, mapping.as_ref())
+            .max(header_map::file_b_header_score(headers, mapping.as_ref()))
+    };
+    rebuild(pages, options.and_then(|o| o.header_row), &score)
+}
+
+/// Rebuild the table from the text runs of each page
+fn rebuild(
+    pages: Vec<Vec<TextRun>>,
+    header_row: Option<usize>,
+    score: &dyn Fn(&[String]) -> usize,
+) -> Result<Table, CoreError> {
+    let pages: Vec<Vec<Line>> = pages.into_iter().map(lines).collect();
+    let (header_page, header_idx) = find_header(&pages, header_row, score)?;
+    let header = &pages[header_page][header_idx];
+    let headers = header.texts();
+    let columns = Columns::new(&header.segments);
+
+    let title: HashSet<String> = pages[header_page][..header_idx].iter().map(Line::pattern).collect();
+    let repeated = repeated_lines(&pages[header_page..]);
+    let header_cells: Vec<Data> = headers.iter().map(|h| Data::String(h.clone())).collect();
+
+    let mut table = Table { pages: Vec::new(), uncertain_rows: Vec::new() };
+    for (page_idx, page) in pages.iter().enumerate().skip(header_page) {
+        let name = format!("Page {}", page_idx + 1);
+        // Rows start below the header where the page repeats it
+        let repeat = if page_idx == header_page {
+            Some(header_idx)
+        } else {
+            page.iter().position(|line| same_layout(&line.texts(), &headers))
+        };
+        let mut rows = vec![header_cells.clone()];
+        let mut numbers = vec![repeat.map_or(0, |idx| page[idx].number)];
+        let mut note = |row: usize, reason: UncertainReason, text: String| {
+            table.uncertain_rows.push(UncertainRow { sheet: Some(name.clone()), row, reason, text });
+        };
+
+        for line in &page[repeat.map_or(0, |idx| idx + 1)..] {
+            let pattern = line.pattern();
+            if title.contains(&pattern) || (line.segments.len() == 1 && repeated.contains(&pattern)) {
+                continue;
+            }
+            let (cells, issues) = columns.assign(line);
+            let wrapped = rows.len() > 1 && columns.is_continuation(&cells);
+            let row = if wrapped { numbers[numbers.len() - 1] } else { line.number };
+            for (reason, text) in issues {
+                note(row, reason, text);
+            }
+
+            if wrapped {
+                note(row, UncertainReason::WrappedLine, line.text());
+                let last = rows.last_mut().expect("data row");
+                for (cell, text) in last.iter_mut().zip(cells) {
+                    if let Data::String(text) = text {
+                        join_cell(cell, &text);
+                    }
+                }
+            } else {
+                rows.push(cells);
+                numbers.push(line.number);
+            }
+        }
+        if rows.len() > 1 || page_idx == header_page {
+            table.pages.push(TablePage { name, rows, lines: numbers });
+        }
+    }
+    Ok(table)
+}
+
+/// Page and line index of the header line
+fn find_header(
+    pages: &[Vec<Line>],
+    header_row: Option<usize>,
+    score: &dyn Fn(&[String]) -> usize,
+) -> Result<(usize, usize), CoreError> {
+    if let Some(row) = header_row {
+        let page = pages.iter().position(|lines| !lines.is_empty()).unwrap_or(0);
+        return match pages.get(page).and_then(|lines| lines.get(row.saturating_sub(1))) {
+            Some(_) => Ok((page, row.saturating_sub(1))),
+            None => Err(CoreError::ExcelParseError(format!(
+                "Header row {} is past the last line of page {}",
+                row,
+                page + 1
+            ))),
+        };
+    }
+
+    // Highest score wins; the earliest line wins ties
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (page_idx, lines) in pages.iter().enumerate() {
+        for (idx, line) in lines.iter().take(HEADER_SCAN_ROWS).enumerate() {
+            if line.segments.len() < 2 {
+                continue;
+            }
+            let line_score = score(&line.texts());
+            if line_score > 0 && best.is_none_or(|(_, _, s)| line_score > s) {
+                best = Some((page_idx, idx, line_score));
+            }
+        }
+    }
+    best.map(|(page, idx, _)| (page, idx)).ok_or_else(|| {
+        CoreError::ExcelParseError(
+            "No column header found in the PDF text; set the header row to the line with the column titles"
+                .to_string(),
+        )
+    })
+}
+
+/// Patterns of single-text lines found on at least two pages and on at
+/// least half of them: page numbers, footers, running titles
+fn repeated_lines(pages: &[Vec<Line>]) -> HashSet<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for lines in pages {
+        let patterns: HashSet<String> =
+            lines.iter().filter(|line| line.segments.len() == 1).map(Line::pattern).collect();
+        for pattern in patterns {
+            *counts.entry(pattern).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|&(_, count)| count >= 2 && count * 2 >= pages.len())
+        .map(|(pattern, _)| pattern)
+        .collect()
+}
+
+fn join_cell(cell: &mut Data, text: &str) {
+    match cell {
+        Data::String(existing) => {
+            existing.push(' ');
+            existing.push_str(text);
+        }
+        cell => *cell = Data::String(text.to_string()),
+    }
+}
+
+/// Text of one cell, with its horizontal extent
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    text: String,
+    x0: f64,
+    x1: f64,
+    size: f64,
+}
+
+/// A text line of a page
+struct Line {
+    /// 1-based line on the page
+    number: usize,
+    /// Left to right
+    segments: Vec<Segment>,
+}
+
+impl Line {
+    fn texts(&self) -> Vec<String> {
+        self.segments.iter().map(|s| s.text.clone()).collect()
+    }
+
+    /// Text as laid out, segments two spaces apart
+    fn text(&self) -> String {
+        self.texts().join("  ")
+    }
+
+    /// Text with digit runs masked, so "Page 2 of 3" repeats as "Page # of #"
+    fn pattern(&self) -> String {
+        let mut pattern = String::new();
+        for c in self.text().chars() {
+            if !c.is_ascii_digit() {
+                pattern.push(c);
+            } else if !pattern.ends_with('#') {
+                pattern.push('#');
+            }
+        }
+        pattern
+    }
+}
+
+/// Group the runs of a page into lines, top to bottom
+fn lines(mut runs: Vec<TextRun>) -> Vec<Line> {
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x0.total_cmp(&b.x0)));
+    let mut grouped: Vec<(f64, Vec<TextRun>)> = Vec::new();
+    for run in runs {
+        match grouped.last_mut() {
+            Some((y, line)) if (*y - run.y).abs() <= SAME_LINE * run.size.max(line[0].size) => line.push(run),
+            _ => grouped.push((run.y, vec![run])),
+        }
+    }
+    grouped
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (_, mut runs))| {
+            runs.sort_by(|a, b| a.x0.total_cmp(&b.x0));
+            Line { number: idx + 1, segments: segments(runs) }
+        })
+        .collect()
+}
+
+/// Join the runs of a line, left to right, into segments
+fn segments(runs: Vec<TextRun>) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for run in runs.into_iter().flat_map(split_spaced) {
+        let size = run.size.max(1.0);
+        match segments.last_mut() {
+            // Text drawn twice, slightly offset, to look bold
+            Some(last) if run.x0 < last.x1 - SEGMENT_GAP * size && last.text.ends_with(&run.text) => {}
+            Some(last) if run.x0 - last.x1 < SEGMENT_GAP * size.max(last.size) => {
+                if run.x0 - last.x1 > WORD_GAP * size {
+                    last.text.push(' ');
+                }
+                last.text.push_str(&run.text);
+                last.x1 = last.x1.max(run.x1);
+                last.size = last.size.max(run.size);
+            }
+            _ => segments.push(Segment { text: run.text, x0: run.x0, x1: run.x1, size: run.size }),
+        }
+    }
+    segments
+}
+
+/// Split a run at gaps of two or more spaces, as in text laid out with a
+/// monospaced font, and trim it. Positions within the run are estimated
+/// from the character count.
+fn split_spaced(run: TextRun) -> Vec<TextRun> {
+    let chars: Vec<char> = run.text.chars().collect();
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        if chars[idx].is_whitespace() {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        while idx < chars.len() && !chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        match pieces.last_mut() {
+            Some(piece) if start - piece.1 < 2 => piece.1 = idx,
+            _ => pieces.push((start, idx)),
+        }
+    }
+    if pieces == [(0, chars.len())] {
+        return vec![run];
+    }
+
+    let width = (run.x1 - run.x0) / chars.len().max(1) as f64;
+    pieces
+        .into_iter()
+        .map(|(start, end)| TextRun {
+            text: chars[start..end].iter().collect(),
+            x0: run.x0 + start as f64 * width,
+            x1: run.x0 + end as f64 * width,
+            y: run.y,
+            size: run.size,
+        })
+        .collect()
+}
+
+/// Horizontal extent of each column, from the header titles
+struct Columns {
+    bounds: Vec<(f64, f64)>,
+    /// Left edge of the first title and right edge of the last
+    left: f64,
+    right: f64,
+}
+
+impl Columns {
+    fn new(titles: &[Segment]) -> Self {
+        let bounds = (0..titles.len())
+            .map(|idx| {
+                let lo = match idx {
+                    0 => f64::NEG_INFINITY,
+                    idx => (titles[idx - 1].x1 + titles[idx].x0) / 2.0,
+                };
+                let hi = match titles.get(idx + 1) {
+                    Some(next) => (titles[idx].x1 + next.x0) / 2.0,
+                    None => f64::INFINITY,
+                };
+                (lo, hi)
+            })
+            .collect();
+        Self {
+            bounds,
+            left: titles.first().map_or(0.0, |t| t.x0),
+            right: titles.last().map_or(0.0, |t| t.x1),
+        }
+    }
+
+    /// Place the segments of a line in columns, with what was unclear
+    fn assign(&self, line: &Line) -> (Vec<Data>, Vec<(UncertainReason, String)>) {
+        let mut cells = vec![Data::Empty; self.bounds.len()];
+        let mut issues = Vec::new();
+        for segment in &line.segments {
+            let width = segment.x1 - segment.x0;
+            let overlap = |&(lo, hi): &(f64, f64)| segment.x1.min(hi) - segment.x0.max(lo);
+            let Some((col, best)) = self
+                .bounds
+                .iter()
+                .map(overlap)
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                continue;
+            };
+
+            let margin = OUTER_MARGIN * segment.size;
+            if segment.x1 < self.left - margin || segment.x0 > self.right + margin {
+                issues.push((UncertainReason::OutsideColumns, segment.text.clone()));
+            } else if width > 0.0 && best < CLEAR_SHARE * width {
+                issues.push((UncertainReason::SpansColumns, segment.text.clone()));
+            }
+            if cells[col] != Data::Empty {
+                issues.push((UncertainReason::SharedColumn, segment.text.clone()));
+            }
+            join_cell(&mut cells[col], &segment.text);
+        }
+        (cells, issues)
+    }
+
+    /// A line continuing the row above: nothing in the first column and
+    /// fewer than half of the columns filled
+    fn is_continuation(&self, cells: &[Data]) -> bool {
+        let filled = cells.iter().filter(|c| **c != Data::Empty).count();
+        cells.first() == Some(&Data::Empty) && filled * 2 < self.bounds.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, x0: f64, y: f64) -> TextRun {
+        let width = text.chars().map(|c| if c.is_ascii() { 5.0 } else { 10.0 }).sum::<f64>();
+        TextRun { text: text.to_string(), x0, x1: x0 + width, y, size: 10.0 }
+    }
+
+    fn strings(page: &TablePage) -> Vec<Vec<String>> {
+        page.rows.iter().map(|row| row.iter().map(|c| c.to_string()).collect()).collect()
+    }
+
+    fn score(headers: &[String]) -> usize {
+        header_map::file_a_header_score(headers, None)
+    }
+
+    #[test]
+    fn test_lines_and_segments() {
+        let line = lines(vec![
+            run("ACC1", 150.0, 699.0),
+            run("2024-01-15", 50.0, 700.0),
+            run("10:30", 104.0, 700.0),
+            run("500", 300.0, 700.2),
+            run("ABC   DEF", 400.0, 700.0),
+        ]);
+        assert_eq!(line.len(), 1);
+        assert_eq!(line[0].texts(), ["2024-01-15 10:30", "ACC1", "500", "ABC", "DEF"]);
+    }
+
+    #[test]
+    fn test_rebuild_skips_repeated_blocks_and_flags_uncertain_rows() {
+        let header = |y: f64| {
+            vec![run("交易時間", 50.0, y), run("帳號", 170.0, y), run("支出金額", 250.0, y), run("存入金額", 350.0, y)]
+        };
+        let mut first = vec![run("Statement 2024-01", 50.0, 800.0)];
+        first.extend(header(760.0));
+        first.extend([
+            run("2024-01-15 10:30:00", 50.0, 740.0),
+            run("ACC1", 160.0, 740.0),
+            run("1,250.00", 270.0, 740.0),
+            run("2024-01-15 11:00:00", 50.0, 720.0),
+            run("ACC1", 160.0, 720.0),
+            run("3,000", 370.0, 720.0),
+            run("ATM", 370.0, 708.0),
+            run("Page 1 of 2", 200.0, 40.0),
+        ]);
+        let mut second = vec![run("Statement 2024-02", 50.0, 800.0)];
+        second.extend(header(760.0));
+        second.extend([
+            run("2024-01-16 09:00:00", 50.0, 740.0),
+            run("ACC2", 160.0, 740.0),
+            run("7", 217.0, 740.0),
+            run("2024-01-17 09:00:00", 50.0, 720.0),
+            run("ACC2", 160.0, 720.0),
+            run("80", 600.0, 720.0),
+            run("Page 2 of 2", 200.0, 40.0),
+        ]);
+
+        let table = rebuild(vec![first, second], None, &score).expect("table");
+        assert_eq!(table.pages.len(), 2);
+        assert_eq!(table.pages[0].lines, [2, 3, 4]);
+        assert_eq!(
+            strings(&table.pages[0]),
+            [
+                vec!["交易時間", "帳號", "支出金額", "存入金額"],
+                vec!["2024-01-15 10:30:00", "ACC1", "1,250.00", ""],
+                vec!["2024-01-15 11:00:00", "ACC1", "", "3,000 ATM"],
+            ]
+        );
+        assert_eq!(table.pages[1].name, "Page 2");
+        assert_eq!(table.pages[1].lines, [2, 3, 4]);
+        assert_eq!(strings(&table.pages[1])[1], ["2024-01-16 09:00:00", "ACC2 7", "", ""]);
+
+        let uncertain: Vec<(&str, usize, UncertainReason)> = table
+            .uncertain_rows
+            .iter()
+            .map(|u| (u.sheet.as_deref().unwrap_or_default(), u.row, u.reason))
+            .collect();
+        assert_eq!(
+            uncertain,
+            [
+                ("Page 1", 4, UncertainReason::WrappedLine),
+                ("Page 2", 3, UncertainReason::SpansColumns),
+                ("Page 2", 3, UncertainReason::SharedColumn),
+                ("Page 2", 4, UncertainReason::OutsideColumns),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_header_row_option_and_missing_header() {
+        let page = vec![run("Date", 50.0, 700.0), run("Who", 150.0, 700.0), run("x", 50.0, 680.0)];
+        let table = rebuild(vec![page.clone()], Some(1), &score).expect("table");
+        assert_eq!(strings(&table.pages[0]), [vec!["Date", "Who"], vec!["x", ""]]);
+        assert!(rebuild(vec![page.clone()], None, &score).is_err());
+        assert!(rebuild(vec![page], Some(9), &score).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Excel 2003 XML (SpreadsheetML) workbooks
+//!
+//! Older banking systems write the `urn:schemas-microsoft-com:office:spreadsheet`
+//! XML format under an .xls name. Each `<Worksheet>` becomes a named table.
+//! `ss:Index` on rows and cells skips ahead and `ss:MergeAcross` leaves
+//! empty cells behind, so positions match what Excel shows.
+
+use crate::error::CoreError;
+use calamine::Data;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Excel's sheet size, the most `ss:Index` can skip to
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+
+/// Worksheet names with their cell rows, in workbook order
+pub type Worksheets = Vec<(String, Vec<Vec<Data>>)>;
+
+/// Read every worksheet of a SpreadsheetML document
+pub fn read_worksheets(xml: &str) -> Result<Worksheets, CoreError> {
+    let mut sheets: Worksheets = Vec::new();
+    // Column of the open cell, and type and text of its `<Data>` element
+    let mut cell: Option<usize> = None;
+    let mut data: Option<(String, String)> = None;
+    let mut reader = Reader::from_str(xml);
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| CoreError::ExcelParseError(format!("Failed to read SpreadsheetML: {}", e)))?;
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let has_content = matches!(event, Event::Start(_));
+                match element.local_name().as_ref() {
+                    b"Worksheet" => {
+                        let name = attribute(element, &reader, "Name")
+                            .unwrap_or_else(|| format!("Sheet{}", sheets.len() + 1));
+                        sheets.push((name, Vec::new()));
+                    }
+                    b"Row" => {
+                        if let Some((_, rows)) = sheets.last_mut() {
+                            if let Some(index) = index_of(element, &reader, MAX_ROWS) {
+                                rows.resize(index.max(rows.len() + 1) - 1, Vec::new());
+                            }
+                            rows.push(Vec::new());
+                        }
+                    }
+                    b"Cell" => {
+                        if let Some(row) = sheets.last_mut().and_then(|(_, rows)| rows.last_mut()) {
+                            if let Some(index) = index_of(element, &reader, MAX_COLUMNS) {
+                                row.resize(index.max(row.len() + 1) - 1, Data::Empty);
+                            }
+                            cell = Some(row.len());
+                            row.push(Data::Empty);
+                            let merged = attribute(element, &reader, "MergeAcross")
+                                .and_then(|v| v.trim().parse::<usize>().ok())
+                                .unwrap_or(0)
+                                .min(1000);
+                            row.resize(row.len() + merged, Data::Empty);
+                        }
+                    }
+                    b"Data" if has_content => {
+                        let kind = attribute(element, &reader, "Type").unwrap_or_default();
+                        data = Some((kind, String::new()));
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) => {
+                if let Some((_, value)) = &mut data {
+                    let text = text
+                        .unescape()
+                        .map_err(|e| CoreError::ExcelParseError(format!("Failed to read SpreadsheetML: {}", e)))?;
+                    value.push_str(&text);
+                }
+            }
+            Event::CData(text) => {
+                if let Some((_, value)) = &mut data {
+                    value.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Event::End(element) if element.local_name().as_ref() == b"Data" => {
+                if let Some((kind, value)) = data.take() {
+                    let row = sheets.last_mut().and_then(|(_, rows)| rows.last_mut());
+                    if let Some(slot) = row.zip(cell).and_then(|(row, col)| row.get_mut(col)) {
+                        *slot = value_of(&kind, value);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+/// Typed cell value of a `<Data ss:Type="...">` element
+fn value_of(kind: &str, value: String) -> Data {
+    match kind {
+        "Number" => value.trim().parse().map_or(Data::String(value), Data::Float),
+        "Boolean" => Data::Bool(value.trim() == "1"),
+        // "2024-01-15T10:30:00.000" is written as the timestamp parser reads it
+        "DateTime" => Data::String(value.trim().trim_end_matches(".000").replacen('T', " ", 1)),
+        _ if value.trim().is_empty() => Data::Empty,
+        _ => Data::String(value),
+    }
+}
+
+/// Value of an attribute by local name, ignoring its namespace prefix
+fn attribute(element: &BytesStart, reader: &Reader<&[u8]>, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|a| a.decode_and_unescape_value(reader).ok())
+        .map(|v| v.into_owned())
+}
+
+/// 1-based `ss:Index` of a row or cell, ignored past Excel's sheet limits
+fn index_of(element: &BytesStart, reader: &Reader<&[u8]>, max: usize) -> Option<usize> {
+    attribute(element, reader, "Index")
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&index| index > 0 && index <= max)
+}
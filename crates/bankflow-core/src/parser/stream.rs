@@ -0,0 +1,490 @@
+//! Row-by-row input for the streaming parser
+//!
+//! Walks the selected worksheets (or the text file) one row at a time
+//! instead of loading them into a `Range`. xlsx and xlsb sheets are read
+//! with calamine's cell readers and CSV/TSV with the csv reader over the
+//! decoded text. calamine has no cell reader for xls and ods, so those are
+//! loaded one sheet at a time; JSON needs every record's field names for
+//! its header row, HTML and SpreadsheetML tables are only known once the
+//! markup is read, and a PDF's columns only once its header line is found,
+//! so those are loaded whole as well. A fixed-width report is read whole to
+//! drop the header and footer lines of each page.
+
+use super::{
+    apply_profile, best_header_idx, cell_to_string, delimited, encoding, encrypted, fixed_width, fixed_width_layout,
+    input_format, json, markup, pdf_table, profile_mapping, profile_of, same_layout, select_sheets, InputFormat, ParseOptions,
+    RecordReader, TimestampRule, HEADER_SCAN_ROWS,
+};
+use crate::error::CoreError;
+use crate::merge::SourcedRecord;
+use crate::models::{EncodingInfo, FileMetadata, ParseProgress, ParseReport, UncertainRow};
+use crate::profile::FormatProfile;
+use calamine::{open_workbook_auto_from_rs, Cell, Data, DataRef, Dimensions, Reader, Sheets};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::Cursor;
+
+/// Streaming counterpart of `parse_records`: rows go through `R` as they are
+/// read and the records leave in chunks, so no sheet is held in memory
+pub fn stream_records<R: RecordReader>(
+    data: &[u8],
+    filename: &str,
+    mapping: Option<&HashMap<String, String>>,
+    options: Option<&ParseOptions>,
+    chunk_size: usize,
+    on_chunk: impl FnMut(Vec<R::Record>, &ParseProgress),
+) -> Result<FileMetadata, CoreError> {
+    let options = options.map(ParseOptions::with_profile_defaults);
+    let options = options.as_ref();
+    let mut stream = RecordStream::<R, _> {
+        filename,
+        // JSON field names are always row 1; a PDF page starts with its
+        // header, numbered 0 where the page does not repeat it, and the
+        // field names of a fixed-width layout are row 0
+        forced_header_row: match input_format(data, options) {
+            InputFormat::Json => Some(1),
+            InputFormat::Pdf | InputFormat::FixedWidth => Some(0),
+            _ => options.and_then(|o| o.header_row),
+        },
+        profile: profile_of(options, R::KIND)?,
+        mapping,
+        timestamps: TimestampRule::from_options(options)?,
+        chunk_size: chunk_size.max(1),
+        on_chunk,
+        reader: None,
+        sheet: None,
+        sheets: Vec::new(),
+        header_row: None,
+        report: ParseReport::default(),
+        chunk: Vec::new(),
+        progress: ParseProgress::default(),
+        row_count: 0,
+        column_count: 0,
+    };
+
+    let source = stream_rows(data, options, &mut |event| match event {
+        RowEvent::Sheet { name, width } => stream.start_sheet(name, width),
+        RowEvent::Row { number, cells, fraction } => stream.push_row(number, cells, fraction),
+    })?;
+    stream.finish(source)
+}
+
+/// Parsing state of `stream_records`
+struct RecordStream<'a, R: RecordReader, F> {
+    filename: &'a str,
+    forced_header_row: Option<usize>,
+    profile: Option<&'a FormatProfile>,
+    mapping: Option<&'a HashMap<String, String>>,
+    timestamps: TimestampRule,
+    chunk_size: usize,
+    on_chunk: F,
+    /// Built from the first sheet's header row
+    reader: Option<R>,
+    sheet: Option<SheetState>,
+    sheets: Vec<String>,
+    /// 1-based header row of the first sheet
+    header_row: Option<usize>,
+    report: ParseReport,
+    chunk: Vec<R::Record>,
+    progress: ParseProgress,
+    row_count: usize,
+    column_count: usize,
+}
+
+/// The sheet being streamed
+struct SheetState {
+    name: Option<String>,
+    width: usize,
+    /// Leading rows kept until the header row is located
+    pending: Vec<(usize, Vec<Data>)>,
+    header_located: bool,
+    first_row: Option<usize>,
+    last_row: usize,
+}
+
+impl<R: RecordReader, F: FnMut(Vec<R::Record>, &ParseProgress)> RecordStream<'_, R, F> {
+    fn start_sheet(&mut self, name: Option<String>, width: usize) -> Result<(), CoreError> {
+        self.finish_sheet()?;
+        self.sheets.extend(name.clone());
+        self.sheet = Some(SheetState {
+            name,
+            width,
+            pending: Vec::new(),
+            header_located: false,
+            first_row: None,
+            last_row: 0,
+        });
+        Ok(())
+    }
+
+    fn push_row(&mut self, number: usize, cells: Vec<Data>, fraction: f64) -> Result<(), CoreError> {
+        self.progress.fraction = fraction.min(1.0);
+        let Some(sheet) = self.sheet.as_mut() else { return Ok(()) };
+        sheet.first_row.get_or_insert(number);
+        sheet.last_row = number;
+        self.column_count = self.column_count.max(sheet.width).max(cells.len());
+
+        if sheet.header_located {
+            return self.read_row(number, cells);
+        }
+        match self.forced_header_row {
+            // As with `LoadedInput`, a header row above the sheet's first
+            // row falls back to that first row
+            Some(forced) if number >= forced => self.set_headers(number, &cells),
+            Some(_) => Ok(()),
+            None => {
+                sheet.pending.push((number, cells));
+                if sheet.pending.len() == HEADER_SCAN_ROWS {
+                    self.locate_headers()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Pick the header row among the buffered rows and read the rows below it
+    fn locate_headers(&mut self) -> Result<(), CoreError> {
+        let pending = match self.sheet.as_mut() {
+            Some(sheet) => std::mem::take(&mut sheet.pending),
+            None => return Ok(()),
+        };
+        let (profile, mapping) = (self.profile, self.mapping);
+        let score = |h: &[String]| R::header_score(h, profile_mapping(profile, mapping, h).as_ref());
+        let idx = best_header_idx(pending.iter().map(|(_, row)| row.as_slice()), &score);
+
+        let mut rows = pending.into_iter().skip(idx);
+        match rows.next() {
+            Some((number, cells)) => self.set_headers(number, &cells)?,
+            None => self.set_headers(1, &[])?,
+        }
+        for (number, cells) in rows {
+            self.read_row(number, cells)?;
+        }
+        Ok(())
+    }
+
+    /// Build the reader from the first sheet's headers; later sheets must
+    /// share its layout
+    fn set_headers(&mut self, number: usize, cells: &[Data]) -> Result<(), CoreError> {
+        if let Some(sheet) = self.sheet.as_mut() {
+            sheet.header_located = true;
+        }
+        let headers: Vec<String> = cells.iter().map(cell_to_string).collect();
+        match &self.reader {
+            None => {
+                self.header_row = Some(number);
+                let mapping = profile_mapping(self.profile, self.mapping, &headers);
+                self.reader = Some(R::new(headers, mapping.as_ref(), self.profile, self.timestamps.clone())?);
+            }
+            Some(reader) if !same_layout(reader.headers(), &headers) => {
+                return Err(CoreError::ExcelParseError(format!(
+                    "Sheet '{}' has a different header layout from '{}'",
+                    self.sheets.last().map(String::as_str).unwrap_or_default(),
+                    self.sheets.first().map(String::as_str).unwrap_or_default(),
+                )));
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
+    fn read_row(&mut self, number: usize, mut cells: Vec<Data>) -> Result<(), CoreError> {
+        let (Some(reader), Some(sheet)) = (&self.reader, &self.sheet) else { return Ok(()) };
+        // Pad like a `Range` row so raw columns line up with the headers
+        let width = sheet.width.max(reader.headers().len());
+        if cells.len() < width {
+            cells.resize(width, Data::Empty);
+        }
+
+        for mut record in reader.read(&sheet.name, number, &cells, &mut self.report) {
+            record.set_source_file(self.filename);
+            self.chunk.push(record);
+            self.progress.records += 1;
+        }
+        self.progress.rows_read = self.report.rows_read;
+        if self.chunk.len() >= self.chunk_size {
+            self.flush();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        let chunk = std::mem::take(&mut self.chunk);
+        (self.on_chunk)(chunk, &self.progress);
+    }
+
+    fn finish_sheet(&mut self) -> Result<(), CoreError> {
+        let Some(sheet) = self.sheet.as_ref() else { return Ok(()) };
+        if !sheet.header_located {
+            if sheet.pending.is_empty() {
+                // Nothing at or below the header row
+                self.set_headers(self.forced_header_row.unwrap_or(1), &[])?;
+            } else {
+                self.locate_headers()?;
+            }
+        }
+        if let Some(sheet) = self.sheet.take() {
+            if let Some(first_row) = sheet.first_row {
+                self.row_count += sheet.last_row + 1 - first_row;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, source: StreamSource) -> Result<FileMetadata, CoreError> {
+        self.finish_sheet()?;
+        self.progress.fraction = 1.0;
+        self.flush();
+        self.report.uncertain_rows.extend(source.uncertain_rows);
+
+        let mut metadata = FileMetadata {
+            path: None,
+            filename: self.filename.to_string(),
+            row_count: self.row_count,
+            column_count: self.column_count,
+            file_type: source.file_type,
+            encoding: source.encoding,
+            sheets: self.sheets,
+            header_row: self.header_row,
+            headers: self.reader.as_ref().map(|r| r.headers().to_vec()).unwrap_or_default(),
+            timezone: self.timestamps.zone.map(|z| z.to_string()),
+            report: self.report,
+            profile: None,
+            sensitive_columns: Vec::new(),
+        };
+        let headers = metadata.headers.clone();
+        apply_profile(&mut metadata, self.profile, &headers);
+        Ok(metadata)
+    }
+}
+
+/// What the row source reports, in file order
+pub enum RowEvent {
+    /// A new sheet starts; `width` is its column count where known up front
+    Sheet { name: Option<String>, width: usize },
+    /// A row with its 1-based row number and the share of the input read so far
+    Row { number: usize, cells: Vec<Data>, fraction: f64 },
+}
+
+/// File type and encoding of a streamed input
+pub struct StreamSource {
+    pub file_type: String,
+    pub encoding: Option<EncodingInfo>,
+    /// PDF rows whose layout was unclear
+    pub uncertain_rows: Vec<UncertainRow>,
+}
+
+/// Pass every row of the selected sheets to `visit`.
+///
+/// Rows within a sheet come in order with no gaps: rows without any cell
+/// are reported as empty, as they appear in a `Range`. Leading empty rows
+/// of a worksheet are left out, as calamine does.
+pub fn stream_rows(
+    data: &[u8],
+    options: Option<&ParseOptions>,
+    visit: &mut dyn FnMut(RowEvent) -> Result<(), CoreError>,
+) -> Result<StreamSource, CoreError> {
+    let data = &*encrypted::unlock(data, options.and_then(|o| o.password.as_deref()))?;
+    match input_format(data, options) {
+        InputFormat::Workbook => stream_workbook(data, options, visit),
+        InputFormat::Delimited => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let (text, encoding) = encoding::decode(data, encoding_override)?;
+            let dialect = delimited::sniff_dialect(&text);
+            let total = text.len().max(1) as f64;
+
+            visit(RowEvent::Sheet { name: None, width: 0 })?;
+            delimited::for_each_row(&text, dialect, |number, cells, offset| {
+                visit(RowEvent::Row { number, cells, fraction: offset as f64 / total })
+            })?;
+            Ok(StreamSource {
+                file_type: dialect.file_type().to_string(),
+                encoding: Some(encoding),
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Json => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let sheet = json::read_range(data, encoding_override)?;
+            let total = sheet.range.height().max(1) as f64;
+
+            visit(RowEvent::Sheet { name: None, width: sheet.range.width() })?;
+            for (idx, row) in sheet.range.rows().enumerate() {
+                visit(RowEvent::Row { number: idx + 1, cells: row.to_vec(), fraction: (idx + 1) as f64 / total })?;
+            }
+            Ok(StreamSource {
+                file_type: sheet.file_type.to_string(),
+                encoding: Some(sheet.encoding),
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Markup(kind) => {
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let document = markup::read(data, kind, encoding_override)?;
+            let tables = markup::select(document.tables, options, false)?;
+            let table_count = tables.len() as f64;
+
+            for (idx, (name, range)) in tables.into_iter().enumerate() {
+                let height = range.height().max(1) as f64;
+                visit(RowEvent::Sheet { name: Some(name), width: range.width() })?;
+                for (row_idx, row) in range.rows().enumerate() {
+                    let fraction = (idx as f64 + (row_idx + 1) as f64 / height) / table_count;
+                    visit(RowEvent::Row { number: row_idx + 1, cells: row.to_vec(), fraction })?;
+                }
+            }
+            Ok(StreamSource {
+                file_type: document.file_type.to_string(),
+                encoding: Some(document.encoding),
+                uncertain_rows: Vec::new(),
+            })
+        }
+        InputFormat::Pdf => {
+            let table = pdf_table::read(data, options)?;
+            let page_count = table.pages.len().max(1) as f64;
+
+            for (idx, page) in table.pages.into_iter().enumerate() {
+                let height = page.rows.len().max(1) as f64;
+                let width = page.rows.first().map_or(0, Vec::len);
+                visit(RowEvent::Sheet { name: Some(page.name), width })?;
+                for (row_idx, (number, cells)) in page.lines.into_iter().zip(page.rows).enumerate() {
+                    let fraction = (idx as f64 + (row_idx + 1) as f64 / height) / page_count;
+                    visit(RowEvent::Row { number, cells, fraction })?;
+                }
+            }
+            Ok(StreamSource {
+                file_type: "pdf".to_string(),
+                encoding: None,
+                uncertain_rows: table.uncertain_rows,
+            })
+        }
+        InputFormat::FixedWidth => {
+            let layout = fixed_width_layout(options).expect("fixed-width input has a layout");
+            let encoding_override = options.and_then(|o| o.encoding.as_deref());
+            let table = fixed_width::read(data, layout, encoding_override)?;
+            let height = table.rows.len() as f64;
+
+            visit(RowEvent::Sheet { name: None, width: layout.fields.len() })?;
+            for (idx, (number, cells)) in table.lines.into_iter().zip(table.rows).enumerate() {
+                visit(RowEvent::Row { number, cells, fraction: (idx + 1) as f64 / height })?;
+            }
+            Ok(StreamSource {
+                file_type: "fixed_width".to_string(),
+                encoding: Some(table.encoding),
+                uncertain_rows: Vec::new(),
+            })
+        }
+    }
+}
+
+fn stream_workbook(
+    data: &[u8],
+    options: Option<&ParseOptions>,
+    visit: &mut dyn FnMut(RowEvent) -> Result<(), CoreError>,
+) -> Result<StreamSource, CoreError> {
+    let cursor = Cursor::new(data);
+    let mut workbook: Sheets<_> = open_workbook_auto_from_rs(cursor)
+        .map_err(|e| CoreError::ExcelParseError(format!("Failed to open file: {}", e)))?;
+
+    let sheet_names = workbook.sheet_names();
+    if sheet_names.is_empty() {
+        return Err(CoreError::ExcelParseError("No sheets found".to_string()));
+    }
+    let selected = select_sheets(&sheet_names, options)?;
+    let sheet_count = selected.len() as f64;
+
+    let file_type = match &workbook {
+        Sheets::Xls(_) => "xls",
+        Sheets::Xlsx(_) => "xlsx",
+        Sheets::Xlsb(_) => "xlsb",
+        Sheets::Ods(_) => "ods",
+    };
+    for (idx, name) in selected.into_iter().enumerate() {
+        let read_error = |e: &dyn Display| {
+            CoreError::ExcelParseError(format!("Failed to read sheet '{}': {}", name, e))
+        };
+        // Progress within this sheet, scaled to the whole selection
+        let scale = |fraction: f64| (idx as f64 + fraction) / sheet_count;
+
+        match &mut workbook {
+            Sheets::Xlsx(xlsx) => {
+                let mut reader = xlsx.worksheet_cells_reader(&name).map_err(|e| read_error(&e))?;
+                let dimensions = reader.dimensions();
+                visit(RowEvent::Sheet { name: Some(name.clone()), width: width_of(dimensions) })?;
+                stream_cells(|| reader.next_cell().map_err(|e| read_error(&e)), dimensions, &scale, visit)?;
+            }
+            Sheets::Xlsb(xlsb) => {
+                let mut reader = xlsb.worksheet_cells_reader(&name).map_err(|e| read_error(&e))?;
+                let dimensions = reader.dimensions();
+                visit(RowEvent::Sheet { name: Some(name.clone()), width: width_of(dimensions) })?;
+                stream_cells(|| reader.next_cell().map_err(|e| read_error(&e)), dimensions, &scale, visit)?;
+            }
+            workbook => {
+                let range = workbook.worksheet_range(&name).map_err(|e| read_error(&e))?;
+                let first_row = range.start().map_or(0, |(row, _)| row as usize);
+                let height = range.height().max(1) as f64;
+                visit(RowEvent::Sheet { name: Some(name.clone()), width: range.width() })?;
+                for (idx, row) in range.rows().enumerate() {
+                    visit(RowEvent::Row {
+                        number: first_row + idx + 1,
+                        cells: row.to_vec(),
+                        fraction: scale((idx + 1) as f64 / height),
+                    })?;
+                }
+            }
+        }
+    }
+
+    Ok(StreamSource {
+        file_type: file_type.to_string(),
+        encoding: None,
+        uncertain_rows: Vec::new(),
+    })
+}
+
+fn width_of(dimensions: Dimensions) -> usize {
+    (dimensions.end.1 + 1).saturating_sub(dimensions.start.1) as usize
+}
+
+/// Group the cells of a cell reader into rows. Columns are counted from the
+/// sheet's declared dimensions, which `Range` also starts from for sheets
+/// written by Excel and most exporters.
+fn stream_cells<'a>(
+    mut next_cell: impl FnMut() -> Result<Option<Cell<DataRef<'a>>>, CoreError>,
+    dimensions: Dimensions,
+    scale: &dyn Fn(f64) -> f64,
+    visit: &mut dyn FnMut(RowEvent) -> Result<(), CoreError>,
+) -> Result<(), CoreError> {
+    let first_col = dimensions.start.1;
+    let last_row = dimensions.end.0.max(1) as f64;
+    let mut emit = |number: u32, cells: Vec<Data>| {
+        let fraction = scale((number as f64 / last_row).min(1.0));
+        visit(RowEvent::Row { number: number as usize + 1, cells, fraction })
+    };
+
+    let mut current: Option<(u32, Vec<Data>)> = None;
+    while let Some(cell) = next_cell()? {
+        if cell.get_value() == &DataRef::Empty {
+            continue;
+        }
+        let (row, col) = cell.get_position();
+        let Some(col) = col.checked_sub(first_col) else { continue };
+
+        if current.as_ref().is_some_and(|(r, _)| *r != row) {
+            let (done, cells) = current.take().unwrap_or_default();
+            emit(done, cells)?;
+            for gap in done + 1..row {
+                emit(gap, Vec::new())?;
+            }
+        }
+        let (_, cells) = current.get_or_insert_with(|| (row, Vec::new()));
+        let col = col as usize;
+        if cells.len() <= col {
+            cells.resize(col + 1, Data::Empty);
+        }
+        cells[col] = cell.get_value().clone().into();
+    }
+    if let Some((row, cells)) = current {
+        emit(row, cells)?;
+    }
+    Ok(())
+}
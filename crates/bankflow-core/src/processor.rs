@@ -17,22 +17,41 @@ impl Processor {
         }
     }
 
+    /// Processor hiding the given raw columns instead of the default set
+    /// (e.g. the sensitive columns of a format profile)
+    pub fn with_sensitive_columns(hide_sensitive: bool, sensitive_columns: Vec<usize>) -> Self {
+        Self {
+            hide_sensitive,
+            sensitive_columns,
+        }
+    }
+
     pub fn process(&self, transactions: &mut [Transaction]) {
+        self.process_with_headers(transactions, &mut Vec::new());
+    }
+
+    /// Process transactions together with the file's raw column headers, so
+    /// hidden columns are dropped from both and the headers stay aligned
+    pub fn process_with_headers(&self, transactions: &mut [Transaction], headers: &mut Vec<String>) {
         if self.hide_sensitive {
-            self.hide_columns(transactions);
+            self.hide_columns(transactions, headers);
         }
     }
 
-    fn hide_columns(&self, transactions: &mut [Transaction]) {
+    fn hide_columns(&self, transactions: &mut [Transaction], headers: &mut Vec<String>) {
         let mut indices = self.sensitive_columns.clone();
         indices.sort_by(|a, b| b.cmp(a));
 
-        for tx in transactions.iter_mut() {
+        let remove = |columns: &mut Vec<String>| {
             for &idx in &indices {
-                if idx < tx.raw_columns.len() {
-                    tx.raw_columns.remove(idx);
+                if idx < columns.len() {
+                    columns.remove(idx);
                 }
             }
+        };
+        remove(headers);
+        for tx in transactions.iter_mut() {
+            remove(&mut tx.raw_columns);
         }
     }
 
@@ -41,13 +60,13 @@ impl Processor {
     ) -> (Vec<Transaction>, Vec<Transaction>) {
         let income: Vec<Transaction> = transactions
             .iter()
-            .filter(|tx| tx.income.map_or(false, |v| v > 0.0))
+            .filter(|tx| tx.income.is_some_and(|v| v > 0.0))
             .cloned()
             .collect();
 
         let expense: Vec<Transaction> = transactions
             .iter()
-            .filter(|tx| tx.expense.map_or(false, |v| v > 0.0))
+            .filter(|tx| tx.expense.is_some_and(|v| v > 0.0))
             .cloned()
             .collect();
 
@@ -66,8 +85,10 @@ pub struct ProcessingStats {
 
 impl ProcessingStats {
     pub fn from_transactions(transactions: &[Transaction]) -> Self {
-        let mut stats = ProcessingStats::default();
-        stats.total = transactions.len();
+        let mut stats = ProcessingStats {
+            total: transactions.len(),
+            ..Default::default()
+        };
 
         for tx in transactions {
             if let Some(income) = tx.income {
@@ -99,7 +120,13 @@ mod tests {
             account: "ACC001".to_string(),
             income,
             expense,
+            counterparty_account: None,
+            counterparty_name: None,
+            balance: None,
+            currency: None,
+            memo: None,
             matched_ip: None,
+            matched_login: Default::default(),
             ip_country: None,
             ip_isp: None,
             raw_columns: vec![
@@ -112,6 +139,8 @@ mod tests {
                 "col6".to_string(),
             ],
             row_index: 1,
+            sheet: None,
+            source_file: None,
         }
     }
 
@@ -127,6 +156,34 @@ mod tests {
         assert!(transactions[0].raw_columns.len() < 7);
     }
 
+    #[test]
+    fn test_processor_custom_sensitive_columns() {
+        let mut transactions = vec![create_test_transaction(None, None)];
+
+        let processor = Processor::with_sensitive_columns(true, vec![0, 6]);
+        processor.process(&mut transactions);
+
+        assert_eq!(
+            transactions[0].raw_columns,
+            vec!["col1", "col2", "col3", "col4", "col5"]
+        );
+    }
+
+    #[test]
+    fn test_processor_hides_headers_with_columns() {
+        let mut transactions = vec![create_test_transaction(None, None)];
+        let mut headers: Vec<String> = (0..7).map(|i| format!("head{}", i)).collect();
+
+        let processor = Processor::new(true);
+        processor.process_with_headers(&mut transactions, &mut headers);
+
+        assert_eq!(headers, vec!["head0", "head1", "head3", "head4", "head6"]);
+        assert_eq!(
+            transactions[0].raw_columns,
+            vec!["col0", "col1", "col3", "col4", "col6"]
+        );
+    }
+
     #[test]
     fn test_processor_no_hide() {
         let mut transactions = vec![create_test_transaction(None, None)];
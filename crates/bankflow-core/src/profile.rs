@@ -0,0 +1,494 @@
+//! Named format profiles for bank and ISP exports
+//!
+//! A profile records how one institution lays out its export: header aliases
+//! for each field, the amount layout, the timestamp format, the text encoding
+//! and which columns are sensitive. Profiles are stored as JSON or TOML so they
+//! can be shared, and the parser can pick one by fingerprinting the header row.
+//! A profile of a fixed-width text report also gives the character positions
+//! of its fields, which no header row could reveal.
+
+use crate::error::CoreError;
+use crate::timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Current profile schema version
+pub const PROFILE_VERSION: u32 = 1;
+
+/// Mapping keys a transaction (File A) profile may use
+const TRANSACTION_KEYS: [&str; 13] = [
+    "timestamp", "date", "time", "account", "expense", "income", "amount", "direction",
+    "counterparty_account", "counterparty_name", "balance", "currency", "memo",
+];
+
+/// Mapping keys an IP log (File B) profile may use
+const IP_LOG_KEYS: [&str; 10] = [
+    "timestamp", "date", "time", "account", "ip_address",
+    "device_id", "user_agent", "channel", "login_result", "session_id",
+];
+
+/// Which input a profile describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    /// Bank statement (File A)
+    Transactions,
+    /// Login / IP log (File B)
+    IpLog,
+}
+
+/// How a statement records money going out and coming in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountMode {
+    /// Separate `expense` and `income` columns
+    Split,
+    /// One signed `amount` column
+    Signed,
+    /// An `amount` column plus a 借/貸 `direction` column
+    Indicator,
+}
+
+impl AmountMode {
+    fn required_keys(&self) -> &'static [&'static str] {
+        match self {
+            AmountMode::Split => &["expense", "income"],
+            AmountMode::Signed => &["amount"],
+            AmountMode::Indicator => &["amount", "direction"],
+        }
+    }
+}
+
+/// Serialization format of a profile file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    Json,
+    Toml,
+}
+
+impl ProfileFormat {
+    /// Format implied by a file name's extension (`.json` or `.toml`)
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let extension = filename.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(ProfileFormat::Json),
+            "toml" => Some(ProfileFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Layout of one bank's or ISP's export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatProfile {
+    /// Schema version the profile was written with
+    #[serde(default = "current_version")]
+    pub version: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub kind: ProfileKind,
+    /// Amount layout the statement must have; a file with another layout
+    /// fails with its missing columns. Detected from the headers when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_mode: Option<AmountMode>,
+    /// Timestamp pattern, as for `ParseOptions::timestamp_format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_format: Option<String>,
+    /// Time zone, as for `ParseOptions::timezone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Text encoding label for CSV/TSV exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// 1-based header row, for exports whose title block defeats detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_row: Option<usize>,
+    /// Headers of the columns hidden when sensitive data is masked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_columns: Vec<String>,
+    /// Headers that identify this layout. All must be present for the profile
+    /// to be auto-selected; when empty, every mapped field must resolve.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fingerprint: Vec<String>,
+    /// Header aliases per mapping key (`timestamp`, `account`, `expense`, ...);
+    /// the first alias present in the file is used
+    #[serde(default)]
+    pub columns: BTreeMap<String, Vec<String>>,
+    /// Field positions of a fixed-width text report; plain-text files parsed
+    /// with this profile are then read as such a report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_width: Option<FixedWidthLayout>,
+}
+
+/// Layout of a fixed-width text report, as printed by mainframe systems.
+///
+/// Positions are 0-based character columns, end exclusive, with a
+/// full-width (CJK) character taking two columns as in the report's
+/// double-byte encoding. The field names act as the header row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixedWidthLayout {
+    pub fields: Vec<FixedWidthField>,
+    /// Only lines with one of these record types are data records
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_type: Option<RecordTypeFilter>,
+    /// Lines per page, for reports whose pages are not separated by form feeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_length: Option<usize>,
+    /// Non-blank lines at the top of every page to skip (title, column headings)
+    #[serde(default)]
+    pub page_header_lines: usize,
+    /// Non-blank lines at the bottom of every page to skip (page totals, page number)
+    #[serde(default)]
+    pub page_footer_lines: usize,
+    /// Lines containing any of these texts are skipped wherever they appear
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_lines: Vec<String>,
+}
+
+/// A named field of a fixed-width record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixedWidthField {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The record-type code of a fixed-width line and the codes of data records
+/// (e.g. "D" for detail lines, leaving out "H" headers and "T" trailers)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordTypeFilter {
+    pub start: usize,
+    pub end: usize,
+    pub values: Vec<String>,
+}
+
+impl FixedWidthLayout {
+    fn validate(&self) -> Result<(), CoreError> {
+        if self.fields.is_empty() {
+            return Err(invalid("fixed_width has no fields".to_string()));
+        }
+        let mut names: Vec<String> = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let name = normalize_header(&field.name);
+            if name.is_empty() {
+                return Err(invalid("fixed_width field name is empty".to_string()));
+            }
+            if field.start >= field.end {
+                return Err(invalid(format!("fixed_width field '{}' ends before it starts", field.name)));
+            }
+            if names.contains(&name) {
+                return Err(invalid(format!("fixed_width field '{}' is defined twice", field.name)));
+            }
+            names.push(name);
+        }
+        if let Some(filter) = &self.record_type {
+            if filter.start >= filter.end || filter.values.is_empty() {
+                return Err(invalid("fixed_width record_type needs a position and values".to_string()));
+            }
+        }
+        if self.page_length == Some(0) {
+            return Err(invalid("fixed_width page_length must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn current_version() -> u32 {
+    PROFILE_VERSION
+}
+
+fn normalize_header(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+impl FormatProfile {
+    /// Empty profile with no column aliases
+    pub fn new(name: impl Into<String>, kind: ProfileKind) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            name: name.into(),
+            description: None,
+            kind,
+            amount_mode: None,
+            timestamp_format: None,
+            timezone: None,
+            encoding: None,
+            header_row: None,
+            sensitive_columns: Vec::new(),
+            fingerprint: Vec::new(),
+            columns: BTreeMap::new(),
+            fixed_width: None,
+        }
+    }
+
+    /// Profile from a session's column mapping (key to header name)
+    pub fn from_mapping(name: impl Into<String>, kind: ProfileKind, mapping: &HashMap<String, String>) -> Self {
+        let mut profile = Self::new(name, kind);
+        for (key, header) in mapping {
+            profile.columns.insert(key.clone(), vec![header.clone()]);
+        }
+        profile
+    }
+
+    /// Read a profile from JSON or TOML text (JSON if it starts with `{`)
+    pub fn import(text: &str) -> Result<Self, CoreError> {
+        let format = if text.trim_start().starts_with('{') {
+            ProfileFormat::Json
+        } else {
+            ProfileFormat::Toml
+        };
+        Self::import_as(text, format)
+    }
+
+    /// Read a profile in the given format and check it
+    pub fn import_as(text: &str, format: ProfileFormat) -> Result<Self, CoreError> {
+        let profile: FormatProfile = match format {
+            ProfileFormat::Json => serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?,
+            ProfileFormat::Toml => toml::from_str(text).map_err(|e| invalid(e.to_string()))?,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Write the profile as JSON or TOML
+    pub fn export(&self, format: ProfileFormat) -> Result<String, CoreError> {
+        match format {
+            ProfileFormat::Json => serde_json::to_string_pretty(self).map_err(|e| CoreError::ExportError(e.to_string())),
+            ProfileFormat::Toml => toml::to_string(self).map_err(|e| CoreError::ExportError(e.to_string())),
+        }
+    }
+
+    /// Check the version, mapping keys, amount layout, timestamp format and
+    /// fixed-width layout
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.version > PROFILE_VERSION {
+            return Err(invalid(format!(
+                "profile '{}' uses version {}, newer than supported version {}",
+                self.name, self.version, PROFILE_VERSION
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(invalid("profile name is empty".to_string()));
+        }
+
+        let known: &[&str] = match self.kind {
+            ProfileKind::Transactions => &TRANSACTION_KEYS,
+            ProfileKind::IpLog => &IP_LOG_KEYS,
+        };
+        if let Some(key) = self.columns.keys().find(|k| !known.contains(&k.as_str())) {
+            return Err(invalid(format!("unknown column key '{}'", key)));
+        }
+
+        if let Some(mode) = self.amount_mode {
+            if self.kind != ProfileKind::Transactions {
+                return Err(invalid("amount_mode only applies to transaction profiles".to_string()));
+            }
+            if let Some(key) = mode.required_keys().iter().find(|k| !self.columns.contains_key(**k)) {
+                return Err(invalid(format!("amount_mode {:?} needs a '{}' column", mode, key)));
+            }
+            if mode == AmountMode::Split && self.columns.contains_key("amount") {
+                return Err(invalid("amount_mode Split cannot map an 'amount' column".to_string()));
+            }
+        }
+
+        if let Some(format) = &self.timestamp_format {
+            timestamp::validate_format(format)?;
+        }
+        if let Some(zone) = &self.timezone {
+            timestamp::FileTimeZone::parse(zone)?;
+        }
+        if let Some(layout) = &self.fixed_width {
+            layout.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Column mapping for a header row: each key maps to the first of its
+    /// aliases found among `headers`
+    pub fn mapping(&self, headers: &[String]) -> HashMap<String, String> {
+        let mut mapping = HashMap::new();
+        for (key, aliases) in &self.columns {
+            let found = aliases.iter().find_map(|alias| {
+                let alias = normalize_header(alias);
+                headers.iter().find(|h| normalize_header(h) == alias)
+            });
+            if let Some(header) = found {
+                mapping.insert(key.clone(), header.clone());
+            }
+        }
+        mapping
+    }
+
+    /// 0-based positions of the profile's sensitive columns in `headers`
+    pub fn sensitive_indices(&self, headers: &[String]) -> Vec<usize> {
+        let sensitive: Vec<String> = self.sensitive_columns.iter().map(|h| normalize_header(h)).collect();
+        headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| sensitive.contains(&normalize_header(h)))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// How well a header row matches this profile, or `None` if it does not.
+    ///
+    /// With a fingerprint every fingerprint header must be present; otherwise
+    /// every mapped field must resolve. More matched headers score higher.
+    pub fn match_score(&self, headers: &[String]) -> Option<usize> {
+        if self.fingerprint.is_empty() {
+            let resolved = self.mapping(headers).len();
+            return (resolved > 0 && resolved == self.columns.len()).then_some(resolved);
+        }
+
+        let present: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+        self.fingerprint
+            .iter()
+            .all(|h| present.contains(&normalize_header(h)))
+            .then_some(self.fingerprint.len())
+    }
+}
+
+/// Best-matching profile of `kind` for a header row (earliest wins ties)
+pub fn best_match<'a>(
+    profiles: &'a [FormatProfile],
+    kind: ProfileKind,
+    headers: &[String],
+) -> Option<(&'a FormatProfile, usize)> {
+    let mut best: Option<(&FormatProfile, usize)> = None;
+    for profile in profiles.iter().filter(|p| p.kind == kind) {
+        if let Some(score) = profile.match_score(headers) {
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((profile, score));
+            }
+        }
+    }
+    best
+}
+
+fn invalid(message: String) -> CoreError {
+    CoreError::InvalidData(format!("Invalid profile: {}", message))
+}
+
+// Native-only functions (not available in WASM)
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    impl FormatProfile {
+        /// Read a profile file; the format follows the extension (native only)
+        pub fn load(path: &Path) -> Result<Self, CoreError> {
+            let text = fs::read_to_string(path)?;
+            match ProfileFormat::from_filename(&path.to_string_lossy()) {
+                Some(format) => Self::import_as(&text, format),
+                None => Self::import(&text),
+            }
+        }
+
+        /// Write a profile file; the format follows the extension, JSON by default (native only)
+        pub fn save(&self, path: &Path) -> Result<(), CoreError> {
+            let format = ProfileFormat::from_filename(&path.to_string_lossy()).unwrap_or(ProfileFormat::Json);
+            fs::write(path, self.export(format)?)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn sample() -> FormatProfile {
+        let mut profile = FormatProfile::new("Sample Bank", ProfileKind::Transactions);
+        profile.amount_mode = Some(AmountMode::Indicator);
+        profile.timestamp_format = Some("ROC/%m/%d %H:%M:%S".to_string());
+        profile.sensitive_columns = vec!["身分證號".to_string()];
+        profile.fingerprint = vec!["交易序號".to_string(), "借貸別".to_string()];
+        for (key, aliases) in [
+            ("timestamp", vec!["交易日期時間"]),
+            ("account", vec!["帳號", "存款帳號"]),
+            ("amount", vec!["交易金額"]),
+            ("direction", vec!["借貸別"]),
+        ] {
+            profile.columns.insert(key.to_string(), aliases.iter().map(|s| s.to_string()).collect());
+        }
+        profile
+    }
+
+    fn fixed_width_sample() -> FormatProfile {
+        let mut profile = sample();
+        let field = |name: &str, start, end| FixedWidthField { name: name.to_string(), start, end };
+        profile.fixed_width = Some(FixedWidthLayout {
+            fields: vec![field("交易日期時間", 2, 20), field("帳號", 21, 31), field("交易金額", 32, 44)],
+            record_type: Some(RecordTypeFilter { start: 0, end: 1, values: vec!["D".to_string()] }),
+            page_length: Some(60),
+            page_header_lines: 3,
+            page_footer_lines: 1,
+            skip_lines: vec!["本頁小計".to_string()],
+        });
+        profile
+    }
+
+    #[test]
+    fn test_json_and_toml_round_trip() {
+        for profile in [sample(), fixed_width_sample()] {
+            for format in [ProfileFormat::Json, ProfileFormat::Toml] {
+                let text = profile.export(format).unwrap();
+                assert_eq!(FormatProfile::import(&text).unwrap(), profile);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut profile = sample();
+        profile.columns.remove("direction");
+        assert!(profile.validate().is_err());
+
+        let mut profile = sample();
+        profile.columns.insert("ip_address".to_string(), vec!["IP".to_string()]);
+        assert!(profile.validate().is_err());
+
+        let mut profile = sample();
+        profile.version = PROFILE_VERSION + 1;
+        assert!(profile.validate().is_err());
+
+        assert!(fixed_width_sample().validate().is_ok());
+        let mut profile = fixed_width_sample();
+        let layout = profile.fixed_width.as_mut().unwrap();
+        layout.fields[1].end = layout.fields[1].start;
+        assert!(profile.validate().is_err());
+
+        let mut profile = fixed_width_sample();
+        let layout = profile.fixed_width.as_mut().unwrap();
+        layout.fields[2].name = " 帳號 ".to_string();
+        assert!(profile.validate().is_err());
+
+        let mut profile = fixed_width_sample();
+        profile.fixed_width.as_mut().unwrap().record_type.as_mut().unwrap().values.clear();
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_mapping_and_fingerprint() {
+        let profile = sample();
+        let row = headers(&["交易序號", "交易日期時間", "存款帳號", "交易金額", "借貸別", "身分證號"]);
+
+        let mapping = profile.mapping(&row);
+        assert_eq!(mapping.get("account").map(String::as_str), Some("存款帳號"));
+        assert_eq!(profile.sensitive_indices(&row), vec![5]);
+        assert_eq!(profile.match_score(&row), Some(2));
+        assert_eq!(profile.match_score(&headers(&["交易日期時間", "帳號"])), None);
+
+        let other = FormatProfile::new("Other", ProfileKind::Transactions);
+        let profiles = [other, profile];
+        assert_eq!(best_match(&profiles, ProfileKind::Transactions, &row).unwrap().0.name, "Sample Bank");
+        assert!(best_match(&profiles, ProfileKind::IpLog, &row).is_none());
+    }
+}
@@ -0,0 +1,459 @@
+//! Timestamp parsing for bank statements and login logs
+//!
+//! Besides the usual Gregorian layouts, Taiwanese exports use ROC (Minguo)
+//! years ("113/01/15", "1130115"), 上午/下午 instead of AM/PM, full-width
+//! digits and 年/月/日 separators. Text is normalized first, then parsed
+//! either with a per-file format override or by trying the known layouts.
+//! Fractional seconds ("14:03:22.125") are kept, since login events can be
+//! milliseconds apart.
+//!
+//! Parsed times carry no zone. A file can declare the zone it was written in
+//! (`FileTimeZone`), and its times are then normalized to UTC so that a bank
+//! statement in Taiwan time and a platform log in UTC line up.
+
+use crate::error::CoreError;
+use chrono::format::{parse, Parsed, StrftimeItems};
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// Offset between ROC (Minguo) years and Gregorian years
+const ROC_YEAR_OFFSET: i32 = 1911;
+
+/// Placeholder for a ROC year in a format override (used instead of `%Y`)
+const ROC_YEAR_TOKEN: &str = "ROC";
+
+/// Gregorian layouts tried after the structured date/time parser
+/// (`%.f` accepts optional fractional seconds)
+const FALLBACK_FORMATS: [&str; 5] = [
+    "%d/%m/%Y %H:%M:%S%.f",
+    "%m/%d/%Y %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%d/%m/%Y %H:%M",
+    "%m/%d/%Y %H:%M",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Meridiem {
+    Am,
+    Pm,
+}
+
+/// Parse a timestamp, trying `format` first when one is configured for the file.
+///
+/// `format` is a chrono strftime pattern applied to the normalized text; write
+/// `ROC` where a Minguo year appears (e.g. `"ROC/%m/%d %H:%M:%S"`). 上午/下午 and
+/// AM/PM markers are handled separately, so the pattern should use `%H`.
+pub fn parse_timestamp(s: &str, format: Option<&str>) -> Option<NaiveDateTime> {
+    let (text, meridiem) = normalize(s);
+    if text.is_empty() {
+        return None;
+    }
+
+    let parsed = format
+        .and_then(|fmt| parse_with_format(&text, fmt))
+        .or_else(|| parse_auto(&text))?;
+    apply_meridiem(parsed, meridiem)
+}
+
+/// Display form of a timestamp: `YYYY-MM-DD HH:MM:SS`, with milliseconds
+/// (or finer) appended only when the time has a fractional second
+pub fn format_datetime(dt: &NaiveDateTime) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+}
+
+/// Display form of a time of day, with fractional seconds as in `format_datetime`
+pub fn format_time(time: &NaiveTime) -> String {
+    time.format("%H:%M:%S%.f").to_string()
+}
+
+/// Check that a format override is a usable strftime pattern
+pub fn validate_format(format: &str) -> Result<(), CoreError> {
+    let pattern = format.replace(ROC_YEAR_TOKEN, "%Y");
+    StrftimeItems::new(&pattern)
+        .parse()
+        .map(|_| ())
+        .map_err(|_| CoreError::InvalidData(format!("Invalid timestamp format: {}", format)))
+}
+
+/// Time zone a file's timestamps are written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileTimeZone {
+    /// Fixed UTC offset, declared as `+08:00`, `UTC+8`, `GMT-0530` or `Z`
+    Offset(FixedOffset),
+    /// IANA zone such as `Asia/Taipei`, following its daylight-saving rules
+    Named(Tz),
+}
+
+impl FileTimeZone {
+    /// Parse a declared zone: a UTC offset or an IANA zone name
+    pub fn parse(declared: &str) -> Result<Self, CoreError> {
+        let text = declared.trim();
+        if let Some(offset) = parse_utc_offset(text) {
+            return Ok(Self::Offset(offset));
+        }
+        text.parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| CoreError::InvalidData(format!("Unknown time zone: {}", declared)))
+    }
+
+    /// UTC equivalent of a local time in this zone. A local time that occurs
+    /// twice (clocks turned back) takes the earlier instant; one skipped by a
+    /// daylight-saving jump uses the offset in force around it.
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let offset = match self {
+            Self::Offset(offset) => *offset,
+            Self::Named(tz) => match tz.from_local_datetime(&local).earliest() {
+                Some(dt) => return dt.naive_utc(),
+                None => tz.offset_from_utc_datetime(&local).fix(),
+            },
+        };
+        local - Duration::seconds(offset.local_minus_utc() as i64)
+    }
+}
+
+impl fmt::Display for FileTimeZone {
+    /// `UTC`, `UTC+08:00` or the IANA name, as recorded in metadata and exports
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offset(offset) if offset.local_minus_utc() == 0 => write!(f, "UTC"),
+            Self::Offset(offset) => write!(f, "UTC{}", offset),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// `Z`, `UTC`, `+8`, `+08`, `+0800`, `+08:00`, optionally prefixed by
+/// `UTC` or `GMT`
+fn parse_utc_offset(text: &str) -> Option<FixedOffset> {
+    let upper = text.to_ascii_uppercase();
+    if matches!(upper.as_str(), "Z" | "UTC" | "GMT") {
+        return FixedOffset::east_opt(0);
+    }
+    let rest = upper.strip_prefix("UTC").or_else(|| upper.strip_prefix("GMT")).unwrap_or(&upper);
+    let (sign, rest) = match rest.as_bytes().first()? {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+        None => (rest, "0"),
+    };
+    let all_digits = |s: &str| !s.is_empty() && s.len() <= 2 && s.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(hours) || !all_digits(minutes) {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Map full-width ASCII forms (U+FF01..FF5E) and the ideographic space to ASCII
+pub(crate) fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Convert full-width characters and CJK date units to ASCII, and pull out
+/// any 上午/下午 (or AM/PM) marker
+fn normalize(s: &str) -> (String, Option<Meridiem>) {
+    let mut text = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '年' | '月' => text.push('/'),
+            '日' => text.push(' '),
+            '時' | '时' | '點' | '点' | '分' => text.push(':'),
+            '秒' => {}
+            _ => text.push(to_half_width(c)),
+        }
+    }
+
+    let mut meridiem = None;
+    for (marker, value) in [
+        ("上午", Meridiem::Am),
+        ("早上", Meridiem::Am),
+        ("凌晨", Meridiem::Am),
+        ("下午", Meridiem::Pm),
+        ("晚上", Meridiem::Pm),
+    ] {
+        if text.contains(marker) {
+            text = text.replace(marker, " ");
+            meridiem = Some(value);
+        }
+    }
+
+    let mut tokens = Vec::new();
+    for token in text.split_whitespace() {
+        match token.to_ascii_uppercase().as_str() {
+            "AM" | "A.M." => meridiem = Some(Meridiem::Am),
+            "PM" | "P.M." => meridiem = Some(Meridiem::Pm),
+            _ => tokens.push(token.trim_end_matches(['/', ':'])),
+        }
+    }
+
+    (tokens.join(" "), meridiem)
+}
+
+/// 12-hour clock correction for a parsed 上午/下午 timestamp
+fn apply_meridiem(dt: NaiveDateTime, meridiem: Option<Meridiem>) -> Option<NaiveDateTime> {
+    match (meridiem, dt.hour()) {
+        (Some(Meridiem::Pm), h) if h < 12 => dt.with_hour(h + 12),
+        (Some(Meridiem::Am), 12) => dt.with_hour(0),
+        _ => Some(dt),
+    }
+}
+
+fn parse_with_format(text: &str, format: &str) -> Option<NaiveDateTime> {
+    if !format.contains(ROC_YEAR_TOKEN) {
+        return NaiveDateTime::parse_from_str(text, format).ok().or_else(|| {
+            NaiveDate::parse_from_str(text, format)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        });
+    }
+
+    // Parse the ROC year as a plain number, then shift it before building the
+    // date so leap days (e.g. 113/02/29) validate against the Gregorian year
+    let pattern = format.replace(ROC_YEAR_TOKEN, "%Y");
+    let mut parsed = Parsed::new();
+    parse(&mut parsed, text, StrftimeItems::new(&pattern)).ok()?;
+    let year = parsed.year().filter(|year| (1..1000).contains(year))?;
+    let date = NaiveDate::from_ymd_opt(
+        year + ROC_YEAR_OFFSET,
+        parsed.month()?,
+        parsed.day()?,
+    )?;
+    let time = parsed.to_naive_time().unwrap_or(NaiveTime::MIN);
+    Some(date.and_time(time))
+}
+
+/// Structured "date [time]" parsing, then the Gregorian fallback layouts
+fn parse_auto(text: &str) -> Option<NaiveDateTime> {
+    parse_date_time(text).or_else(|| {
+        FALLBACK_FORMATS
+            .iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(text, fmt).ok())
+            // `%Y` also takes a short year ("15/01/24 10:00:00")
+            .filter(|dt| dt.year() >= 1000)
+    })
+}
+
+fn parse_date_time(text: &str) -> Option<NaiveDateTime> {
+    let mut tokens = text.split_whitespace();
+    let first = tokens.next()?;
+    let second = tokens.next();
+    if tokens.next().is_some() {
+        return None;
+    }
+
+    // Compact date and time in one number: 20240115143022 / 1130115143022
+    if second.is_none() && first.bytes().all(|b| b.is_ascii_digit()) && first.len() > 8 {
+        let (date, time) = first.split_at(first.len() - if first.len() == 12 { 4 } else { 6 });
+        return Some(parse_date(date)?.and_time(parse_time(time)?));
+    }
+
+    let date = parse_date(first)?;
+    let time = match second {
+        Some(t) => parse_time(t)?,
+        None => NaiveTime::MIN,
+    };
+    Some(date.and_time(time))
+}
+
+/// Year-first dates: `2024-01-15`, `2024/1/15`, `113/01/15`, `113.01.15`,
+/// `20240115` and `1130115`
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    if token.bytes().all(|b| b.is_ascii_digit()) {
+        return match token.len() {
+            8 => ymd(&token[..4], &token[4..6], &token[6..]),
+            7 => ymd(&token[..3], &token[3..5], &token[5..]),
+            _ => None,
+        };
+    }
+
+    let parts: Vec<&str> = token.split(['/', '-', '.']).collect();
+    match parts.as_slice() {
+        [y, m, d] if y.len() <= 4 && d.len() <= 2 => ymd(y, m, d),
+        _ => None,
+    }
+}
+
+/// Build a date from a four-digit Gregorian year or a three-digit ROC year.
+/// Any other year ("24/01/15", "0113", "5/01/15") is too ambiguous to guess;
+/// a format override can say how to read it.
+fn ymd(year: &str, month: &str, day: &str) -> Option<NaiveDate> {
+    let value: i32 = year.parse().ok()?;
+    let year = match year.len() {
+        3 if value > 0 => value + ROC_YEAR_OFFSET,
+        4 if value >= 1000 => value,
+        _ => return None,
+    };
+    NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+}
+
+/// `14:03:22`, `14:03`, `140322` or `1403`, optionally with fractional
+/// seconds (`14:03:22.125`, `14:03:22,125`, `140322.125`)
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    let (token, nanos) = match token.split_once(['.', ',']) {
+        Some((whole, fraction)) => (whole, parse_fraction(fraction)?),
+        None => (token, 0),
+    };
+    let fields: Vec<&str> = if token.contains(':') {
+        token.split(':').collect()
+    } else if token.bytes().all(|b| b.is_ascii_digit()) && matches!(token.len(), 4 | 6) {
+        (0..token.len()).step_by(2).map(|i| &token[i..i + 2]).collect()
+    } else {
+        return None;
+    };
+
+    if fields.len() > 3 {
+        return None;
+    }
+    let number = |i: usize| -> Option<u32> {
+        match fields.get(i) {
+            Some(f) => f.parse().ok(),
+            None => Some(0),
+        }
+    };
+    if nanos > 0 && fields.len() < 3 {
+        return None;
+    }
+    NaiveTime::from_hms_nano_opt(number(0)?, number(1)?, number(2)?, nanos)
+}
+
+/// Digits after the decimal mark of a seconds field, as nanoseconds
+fn parse_fraction(fraction: &str) -> Option<u32> {
+    if fraction.is_empty() || fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits: u32 = fraction.parse().ok()?;
+    Some(digits * 10u32.pow(9 - fraction.len() as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> String {
+        parse_timestamp(s, None)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_gregorian_formats() {
+        assert_eq!(at("2024-01-15 10:30:00"), "2024-01-15 10:30:00");
+        assert_eq!(at("2024/1/5 10:30"), "2024-01-05 10:30:00");
+        assert_eq!(at("2024-01-15T10:30:00"), "2024-01-15 10:30:00");
+        assert_eq!(at("15/01/2024 10:30:00"), "2024-01-15 10:30:00");
+        assert_eq!(at("2024-01-15"), "2024-01-15 00:00:00");
+    }
+
+    #[test]
+    fn test_roc_years() {
+        assert_eq!(at("113/01/15 14:03:22"), "2024-01-15 14:03:22");
+        assert_eq!(at("113.02.29 08:00"), "2024-02-29 08:00:00");
+        assert_eq!(at("1130115"), "2024-01-15 00:00:00");
+        assert_eq!(at("1130115 140322"), "2024-01-15 14:03:22");
+        assert_eq!(at("1130115140322"), "2024-01-15 14:03:22");
+        assert_eq!(at("20240115143022"), "2024-01-15 14:30:22");
+        assert_eq!(at("113年1月15日 14時03分22秒"), "2024-01-15 14:03:22");
+    }
+
+    #[test]
+    fn test_meridiem_and_full_width() {
+        assert_eq!(at("113/01/15 下午 02:03:22"), "2024-01-15 14:03:22");
+        assert_eq!(at("113/01/15 上午 12:10:00"), "2024-01-15 00:10:00");
+        assert_eq!(at("2024/01/15 下午12:30"), "2024-01-15 12:30:00");
+        assert_eq!(at("2024-01-15 02:03:22 PM"), "2024-01-15 14:03:22");
+        assert_eq!(at("１１３／０１／１５　１４：０３：２２"), "2024-01-15 14:03:22");
+    }
+
+    #[test]
+    fn test_format_override() {
+        let dt = parse_timestamp("15-01-113 14.03", Some("%d-%m-ROC %H.%M")).unwrap();
+        assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2024-01-15 14:03");
+
+        let dt = parse_timestamp("01/02/2024 09:00:00", Some("%m/%d/%Y %H:%M:%S")).unwrap();
+        assert_eq!(dt.format("%Y-%m-%d").to_string(), "2024-01-02");
+
+        assert!(validate_format("ROC/%m/%d").is_ok());
+        assert!(validate_format("%Q").is_err());
+    }
+
+    #[test]
+    fn test_fractional_seconds() {
+        let millis = |s: &str| parse_timestamp(s, None).map(|dt| dt.and_utc().timestamp_subsec_millis());
+        assert_eq!(millis("2024-01-15 10:30:00.125"), Some(125));
+        assert_eq!(millis("2024-01-15T10:30:00.5"), Some(500));
+        assert_eq!(millis("113/01/15 14:03:22,042"), Some(42));
+        assert_eq!(millis("20240115 140322.900"), Some(900));
+        assert_eq!(millis("2024-01-15 10:30.5"), None);
+
+        let dt = parse_timestamp("2024-01-15 10:30:00.125", None).unwrap();
+        assert_eq!(format_datetime(&dt), "2024-01-15 10:30:00.125");
+        let dt = parse_timestamp("2024-01-15 10:30:00", None).unwrap();
+        assert_eq!(format_datetime(&dt), "2024-01-15 10:30:00");
+    }
+
+    #[test]
+    fn test_time_zone_declarations() {
+        let label = |s: &str| FileTimeZone::parse(s).map(|z| z.to_string()).ok();
+        assert_eq!(label("+08:00"), Some("UTC+08:00".to_string()));
+        assert_eq!(label("UTC+8"), Some("UTC+08:00".to_string()));
+        assert_eq!(label("gmt-0530"), Some("UTC-05:30".to_string()));
+        assert_eq!(label("Z"), Some("UTC".to_string()));
+        assert_eq!(label(" Asia/Taipei "), Some("Asia/Taipei".to_string()));
+        assert_eq!(label("+25"), None);
+        assert_eq!(label("Taipei"), None);
+    }
+
+    #[test]
+    fn test_time_zone_normalizes_to_utc() {
+        let dt = |s: &str| parse_timestamp(s, None).unwrap();
+        let taipei = FileTimeZone::parse("Asia/Taipei").unwrap();
+        assert_eq!(taipei.to_utc(dt("2024-01-15 10:30:00")), dt("2024-01-15 02:30:00"));
+        let offset = FileTimeZone::parse("+08:00").unwrap();
+        assert_eq!(offset.to_utc(dt("2024-01-15 03:00:00")), dt("2024-01-14 19:00:00"));
+
+        // New York: 01:30 occurs twice on 2024-11-03, 02:30 not at all on 2024-03-10
+        let new_york = FileTimeZone::parse("America/New_York").unwrap();
+        assert_eq!(new_york.to_utc(dt("2024-11-03 01:30:00")), dt("2024-11-03 05:30:00"));
+        assert_eq!(new_york.to_utc(dt("2024-07-01 12:00:00")), dt("2024-07-01 16:00:00"));
+        assert_eq!(new_york.to_utc(dt("2024-03-10 02:30:00")), dt("2024-03-10 07:30:00"));
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert_eq!(at(""), "");
+        assert_eq!(at("N/A"), "");
+        assert_eq!(at("113/13/40"), "");
+    }
+
+    #[test]
+    fn test_roc_years_need_three_digits() {
+        assert_eq!(at("099/01/15"), "2010-01-15 00:00:00");
+        assert_eq!(at("24/01/15"), "");
+        assert_eq!(at("24/01/15 10:00:00"), "");
+        assert_eq!(at("15/01/24 10:00:00"), "");
+        assert_eq!(at("0113/01/15"), "");
+        assert_eq!(at("5/01/15"), "");
+        assert_eq!(at("000/01/15"), "");
+        assert_eq!(at("01130115"), "");
+        assert!(parse_timestamp("15-01-2024", Some("%d-%m-ROC")).is_none());
+
+        // A format override says how to read a two-digit year
+        let dt = parse_timestamp("24/01/15 10:00:00", Some("%y/%m/%d %H:%M:%S")).unwrap();
+        assert_eq!(dt.format("%Y-%m-%d").to_string(), "2024-01-15");
+        let dt = parse_timestamp("99/01/15", Some("ROC/%m/%d")).unwrap();
+        assert_eq!(dt.format("%Y-%m-%d").to_string(), "2010-01-15");
+    }
+}
@@ -6,7 +6,7 @@
 
 use wasm_bindgen::prelude::*;
 use serde_wasm_bindgen;
-use crate::{Exporter, IpMatcher, Parser, Processor};
+use crate::{AccountNormalizer, Exporter, IpMatcher, ParseOptions, Parser, Processor, TimeWindow};
 
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -14,8 +14,17 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-/// Analyze Excel files and return results as JSON
+/// Analyze Excel files and return results as JSON.
+///
+/// `file_a_timezone` and `file_b_timezone` declare the zone each file's
+/// timestamps are written in (UTC offset or IANA name); when given, times
+/// are normalized to UTC before matching. `account_normalizer` is an
+/// optional `{ digits_only, pad_width, strip_prefixes }` object applied to
+/// the accounts of both files before joining them. `file_a_password` and
+/// `file_b_password` open password-encrypted workbooks, which are decrypted
+/// in memory.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)] // positional arguments of the JS binding
 pub fn analyze(
     file_a_bytes: &[u8],
     file_a_name: &str,
@@ -23,21 +32,48 @@ pub fn analyze(
     file_b_name: &str,
     hide_sensitive: bool,
     ip_cross_reference: bool,
+    file_a_timezone: Option<String>,
+    file_b_timezone: Option<String>,
+    account_normalizer: JsValue,
+    file_a_password: Option<String>,
+    file_b_password: Option<String>,
 ) -> Result<JsValue, JsError> {
+    let options = |timezone: Option<String>, password: Option<String>| ParseOptions {
+        timezone,
+        password,
+        ..Default::default()
+    };
+
     // Parse files
-    let (mut transactions, meta_a) = Parser::parse_transactions_from_bytes(file_a_bytes, file_a_name)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let (mut transactions, meta_a) = Parser::parse_transactions_from_bytes(
+        file_a_bytes,
+        file_a_name,
+        None,
+        Some(&options(file_a_timezone, file_a_password)),
+    )
+    .map_err(|e| JsError::new(&e.to_string()))?;
 
-    let (ip_records, meta_b) = Parser::parse_ip_records_from_bytes(file_b_bytes, file_b_name)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let (ip_records, meta_b) = Parser::parse_ip_records_from_bytes(
+        file_b_bytes,
+        file_b_name,
+        None,
+        Some(&options(file_b_timezone, file_b_password)),
+    )
+    .map_err(|e| JsError::new(&e.to_string()))?;
 
-    // Preprocess
+    // Preprocess, keeping the File A headers aligned with the raw columns
+    let mut raw_headers = meta_a.headers.clone();
     let processor = Processor::new(hide_sensitive);
-    processor.process(&mut transactions);
+    processor.process_with_headers(&mut transactions, &mut raw_headers);
 
     // IP matching
     if ip_cross_reference {
-        let matcher = IpMatcher::with_default_window(&ip_records);
+        let accounts: AccountNormalizer = if account_normalizer.is_undefined() || account_normalizer.is_null() {
+            AccountNormalizer::default()
+        } else {
+            serde_wasm_bindgen::from_value(account_normalizer).map_err(|e| JsError::new(&e.to_string()))?
+        };
+        let matcher = IpMatcher::with_normalizer(&ip_records, TimeWindow::default(), accounts);
         matcher.match_all(&mut transactions);
     }
 
@@ -48,6 +84,7 @@ pub fn analyze(
     let result = serde_json::json!({
         "fileA": meta_a,
         "fileB": meta_b,
+        "rawHeaders": raw_headers,
         "totalRecords": transactions.len(),
         "incomeCount": income.len(),
         "expenseCount": expense.len(),
@@ -67,19 +104,33 @@ pub fn export_excel(transactions_json: &str) -> Result<Vec<u8>, JsError> {
         transactions: Vec<crate::Transaction>,
         income: Vec<crate::Transaction>,
         expense: Vec<crate::Transaction>,
+        /// File A headers naming the raw columns, as returned by `analyze`
+        #[serde(default, rename = "rawHeaders")]
+        raw_headers: Vec<String>,
+        /// File metadata (with parse reports) for an optional "Parse Report" sheet
+        #[serde(default)]
+        files: Vec<crate::FileMetadata>,
     }
 
     let input: ExportInput = serde_json::from_str(transactions_json)
         .map_err(|e| JsError::new(&format!("Invalid JSON: {}", e)))?;
 
-    Exporter::export_to_bytes(&input.transactions, &input.income, &input.expense)
+    Exporter::export_to_bytes_with_report(
+        &input.transactions,
+        &input.income,
+        &input.expense,
+        &input.raw_headers,
+        &input.files,
+    )
         .map_err(|e| JsError::new(&e.to_string()))
 }
 
-/// Get file metadata without full parsing
+/// Get file metadata without full parsing; `password` opens an encrypted
+/// workbook
 #[wasm_bindgen]
-pub fn get_file_info(bytes: &[u8], filename: &str) -> Result<JsValue, JsError> {
-    let metadata = Parser::get_metadata_from_bytes(bytes, filename)
+pub fn get_file_info(bytes: &[u8], filename: &str, password: Option<String>) -> Result<JsValue, JsError> {
+    let options = ParseOptions { password, ..Default::default() };
+    let metadata = Parser::get_metadata_from_bytes(bytes, filename, Some(&options))
         .map_err(|e| JsError::new(&e.to_string()))?;
     serde_wasm_bindgen::to_value(&metadata).map_err(|e| JsError::new(&e.to_string()))
 }
@@ -1,13 +1,13 @@
 use bankflow_core::exporter::Exporter;
 use bankflow_core::models::Transaction;
+use bankflow_core::{Parser, Processor};
 use calamine::{open_workbook_auto_from_rs, Data, Reader};
 use std::io::Cursor;
 
-fn make_raw_columns(counterparty: &str) -> Vec<String> {
-    // Canonical raw columns length 13 (A..M). Counterparty account is index 11 (L).
-    let mut cols = vec!["".to_string(); 13];
-    cols[11] = counterparty.to_string();
-    cols
+fn make_transaction(timestamp: &str, income: Option<f64>, expense: Option<f64>, counterparty: &str) -> Transaction {
+    let mut tx = Transaction::new(timestamp.to_string(), "ACC001".to_string(), income, expense, Vec::new(), 2);
+    tx.counterparty_account = Some(counterparty.to_string());
+    tx
 }
 
 fn cell_to_string(cell: &Data) -> String {
@@ -26,27 +26,14 @@ fn cell_to_string(cell: &Data) -> String {
 
 #[test]
 fn export_includes_counterparty_sheet() {
-    let income_tx = Transaction::new(
-        "2024-01-15 10:30:00".to_string(),
-        "ACC001".to_string(),
-        Some(1000.0),
-        None,
-        make_raw_columns("CP-IN-001"),
-        2,
-    );
-    let expense_tx = Transaction::new(
-        "2024-01-15 10:30:01".to_string(),
-        "ACC002".to_string(),
-        None,
-        Some(500.0),
-        make_raw_columns("CP-EX-001"),
-        3,
-    );
+    let income_tx = make_transaction("2024-01-15 10:30:00", Some(1000.0), None, "CP-IN-001");
+    let expense_tx = make_transaction("2024-01-15 10:30:01", None, Some(500.0), "CP-EX-001");
 
     let bytes = Exporter::export_to_bytes(
         &[income_tx.clone(), expense_tx.clone()],
         &[income_tx],
         &[expense_tx],
+        &[],
     )
     .expect("export");
 
@@ -67,3 +54,31 @@ fn export_includes_counterparty_sheet() {
     let v1 = cell_to_string(&first_data_row[1]);
     assert!(v0 == "CP-IN-001" || v1 == "CP-EX-001");
 }
+
+#[test]
+fn counterparty_follows_header_and_survives_masking() {
+    // Counterparty in column C, which the default masking removes
+    let statement = "\
+交易時間,帳號,對方帳號,支出金額,存入金額,餘額,幣別,摘要
+2024-01-15 10:30:00,ACC1,CP-IN-001,,1000,\"5,000\",twd,薪資
+2024-01-15 11:00:00,ACC1,CP-EX-001,500,,0,twd,
+";
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), "a.csv", None, None).expect("parse");
+    assert_eq!(transactions[0].balance, Some(5000.0));
+    assert_eq!(transactions[1].balance, Some(0.0));
+    assert_eq!(transactions[0].currency.as_deref(), Some("TWD"));
+    assert_eq!(transactions[0].memo.as_deref(), Some("薪資"));
+    assert_eq!(transactions[1].memo, None);
+
+    Processor::new(true).process(&mut transactions);
+    assert!(!transactions[0].raw_columns.contains(&"CP-IN-001".to_string()));
+    let (income, expense) = Processor::split_income_expense(&transactions);
+
+    let bytes = Exporter::export_to_bytes(&transactions, &income, &expense, &[]).expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    let range = workbook.worksheet_range("Counterparty").expect("range");
+    let first_data_row = range.rows().nth(1).expect("first data row");
+    assert_eq!(cell_to_string(&first_data_row[0]), "CP-IN-001");
+    assert_eq!(cell_to_string(&first_data_row[1]), "CP-EX-001");
+}
@@ -31,7 +31,7 @@ fn cell_to_string(cell: &calamine::Data) -> String {
 #[test]
 fn export_headers_match_user_manual() {
     let tx = build_sample_transaction();
-    let bytes = Exporter::export_to_bytes(&[tx.clone()], &[tx.clone()], &[tx]).expect("export");
+    let bytes = Exporter::export_to_bytes(std::slice::from_ref(&tx), std::slice::from_ref(&tx), std::slice::from_ref(&tx), &[]).expect("export");
 
     let cursor = Cursor::new(bytes);
     let mut workbook = open_workbook_auto_from_rs(cursor).expect("open workbook");
@@ -49,6 +49,12 @@ fn export_headers_match_user_manual() {
         "Matched IP",
         "IP Country",
         "IP ISP",
+        "Device",
+        "User Agent",
+        "Channel",
+        "Login Result",
+        "Session",
+        "Source",
         "Raw Column 1",
         "Raw Column 2",
         "Raw Column 3",
@@ -56,3 +62,18 @@ fn export_headers_match_user_manual() {
 
     assert_eq!(headers, expected);
 }
+
+#[test]
+fn export_uses_original_raw_headers() {
+    let tx = build_sample_transaction();
+    let raw_headers = vec!["戶名".to_string(), " ".to_string()];
+    let bytes = Exporter::export_to_bytes(std::slice::from_ref(&tx), &[], &[], &raw_headers).expect("export");
+
+    let cursor = Cursor::new(bytes);
+    let mut workbook = open_workbook_auto_from_rs(cursor).expect("open workbook");
+    let range = workbook.worksheet_range("Summary").expect("range");
+    let headers: Vec<String> = range.rows().next().expect("header row").iter().map(cell_to_string).collect();
+
+    // Blank or missing header names fall back to the generic label
+    assert_eq!(&headers[13..], ["戶名", "Raw Column 2", "Raw Column 3"]);
+}
@@ -0,0 +1,82 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::{Dataset, Parser, Transaction};
+use calamine::{open_workbook_auto_from_rs, Reader};
+use std::io::Cursor;
+
+const JANUARY: &str = "\
+交易時間,帳號,支出金額,存入金額,餘額
+2024-01-30 09:00:00,ACC1,100,,900
+2024-01-31 10:00:00,ACC1,,500,1400
+";
+
+/// Overlaps January by one day
+const FEBRUARY: &str = "\
+交易時間,帳號,支出金額,存入金額,餘額
+2024-01-31 10:00:00,ACC1,,500,1400
+2024-02-01 11:00:00,ACC1,200,,1200
+";
+
+fn load(dataset: &mut Dataset<Transaction>, statement: &str, filename: &str) -> usize {
+    let (records, metadata) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), filename, None, None).expect("parse");
+    dataset.append(records, metadata).expect("append")
+}
+
+#[test]
+fn overlapping_statements_merge_with_provenance() {
+    let mut dataset = Dataset::new();
+    assert_eq!(load(&mut dataset, JANUARY, "jan.csv"), 2);
+    assert_eq!(load(&mut dataset, FEBRUARY, "feb.csv"), 1);
+
+    let sources: Vec<String> = dataset.records.iter().map(|t| t.source().to_string()).collect();
+    assert_eq!(sources, ["jan.csv / row 2", "jan.csv / row 3", "feb.csv / row 3"]);
+
+    let duplicates = &dataset.files[1].report.duplicate_rows;
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!((duplicates[0].row, duplicates[0].original.row), (2, 3));
+    assert!(dataset.files[0].report.is_clean());
+}
+
+#[test]
+fn export_shows_source_and_merged_duplicates() {
+    let mut dataset = Dataset::new();
+    load(&mut dataset, JANUARY, "jan.csv");
+    load(&mut dataset, FEBRUARY, "feb.csv");
+
+    let bytes = Exporter::export_to_bytes_with_report(&dataset.records, &[], &[], &[], &dataset.files)
+        .expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+
+    let summary = workbook.worksheet_range("Summary").expect("summary sheet");
+    let sources: Vec<String> = summary.rows().skip(1).map(|row| row[12].to_string()).collect();
+    assert_eq!(sources, ["jan.csv / row 2", "jan.csv / row 3", "feb.csv / row 3"]);
+
+    let report = workbook.worksheet_range("Parse Report").expect("report sheet");
+    let duplicates: Vec<(String, String, String)> = report
+        .rows()
+        .filter(|row| row[3] == "Duplicate row")
+        .map(|row| (row[0].to_string(), row[2].to_string(), row[5].to_string()))
+        .collect();
+    assert_eq!(
+        duplicates,
+        [("feb.csv".to_string(), "2".to_string(), "Same as jan.csv / row 3".to_string())]
+    );
+}
+
+#[test]
+fn statements_of_another_layout_are_not_appended() {
+    let mut dataset = Dataset::new();
+    load(&mut dataset, JANUARY, "jan.csv");
+
+    let other = "交易時間,帳號,支出金額,存入金額,身分證號\n2024-02-01 11:00:00,ACC1,200,,A123456789\n";
+    let (records, metadata) =
+        Parser::parse_transactions_from_bytes(other.as_bytes(), "other.csv", None, None).expect("parse");
+    assert!(dataset.append(records, metadata).is_err());
+    assert_eq!(dataset.files.len(), 1);
+    assert_eq!(dataset.len(), 2);
+
+    // Reloading the only statement may change its layout
+    let (records, metadata) =
+        Parser::parse_transactions_from_bytes(other.as_bytes(), "jan.csv", None, None).expect("parse");
+    assert_eq!(dataset.append(records, metadata).expect("replace"), 1);
+}
@@ -0,0 +1,60 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::parser::Parser;
+use bankflow_core::Processor;
+use calamine::{open_workbook_auto_from_rs, Reader};
+use std::io::Cursor;
+
+#[test]
+fn signed_amount_column_is_split() {
+    let csv = "\
+交易時間,帳號,交易金額\n\
+2024-01-15 10:30:00,ACC1,-1500\n\
+2024-01-15 11:00:00,ACC1,\"2,000\"\n\
+2024-01-15 12:00:00,ACC1,(300)\n";
+
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(csv.as_bytes(), "a.csv", None, None).expect("parse");
+
+    let pairs: Vec<(Option<f64>, Option<f64>)> =
+        transactions.iter().map(|t| (t.expense, t.income)).collect();
+    assert_eq!(
+        pairs,
+        vec![(Some(1500.0), None), (None, Some(2000.0)), (Some(300.0), None)]
+    );
+
+    let (income, expense) = Processor::split_income_expense(&transactions);
+    assert_eq!(income.len(), 1);
+    assert_eq!(expense.len(), 2);
+}
+
+#[test]
+fn debit_credit_indicator_sets_direction() {
+    let csv = "\
+交易時間,帳號,借貸,金額\n\
+2024-01-15 10:30:00,ACC1,借,1500\n\
+2024-01-15 11:00:00,ACC1,貸,2000\n\
+2024-01-15 12:00:00,ACC1,?,-40\n";
+
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(csv.as_bytes(), "a.csv", None, None).expect("parse");
+
+    let pairs: Vec<(Option<f64>, Option<f64>)> =
+        transactions.iter().map(|t| (t.expense, t.income)).collect();
+    assert_eq!(
+        pairs,
+        vec![(Some(1500.0), None), (None, Some(2000.0)), (Some(40.0), None)]
+    );
+
+    // The unknown marker is reported as such and the amount's sign is used instead
+    assert!(metadata.report.unparsed_amounts.is_empty());
+    assert_eq!(metadata.report.unknown_directions.len(), 1);
+    assert_eq!(metadata.report.unknown_directions[0].row, 4);
+    assert_eq!(metadata.report.unknown_directions[0].column, "借貸");
+    assert_eq!(metadata.report.unknown_directions[0].value, "?");
+
+    let bytes = Exporter::export_to_bytes_with_report(&transactions, &[], &[], &[], &[metadata]).expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    let range = workbook.worksheet_range("Parse Report").expect("report sheet");
+    let issues: Vec<String> = range.rows().skip(1).map(|row| row[3].to_string()).collect();
+    assert_eq!(issues, ["Unknown debit/credit marker"]);
+}
@@ -0,0 +1,47 @@
+use bankflow_core::parser::Parser;
+use bankflow_core::{ProcessingStats, Processor};
+
+const STATEMENT: &str = "\
+交易時間\t帳號\t支出金額\t存入金額\n\
+2024-01-15 10:30:00\tACC1\t1,234,567\t-\n\
+2024-01-15 11:00:00\tACC1\tNT$5,000\t\n\
+2024-01-15 12:00:00\tACC1\t(3,000)\t\n\
+2024-01-15 13:00:00\tACC1\t\t５０００\n\
+2024-01-15 14:00:00\tACC1\t轉帳\t200\n";
+
+#[test]
+fn formatted_amounts_are_parsed() {
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.tsv", None, None)
+            .expect("parse");
+
+    let expenses: Vec<Option<f64>> = transactions.iter().map(|t| t.expense).collect();
+    assert_eq!(
+        expenses,
+        vec![Some(1234567.0), Some(5000.0), Some(3000.0), None, None]
+    );
+    assert_eq!(transactions[0].income, None);
+    assert_eq!(transactions[3].income, Some(5000.0));
+
+    let stats = ProcessingStats::from_transactions(&transactions);
+    assert!((stats.total_income - 5200.0).abs() < 0.01);
+    assert!((stats.total_expense - 1242567.0).abs() < 0.01);
+    assert_eq!(stats.expense_count, 3);
+
+    let (_, expense) = Processor::split_income_expense(&transactions);
+    let rows: Vec<usize> = expense.iter().map(|t| t.row_index).collect();
+    assert_eq!(rows, [2, 3, 4]);
+}
+
+#[test]
+fn unreadable_amounts_are_reported() {
+    let (_, metadata) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.tsv", None, None)
+            .expect("parse");
+
+    assert_eq!(metadata.report.unparsed_amounts.len(), 1);
+    let cell = &metadata.report.unparsed_amounts[0];
+    assert_eq!(cell.row, 6);
+    assert_eq!(cell.column, "支出金額");
+    assert_eq!(cell.value, "轉帳");
+}
@@ -0,0 +1,61 @@
+use bankflow_core::parser::Parser;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+/// IP log with the date as an Excel serial and the time as a day fraction
+fn build_split_log() -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    let date_fmt = Format::new().set_num_format("yyyy/mm/dd");
+    let time_fmt = Format::new().set_num_format("hh:mm:ss");
+
+    for (col, header) in ["登入日期", "登入時間", "帳號", "IP位址"].iter().enumerate() {
+        ws.write_string(0, col as u16, *header)?;
+    }
+    // 45306 = 2024-01-15, 0.5853009... = 14:02:50
+    ws.write_number_with_format(1, 0, 45306.0, &date_fmt)?;
+    ws.write_number_with_format(1, 1, 50570.0 / 86400.0, &time_fmt)?;
+    ws.write_string(1, 2, "ACC1")?;
+    ws.write_string(1, 3, "1.2.3.4")?;
+    // Plain numbers without a date format
+    ws.write_number(2, 0, 45307.0)?;
+    ws.write_number(2, 1, 0.25)?;
+    ws.write_string(2, 2, "ACC1")?;
+    ws.write_string(2, 3, "5.6.7.8")?;
+
+    workbook.save_to_buffer()
+}
+
+#[test]
+fn excel_date_and_time_cells_are_combined() {
+    let bytes = build_split_log().expect("build workbook");
+    let (records, _) =
+        Parser::parse_ip_records_from_bytes(&bytes, "b.xlsx", None, None).expect("parse");
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].timestamp, "2024-01-15 14:02:50");
+    let dt = records[0].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-15 14:02:50");
+    assert_eq!(records[1].timestamp, "2024-01-16 06:00:00");
+}
+
+#[test]
+fn text_date_and_time_columns_are_combined() {
+    let csv = "\
+交易日期,交易時間,帳號,支出金額,存入金額\n\
+113/01/15,下午 02:03:22,ACC1,500,\n\
+2024-01-16,90500,ACC1,,200\n\
+2024-01-17,2024-01-17 10:00:00,ACC1,100,\n";
+
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(csv.as_bytes(), "a.csv", None, None).expect("parse");
+
+    let parsed: Vec<String> = transactions
+        .iter()
+        .map(|t| t.datetime.expect("datetime").format("%Y-%m-%d %H:%M:%S").to_string())
+        .collect();
+    assert_eq!(
+        parsed,
+        vec!["2024-01-15 14:03:22", "2024-01-16 09:05:00", "2024-01-17 10:00:00"]
+    );
+    assert_eq!(transactions[0].timestamp, "113/01/15 下午 02:03:22");
+}
@@ -0,0 +1,90 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use bankflow_core::EncodingSource;
+
+const FILE_A_CSV: &str = "\u{feff}交易序號,帳號,客戶姓名,交易時間,交易類型,身分證/統編,交易摘要,交易後餘額,支出金額,存入金額
+1,0012345,王小明,2024-01-15 10:30:00,轉帳,A123456789,\"房租, 一月\",10000,500,
+2,0012345,王小明,2024-01-15 11:00:00,轉帳,A123456789,薪資,20000,,1500
+";
+
+const FILE_B_TSV: &str = "登入序號\t帳號\t登入時間\tIP位址
+1\t0012345\t2024-01-15 10:30:01\t203.0.113.1
+";
+
+#[test]
+fn parse_transactions_from_csv() {
+    let (transactions, meta) =
+        Parser::parse_transactions_from_bytes(FILE_A_CSV.as_bytes(), "a.csv", None, None).expect("parse");
+    assert_eq!(meta.file_type, "csv");
+    assert_eq!(meta.row_count, 3);
+    assert_eq!(meta.column_count, 10);
+    assert_eq!(transactions.len(), 2);
+
+    let tx = &transactions[0];
+    assert_eq!(tx.account, "0012345");
+    assert_eq!(tx.timestamp, "2024-01-15 10:30:00");
+    assert!(tx.datetime.is_some());
+    assert_eq!(tx.expense, Some(500.0));
+    assert_eq!(tx.income, None);
+    assert_eq!(tx.raw_columns[6], "房租, 一月");
+    assert_eq!(transactions[1].income, Some(1500.0));
+}
+
+#[test]
+fn parse_ip_records_from_tsv() {
+    let (records, meta) =
+        Parser::parse_ip_records_from_bytes(FILE_B_TSV.as_bytes(), "b.txt", None, None).expect("parse");
+    assert_eq!(meta.file_type, "tsv");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].account, "0012345");
+    assert_eq!(records[0].ip_address, "203.0.113.1");
+    assert_eq!(records[0].row_index, 2);
+}
+
+#[test]
+fn get_headers_from_csv() {
+    let sheets = Parser::get_headers_from_bytes(FILE_A_CSV.as_bytes(), "a.csv", None).expect("headers");
+    assert_eq!(sheets.len(), 1);
+    assert_eq!(sheets[0].name, "a.csv");
+    assert_eq!(sheets[0].headers[1], "帳號");
+    assert_eq!(sheets[0].headers[9], "存入金額");
+}
+
+#[test]
+fn parse_big5_csv_detects_encoding() {
+    let (bytes, _, _) = encoding_rs::BIG5.encode(FILE_A_CSV.trim_start_matches('\u{feff}'));
+    let (transactions, meta) =
+        Parser::parse_transactions_from_bytes(&bytes, "a.csv", None, None).expect("parse");
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].raw_columns[2], "王小明");
+
+    let encoding = meta.encoding.expect("encoding diagnostic");
+    assert_eq!(encoding.encoding, "Big5");
+    assert_eq!(encoding.source, EncodingSource::Detected);
+}
+
+#[test]
+fn parse_utf16_tsv_with_bom() {
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in FILE_B_TSV.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    let (records, meta) =
+        Parser::parse_ip_records_from_bytes(&bytes, "b.tsv", None, None).expect("parse");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].ip_address, "203.0.113.1");
+    assert_eq!(meta.encoding.expect("encoding").source, EncodingSource::Bom);
+}
+
+#[test]
+fn encoding_override_is_respected() {
+    let (bytes, _, _) = encoding_rs::BIG5.encode("帳號,交易時間\nACC1,2024-01-15 10:30:00\n");
+    let options = ParseOptions {
+        encoding: Some("cp950".to_string()),
+        ..Default::default()
+    };
+    let sheets = Parser::get_headers_from_bytes(&bytes, "a.csv", Some(&options)).expect("headers");
+    assert_eq!(sheets[0].headers, vec!["帳號", "交易時間"]);
+
+    let meta = Parser::get_metadata_from_bytes(&bytes, "a.csv", Some(&options)).expect("meta");
+    assert_eq!(meta.encoding.expect("encoding").source, EncodingSource::Override);
+}
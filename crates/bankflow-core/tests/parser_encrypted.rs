@@ -0,0 +1,75 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use bankflow_core::CoreError;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures").join(name);
+    std::fs::read(path).expect("read fixture")
+}
+
+/// Agile-encrypted (AES-256, SHA-512) statement; its password is "bankflow"
+fn encrypted_fixture() -> Vec<u8> {
+    fixture("encrypted_statement.xlsx")
+}
+
+fn with_password(password: &str) -> ParseOptions {
+    ParseOptions { password: Some(password.to_string()), ..Default::default() }
+}
+
+#[test]
+fn encrypted_workbook_opens_with_password() {
+    let bytes = encrypted_fixture();
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(&bytes, "statement.xlsx", None, Some(&with_password("bankflow")))
+            .expect("parse");
+
+    assert_eq!(metadata.file_type, "xlsx");
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].account, "ACC1");
+    assert_eq!(transactions[0].expense, Some(500.0));
+    assert_eq!(transactions[1].income, Some(1200.0));
+
+    let mut streamed = Vec::new();
+    Parser::stream_transactions_from_bytes(
+        &bytes,
+        "statement.xlsx",
+        None,
+        Some(&with_password("bankflow")),
+        1,
+        |chunk, _| streamed.extend(chunk),
+    )
+    .expect("stream");
+    assert_eq!(streamed.len(), 2);
+}
+
+#[test]
+fn sha1_encrypted_workbook_opens_with_password() {
+    // The same statement, agile-encrypted with AES-128 and SHA-1
+    let bytes = fixture("encrypted_statement_sha1.xlsx");
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(&bytes, "statement.xlsx", None, Some(&with_password("bankflow")))
+            .expect("parse");
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].expense, Some(500.0));
+
+    let wrong = Parser::get_metadata_from_bytes(&bytes, "statement.xlsx", Some(&with_password("bankf1ow")));
+    assert!(matches!(wrong, Err(CoreError::WrongPassword)));
+}
+
+#[test]
+fn encrypted_workbook_reports_missing_or_wrong_password() {
+    let bytes = encrypted_fixture();
+    let missing = Parser::parse_transactions_from_bytes(&bytes, "statement.xlsx", None, None);
+    assert!(matches!(missing, Err(CoreError::PasswordRequired)));
+
+    let wrong = Parser::get_metadata_from_bytes(&bytes, "statement.xlsx", Some(&with_password("bankf1ow")));
+    assert!(matches!(wrong, Err(CoreError::WrongPassword)));
+}
+
+#[test]
+fn password_is_not_serialized() {
+    let json = serde_json::to_string(&with_password("bankflow")).expect("serialize");
+    assert!(!json.contains("bankflow"));
+    let options: ParseOptions = serde_json::from_str(r#"{"password": "bankflow"}"#).expect("deserialize");
+    assert_eq!(options.password.as_deref(), Some("bankflow"));
+}
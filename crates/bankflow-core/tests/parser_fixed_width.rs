@@ -0,0 +1,251 @@
+use bankflow_core::parser::Parser;
+use bankflow_core::{FormatProfile, IpMatcher, ParseOptions};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+const REPORT_PROFILE: &str = r#"
+version = 1
+name = "Legacy core banking return"
+kind = "transactions"
+amount_mode = "indicator"
+encoding = "big5"
+timestamp_format = "ROC/%m/%d %H:%M:%S"
+
+[columns]
+timestamp = ["交易日期時間"]
+account = ["帳號"]
+counterparty_name = ["戶名"]
+amount = ["交易金額"]
+direction = ["借貸別"]
+
+[fixed_width]
+page_header_lines = 3
+page_footer_lines = 1
+skip_lines = ["本頁小計"]
+
+[fixed_width.record_type]
+start = 0
+end = 1
+values = ["D"]
+
+[[fixed_width.fields]]
+name = "交易日期時間"
+start = 2
+end = 20
+
+[[fixed_width.fields]]
+name = "帳號"
+start = 21
+end = 31
+
+[[fixed_width.fields]]
+name = "戶名"
+start = 32
+end = 40
+
+[[fixed_width.fields]]
+name = "交易金額"
+start = 41
+end = 53
+
+[[fixed_width.fields]]
+name = "借貸別"
+start = 54
+end = 56
+"#;
+
+const LOG_PROFILE: &str = r#"
+version = 1
+name = "Legacy login log"
+kind = "ip_log"
+
+[columns]
+timestamp = ["時間"]
+account = ["帳號"]
+ip_address = ["IP"]
+
+[fixed_width]
+page_length = 4
+page_header_lines = 1
+
+[[fixed_width.fields]]
+name = "時間"
+start = 0
+end = 19
+
+[[fixed_width.fields]]
+name = "帳號"
+start = 20
+end = 30
+
+[[fixed_width.fields]]
+name = "IP"
+start = 31
+end = 46
+"#;
+
+/// Left-align `text` in `width` display columns (CJK characters take two)
+fn pad(text: &str, width: usize) -> String {
+    let used: usize = text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(used)))
+}
+
+fn record(kind: &str, time: &str, account: &str, name: &str, amount: &str, direction: &str) -> String {
+    format!(
+        "{}{}{}{}{:>12} {}",
+        pad(kind, 2),
+        pad(time, 19),
+        pad(account, 11),
+        pad(name, 9),
+        amount,
+        direction
+    )
+}
+
+/// Two form-feed pages of a Big5 report, each with a title, column headings,
+/// a ruling, account header and trailer records and a page-number footer
+fn legacy_report() -> Vec<u8> {
+    let heading = |page: usize| {
+        vec![
+            format!("{}範例銀行 存款交易明細表", if page == 1 { "" } else { "\u{c}" }),
+            record("類", "交易日期時間", "帳號", "戶名", "交易金額", "借貸"),
+            "=".repeat(56),
+        ]
+    };
+    let mut lines = heading(1);
+    lines.extend([
+        record("H", "帳戶", "ACC1", "王小明", "", ""),
+        record("D", "113/01/15 10:30:00", "ACC1", "王小明", "1,250", "借"),
+        String::new(),
+        record("D", "113/01/15 11:00:00", "ACC1", "王小明", "3,000", "貸"),
+        record("T", "本頁小計", "", "", "4,250", ""),
+        "第 1 頁".to_string(),
+    ]);
+    lines.extend(heading(2));
+    lines.extend([
+        record("D", "113/01/16 09:00:00", "ACC2", "陳大文", "80", "借"),
+        "第 2 頁".to_string(),
+    ]);
+    let text = lines.join("\r\n") + "\r\n";
+    let (bytes, _, _) = encoding_rs::BIG5.encode(&text);
+    bytes.into_owned()
+}
+
+fn options(profile: &str) -> ParseOptions {
+    ParseOptions {
+        profile: Some(FormatProfile::import(profile).expect("profile")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn fixed_width_report_is_cut_by_profile_layout() {
+    let bytes = legacy_report();
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(&bytes, "return.txt", None, Some(&options(REPORT_PROFILE)))
+            .expect("parse");
+
+    assert_eq!(metadata.file_type, "fixed_width");
+    assert_eq!(metadata.encoding.as_ref().map(|e| e.encoding.as_str()), Some("Big5"));
+    assert_eq!(metadata.header_row, Some(0));
+    assert_eq!(metadata.headers, ["交易日期時間", "帳號", "戶名", "交易金額", "借貸別"]);
+
+    let rows: Vec<usize> = transactions.iter().map(|t| t.row_index).collect();
+    assert_eq!(rows, [5, 7, 13]);
+    let amounts: Vec<(&str, Option<f64>, Option<f64>)> =
+        transactions.iter().map(|t| (t.account.as_str(), t.expense, t.income)).collect();
+    assert_eq!(amounts, [("ACC1", Some(1250.0), None), ("ACC1", None, Some(3000.0)), ("ACC2", Some(80.0), None)]);
+    assert_eq!(transactions[2].counterparty_name.as_deref(), Some("陳大文"));
+    let dt = transactions[0].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-15 10:30:00");
+    assert!(metadata.report.skipped_rows.is_empty());
+
+    let mut streamed = Vec::new();
+    let stream_metadata = Parser::stream_transactions_from_bytes(
+        &bytes,
+        "return.txt",
+        None,
+        Some(&options(REPORT_PROFILE)),
+        2,
+        |chunk, _| streamed.extend(chunk),
+    )
+    .expect("stream");
+    let key = |t: &bankflow_core::Transaction| (t.row_index, t.account.clone(), t.expense, t.income, t.datetime);
+    assert_eq!(streamed.iter().map(key).collect::<Vec<_>>(), transactions.iter().map(key).collect::<Vec<_>>());
+    assert_eq!(stream_metadata.header_row, Some(0));
+    assert_eq!(stream_metadata.file_type, "fixed_width");
+}
+
+#[test]
+fn fixed_width_returns_match_fixed_width_logs() {
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(&legacy_report(), "return.txt", None, Some(&options(REPORT_PROFILE)))
+            .expect("parse");
+
+    // Pages of four lines without form feeds, each opening with a heading
+    let log = "\
+時間                帳號       IP
+2024-01-15 10:30:00 ACC1       1.1.1.1
+2024-01-16 09:00:00 ACC2       2.2.2.2
+
+時間                帳號       IP
+2024-01-16 12:00:00 ACC2       3.3.3.3
+";
+    let (records, metadata) =
+        Parser::parse_ip_records_from_bytes(log.as_bytes(), "login.txt", None, Some(&options(LOG_PROFILE)))
+            .expect("parse");
+    assert_eq!(metadata.file_type, "fixed_width");
+    let rows: Vec<usize> = records.iter().map(|r| r.row_index).collect();
+    assert_eq!(rows, [2, 3, 6]);
+
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    let matched: Vec<Option<&str>> = transactions.iter().map(|t| t.matched_ip.as_deref()).collect();
+    assert_eq!(matched, [Some("1.1.1.1"), Some("N/A"), Some("2.2.2.2")]);
+}
+
+#[test]
+fn fixed_width_layout_without_records_is_an_error() {
+    let text = "範例銀行 存款交易明細表\nno records today\n";
+    let options = options(REPORT_PROFILE);
+    assert!(Parser::parse_transactions_from_bytes(text.as_bytes(), "empty.txt", None, Some(&options)).is_err());
+}
+
+/// The same return saved as a workbook, with the layout's field names as headers
+fn report_workbook() -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    let rows = [
+        ["交易日期時間", "帳號", "戶名", "交易金額", "借貸別"],
+        ["113/01/15 10:30:00", "ACC1", "王小明", "1250", "借"],
+    ];
+    for (row, cells) in rows.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            ws.write_string(row as u32, col as u16, *cell)?;
+        }
+    }
+    workbook.save_to_buffer()
+}
+
+#[test]
+fn workbook_matched_to_a_fixed_width_profile_is_read_as_a_workbook() {
+    let bytes = report_workbook().expect("workbook");
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(&bytes, "return.xlsx", None, Some(&options(REPORT_PROFILE)))
+            .expect("parse");
+
+    assert_eq!(metadata.file_type, "xlsx");
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].expense, Some(1250.0));
+
+    let mut streamed = Vec::new();
+    let stream_metadata = Parser::stream_transactions_from_bytes(
+        &bytes,
+        "return.xlsx",
+        None,
+        Some(&options(REPORT_PROFILE)),
+        10,
+        |chunk, _| streamed.extend(chunk),
+    )
+    .expect("stream");
+    assert_eq!(stream_metadata.file_type, "xlsx");
+    assert_eq!(streamed.len(), 1);
+}
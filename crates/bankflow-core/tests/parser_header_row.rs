@@ -0,0 +1,85 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+const STATEMENT_CSV: &str = "\
+某某商業銀行 存款交易明細\n\
+戶名：王小明,帳號：0123456789\n\
+查詢期間：2024/01/01 ~ 2024/01/31\n\
+\n\
+交易時間,帳號,支出金額,存入金額\n\
+2024-01-15 10:30:00,ACC1,500,\n\
+2024-01-16 11:00:00,ACC1,,1200\n";
+
+/// Statement whose header sits below a title block, starting at row 3
+fn build_titled_workbook() -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    ws.write_string(2, 0, "某某商業銀行 IP 登入紀錄")?;
+    ws.write_string(3, 0, "查詢期間")?;
+    ws.write_string(3, 1, "2024/01")?;
+    for (col, header) in ["登入時間", "帳號", "IP位址"].iter().enumerate() {
+        ws.write_string(5, col as u16, *header)?;
+    }
+    ws.write_string(6, 0, "2024-01-15 10:25:00")?;
+    ws.write_string(6, 1, "ACC1")?;
+    ws.write_string(6, 2, "1.2.3.4")?;
+    workbook.save_to_buffer()
+}
+
+#[test]
+fn header_row_is_found_below_title_block() {
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(STATEMENT_CSV.as_bytes(), "a.csv", None, None)
+            .expect("parse");
+
+    assert_eq!(metadata.header_row, Some(5));
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].row_index, 6);
+    assert_eq!(transactions[1].income, Some(1200.0));
+}
+
+#[test]
+fn header_row_accounts_for_leading_empty_rows() {
+    let bytes = build_titled_workbook().expect("build workbook");
+    let (records, metadata) =
+        Parser::parse_ip_records_from_bytes(&bytes, "b.xlsx", None, None).expect("parse");
+
+    assert_eq!(metadata.header_row, Some(6));
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].ip_address, "1.2.3.4");
+    assert_eq!(records[0].row_index, 7);
+
+    let sheets = Parser::get_headers_from_bytes(&bytes, "b.xlsx", None).expect("headers");
+    assert_eq!(sheets[0].header_row, 6);
+    assert_eq!(sheets[0].headers, vec!["登入時間", "帳號", "IP位址"]);
+}
+
+#[test]
+fn header_row_option_overrides_detection() {
+    let options = ParseOptions {
+        header_row: Some(1),
+        ..Default::default()
+    };
+    let err = Parser::parse_transactions_from_bytes(
+        STATEMENT_CSV.as_bytes(),
+        "a.csv",
+        None,
+        Some(&options),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Missing required columns"));
+
+    let options = ParseOptions {
+        header_row: Some(5),
+        ..Default::default()
+    };
+    let (transactions, metadata) = Parser::parse_transactions_from_bytes(
+        STATEMENT_CSV.as_bytes(),
+        "a.csv",
+        None,
+        Some(&options),
+    )
+    .expect("parse");
+    assert_eq!(metadata.header_row, Some(5));
+    assert_eq!(transactions.len(), 2);
+}
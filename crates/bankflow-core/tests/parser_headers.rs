@@ -1,23 +1,134 @@
-use bankflow_core::parser::header_map::{map_file_a_columns, map_file_b_columns};
+use bankflow_core::parser::header_map::{
+    validate_file_a_headers, validate_file_b_headers, AmountColumns,
+};
+use bankflow_core::parser::Parser;
+use bankflow_core::ProfileKind;
+use std::collections::HashMap;
+
+fn to_headers(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
 
 #[test]
 fn file_a_header_mapping_prefers_named_columns() {
-    let headers = vec![
+    let headers = to_headers(&[
         "交易序號", "帳號", "客戶姓名", "交易時間", "交易類型",
         "身分證/統編", "交易摘要", "交易後餘額", "支出金額", "存入金額",
-    ];
-    let map = map_file_a_columns(&headers);
+    ]);
+    let map = validate_file_a_headers(&headers, None).expect("file a headers");
     assert_eq!(map.timestamp, 3);
     assert_eq!(map.account, 1);
-    assert_eq!(map.expense, 8);
-    assert_eq!(map.income, 9);
+    assert_eq!(map.amounts, AmountColumns::Split { expense: 8, income: 9 });
 }
 
 #[test]
 fn file_b_header_mapping_prefers_named_columns() {
-    let headers = vec!["登入序號", "帳號", "登入時間", "IP位址", "裝置資訊", "登入地區"];
-    let map = map_file_b_columns(&headers);
+    let headers = to_headers(&["登入序號", "帳號", "登入時間", "IP位址", "裝置資訊", "登入地區"]);
+    let map = validate_file_b_headers(&headers, None).expect("file b headers");
     assert_eq!(map.timestamp, 2);
     assert_eq!(map.account, 1);
     assert_eq!(map.ip_address, 3);
 }
+
+#[test]
+fn split_date_and_time_columns_are_paired() {
+    let headers = to_headers(&["交易日期", "交易時間", "帳號", "支出金額", "存入金額"]);
+    let map = validate_file_a_headers(&headers, None).expect("file a headers");
+    assert_eq!(map.timestamp, 0);
+    assert_eq!(map.time, Some(1));
+
+    let headers = to_headers(&["登入日期", "登入時間", "帳號", "IP位址"]);
+    let map = validate_file_b_headers(&headers, None).expect("file b headers");
+    assert_eq!(map.timestamp, 0);
+    assert_eq!(map.time, Some(1));
+
+    let headers = to_headers(&["交易時間", "帳號", "支出金額", "存入金額"]);
+    let map = validate_file_a_headers(&headers, None).expect("file a headers");
+    assert_eq!(map.time, None);
+}
+
+#[test]
+fn date_and_time_columns_follow_the_mapping() {
+    let headers = to_headers(&["Posted", "At", "Stamp", "帳號", "IP位址"]);
+    let mapping: HashMap<String, String> = [("date", "Posted"), ("time", "At")]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let map = validate_file_b_headers(&headers, Some(&mapping)).expect("file b headers");
+    assert_eq!(map.timestamp, 0);
+    assert_eq!(map.time, Some(1));
+
+    // An explicit timestamp mapping keeps a single column
+    let headers = to_headers(&["交易日期", "交易時間", "帳號", "IP位址"]);
+    let mapping: HashMap<String, String> = [("timestamp".to_string(), "交易時間".to_string())].into();
+    let map = validate_file_b_headers(&headers, Some(&mapping)).expect("file b headers");
+    assert_eq!(map.timestamp, 1);
+    assert_eq!(map.time, None);
+}
+
+#[test]
+fn single_amount_layouts_are_detected() {
+    let headers = to_headers(&["交易時間", "帳號", "交易金額", "摘要"]);
+    let map = validate_file_a_headers(&headers, None).expect("signed amount");
+    assert_eq!(map.amounts, AmountColumns::Signed { amount: 2 });
+
+    let headers = to_headers(&["交易時間", "帳號", "借貸", "金額"]);
+    let map = validate_file_a_headers(&headers, None).expect("indicator");
+    assert_eq!(map.amounts, AmountColumns::Indicator { amount: 3, indicator: 2 });
+
+    // An explicit amount mapping wins over separate columns
+    let headers = to_headers(&["交易時間", "帳號", "支出金額", "存入金額", "淨額"]);
+    let mapping: HashMap<String, String> = [("amount".to_string(), "淨額".to_string())].into();
+    let map = validate_file_a_headers(&headers, Some(&mapping)).expect("mapped amount");
+    assert_eq!(map.amounts, AmountColumns::Signed { amount: 4 });
+}
+
+#[test]
+fn near_miss_headers_are_matched() {
+    let headers = to_headers(&["交易 時間", "帳號", "支出金額", "存入金額"]);
+    let map = validate_file_a_headers(&headers, None).expect("spaced header");
+    assert_eq!(map.timestamp, 0);
+
+    let headers = to_headers(&["交易時間(台北)", "帳號", "支出金額", "存入金額"]);
+    let map = validate_file_a_headers(&headers, None).expect("annotated header");
+    assert_eq!(map.timestamp, 0);
+
+    let headers = to_headers(&["Txn Time", "Account", "Expense", "Income"]);
+    let map = validate_file_a_headers(&headers, None).expect("english header");
+    assert_eq!(map.timestamp, 0);
+
+    let headers = to_headers(&["登入時間", "帳號", "ＩＰ 位址"]);
+    let map = validate_file_b_headers(&headers, None).expect("full-width header");
+    assert_eq!(map.ip_address, 2);
+}
+
+#[test]
+fn missing_columns_come_with_suggestions() {
+    let headers = to_headers(&["交易日時", "對方帳號", "支出金額", "存入金額", "備註"]);
+    let missing = match validate_file_a_headers(&headers, None) {
+        Err(missing) => missing,
+        Ok(map) => panic!("unexpected match: {:?}", map),
+    };
+
+    let keys: Vec<&str> = missing.iter().map(|m| m.key.as_str()).collect();
+    assert_eq!(keys, vec!["timestamp", "account"]);
+    assert_eq!(missing[0].suggestions.first().map(String::as_str), Some("交易日時"));
+    assert_eq!(missing[1].suggestions, vec!["對方帳號".to_string()]);
+}
+
+#[test]
+fn missing_columns_are_listed_for_repair() {
+    let data = "交易日時,帳號,支出金額,存入金額\n2024-01-15 10:30:00,ACC1,100,\n";
+    let missing =
+        Parser::missing_columns_from_bytes(data.as_bytes(), ProfileKind::Transactions, None, None).expect("headers");
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].key, "timestamp");
+
+    let mapping: HashMap<String, String> = [("timestamp".to_string(), "交易日時".to_string())].into();
+    let missing = Parser::missing_columns_from_bytes(data.as_bytes(), ProfileKind::Transactions, Some(&mapping), None)
+        .expect("headers");
+    assert!(missing.is_empty());
+
+    let err = Parser::parse_transactions_from_bytes(data.as_bytes(), "a.csv", None, None).unwrap_err();
+    assert!(err.to_string().contains("did you mean: 交易日時"));
+}
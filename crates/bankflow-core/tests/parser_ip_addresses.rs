@@ -0,0 +1,55 @@
+use bankflow_core::parser::Parser;
+use bankflow_core::{IpMatcher, Transaction};
+
+const IP_LOG: &str = "\
+登入時間,帳號,IP位址
+2024-01-15 10:30:00,ACC1,1.2.3.4:443
+2024-01-15 10:30:01,ACC1, 1.2.3.4 
+2024-01-15 10:30:01,ACC1,\"[2001:DB8::1]:8443, ::ffff:5.6.7.8\"
+2024-01-15 11:00:00,ACC2,fe80::1%eth0
+2024-01-15 12:00:00,ACC3,unknown
+";
+
+#[test]
+fn ip_cells_are_normalized_split_and_checked() {
+    let (records, metadata) =
+        Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None).expect("parse");
+
+    let parsed: Vec<(usize, &str, bool)> =
+        records.iter().map(|r| (r.row_index, r.ip_address.as_str(), r.ip.is_some())).collect();
+    assert_eq!(
+        parsed,
+        [
+            (2, "1.2.3.4", true),
+            (3, "1.2.3.4", true),
+            (4, "2001:db8::1", true),
+            (4, "5.6.7.8", true),
+            (5, "fe80::1", true),
+            (6, "unknown", false),
+        ]
+    );
+    assert_eq!(records[2].ip_text, "[2001:DB8::1]:8443, ::ffff:5.6.7.8");
+
+    let invalid = &metadata.report.invalid_ips;
+    assert_eq!(invalid.len(), 1);
+    assert_eq!((invalid[0].row, invalid[0].column.as_str(), invalid[0].value.as_str()), (6, "IP位址", "unknown"));
+}
+
+#[test]
+fn equivalent_addresses_match_once() {
+    let (records, _) = Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None).expect("parse");
+    let mut transactions = vec![Transaction::new(
+        "2024-01-15 10:30:00".to_string(),
+        "ACC1".to_string(),
+        None,
+        Some(100.0),
+        vec![],
+        2,
+    )];
+
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    assert_eq!(
+        transactions[0].matched_ip.as_deref(),
+        Some("0s:1.2.3.4 | +1s:2001:db8::1 | +1s:5.6.7.8")
+    );
+}
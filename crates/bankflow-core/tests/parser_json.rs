@@ -0,0 +1,82 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use bankflow_core::IpMatcher;
+use std::collections::HashMap;
+
+const STATEMENT: &str = r#"{
+  "exported_at": "2024-02-01",
+  "records": [
+    {"txn_time": "2024-01-15 10:30:00", "acct_no": "ACC1", "支出金額": 250.5, "存入金額": null,
+     "counterparty": {"account": "CP9", "name": "王小明"}},
+    {"txn_time": "2024-01-15 11:00:00", "acct_no": "ACC1", "支出金額": null, "存入金額": 1000}
+  ]
+}"#;
+
+const LOGIN_LOG: &str = "\
+{\"timestamp\": \"2024-01-15 10:30:01\", \"account\": \"ACC1\", \"ip\": [\"1.1.1.1\", \"2.2.2.2\"], \"device_id\": \"DEV-A\"}
+{\"timestamp\": \"2024-01-15 11:00:00\", \"account\": \"ACC1\", \"ip\": \"3.3.3.3\"}
+";
+
+fn statement_mapping() -> HashMap<String, String> {
+    HashMap::from([
+        ("timestamp".to_string(), "txn_time".to_string()),
+        ("account".to_string(), "acct_no".to_string()),
+        ("counterparty_account".to_string(), "counterparty.account".to_string()),
+    ])
+}
+
+#[test]
+fn json_statement_maps_field_names_like_headers() {
+    let mapping = statement_mapping();
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "case.json", Some(&mapping), None).expect("parse");
+
+    assert_eq!(metadata.file_type, "json");
+    assert_eq!(metadata.header_row, Some(1));
+    assert_eq!(metadata.headers, ["txn_time", "acct_no", "支出金額", "存入金額", "counterparty.account", "counterparty.name"]);
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].expense, Some(250.5));
+    assert_eq!(transactions[0].counterparty_account.as_deref(), Some("CP9"));
+    assert_eq!(transactions[1].income, Some(1000.0));
+    assert_eq!(transactions[1].row_index, 3);
+}
+
+#[test]
+fn ndjson_log_feeds_matching() {
+    // A header row option meant for spreadsheets does not apply to JSON
+    let options = ParseOptions { header_row: Some(5), ..Default::default() };
+    let (records, metadata) =
+        Parser::parse_ip_records_from_bytes(LOGIN_LOG.as_bytes(), "log.ndjson", None, Some(&options)).expect("parse");
+    assert_eq!(metadata.file_type, "ndjson");
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].login.device_id.as_deref(), Some("DEV-A"));
+
+    let mapping = statement_mapping();
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "case.json", Some(&mapping), None).expect("parse");
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    assert_eq!(transactions[0].matched_ip.as_deref(), Some("+1s:1.1.1.1 | +1s:2.2.2.2"));
+    assert_eq!(transactions[1].matched_ip.as_deref(), Some("3.3.3.3"));
+}
+
+#[test]
+fn streaming_json_matches_loading() {
+    let (loaded, _) = Parser::parse_ip_records_from_bytes(LOGIN_LOG.as_bytes(), "log.ndjson", None, None).expect("parse");
+
+    let mut streamed = Vec::new();
+    let metadata = Parser::stream_ip_records_from_bytes(LOGIN_LOG.as_bytes(), "log.ndjson", None, None, 1, |chunk, _| {
+        streamed.extend(chunk)
+    })
+    .expect("stream");
+
+    assert_eq!(metadata.file_type, "ndjson");
+    let rows = |records: &[bankflow_core::IpRecord]| -> Vec<(usize, String)> {
+        records.iter().map(|r| (r.row_index, r.ip_address.clone())).collect()
+    };
+    assert_eq!(rows(&streamed), rows(&loaded));
+}
+
+#[test]
+fn json_without_records_is_rejected() {
+    let err = Parser::parse_ip_records_from_bytes(b"[1, 2, 3]", "bad.json", None, None).unwrap_err();
+    assert!(err.to_string().contains("record objects"), "{}", err);
+}
@@ -0,0 +1,56 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::parser::Parser;
+use bankflow_core::{IpMatcher, LoginDetails, Transaction};
+use calamine::{open_workbook_auto_from_rs, Reader};
+use std::io::Cursor;
+
+const LOGIN_LOG: &str = "\
+登入時間,帳號,IP位址,裝置編號,User Agent,登入管道,登入結果,Session ID
+2024-01-15 10:30:00,ACC1,1.1.1.1,DEV-A,Mozilla/5.0,APP,成功,S1
+2024-01-15 10:30:01,ACC1,2.2.2.2,DEV-B,,APP,成功,S2
+2024-01-15 12:00:00,ACC1,3.3.3.3,DEV-C,,WEB,失敗,
+";
+
+fn transaction(timestamp: &str) -> Transaction {
+    Transaction::new(timestamp.to_string(), "ACC1".to_string(), None, Some(100.0), vec![], 2)
+}
+
+#[test]
+fn login_columns_are_read_into_records() {
+    let (records, _) =
+        Parser::parse_ip_records_from_bytes(LOGIN_LOG.as_bytes(), "log.csv", None, None).expect("parse");
+
+    assert_eq!(
+        records[0].login,
+        LoginDetails {
+            device_id: Some("DEV-A".to_string()),
+            user_agent: Some("Mozilla/5.0".to_string()),
+            channel: Some("APP".to_string()),
+            login_result: Some("成功".to_string()),
+            session_id: Some("S1".to_string()),
+        }
+    );
+    // Empty cells stay unset
+    assert_eq!(records[1].login.user_agent, None);
+    assert_eq!(records[2].login.session_id, None);
+}
+
+#[test]
+fn matched_logins_are_carried_into_the_export() {
+    let (records, _) =
+        Parser::parse_ip_records_from_bytes(LOGIN_LOG.as_bytes(), "log.csv", None, None).expect("parse");
+    let mut transactions = vec![transaction("2024-01-15 10:30:00"), transaction("2024-01-15 12:00:01")];
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+
+    let both = &transactions[0].matched_login;
+    assert_eq!(both.device_id.as_deref(), Some("DEV-A | DEV-B"));
+    assert_eq!(both.user_agent.as_deref(), Some("Mozilla/5.0"));
+    assert_eq!(both.channel.as_deref(), Some("APP"));
+    assert_eq!(transactions[1].matched_login.login_result.as_deref(), Some("失敗"));
+
+    let bytes = Exporter::export_to_bytes(&transactions, &[], &[], &[]).expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    let range = workbook.worksheet_range("Summary").expect("range");
+    let row: Vec<String> = range.rows().nth(1).expect("first row")[7..12].iter().map(|c| c.to_string()).collect();
+    assert_eq!(row, ["DEV-A | DEV-B", "Mozilla/5.0", "APP", "成功", "S1 | S2"]);
+}
@@ -0,0 +1,117 @@
+use bankflow_core::parser::{ParseOptions, Parser, SheetSelector};
+use bankflow_core::IpMatcher;
+
+/// Online-banking "Excel" download: an HTML page with a title row above the
+/// headers, a summary table after it and sloppy markup
+const HTML_STATEMENT: &str = r#"<html xmlns:x="urn:schemas-microsoft-com:office:excel">
+<head><meta http-equiv="Content-Type" content="text/html; charset=utf-8">
+<style>td { mso-number-format:"\@"; }</style></head>
+<body>
+<table border="1">
+  <tr><td colspan="4"><b>存款交易明細</b></td></tr>
+  <tr><th>交易時間</th><th>帳號</th><th>支出金額</th><th>存入金額</th></tr>
+  <tr><td>2024-01-15 10:30:00<td>ACC1<td>1,250.00<td>&nbsp;
+  <tr><td>2024-01-15 11:00:00</td><td>ACC1</td><td></td><td>3,000</td></tr>
+</table>
+<table><tr><td>合計</td><td>2</td></tr></table>
+</body></html>"#;
+
+const HTML_LOGIN_LOG: &str = "<table>\
+    <tr><td>時間</td><td>帳號</td><td>IP位址</td></tr>\
+    <tr><td>2024-01-15 10:30:01</td><td>ACC1</td><td>1.1.1.1<br>2.2.2.2</td></tr>\
+    </table>";
+
+const SPREADSHEET_ML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?mso-application progid="Excel.Sheet"?>
+<Workbook xmlns="urn:schemas-microsoft-com:office:spreadsheet"
+ xmlns:ss="urn:schemas-microsoft-com:office:spreadsheet">
+ <Worksheet ss:Name="說明">
+  <Table><Row><Cell><Data ss:Type="String">對帳單</Data></Cell></Row></Table>
+ </Worksheet>
+ <Worksheet ss:Name="明細">
+  <Table>
+   <Row ss:Index="2">
+    <Cell><Data ss:Type="String">交易時間</Data></Cell>
+    <Cell><Data ss:Type="String">帳號</Data></Cell>
+    <Cell><Data ss:Type="String">支出金額</Data></Cell>
+    <Cell><Data ss:Type="String">存入金額</Data></Cell>
+   </Row>
+   <Row>
+    <Cell><Data ss:Type="DateTime">2024-01-15T10:30:00.000</Data></Cell>
+    <Cell><Data ss:Type="String">ACC1</Data></Cell>
+    <Cell><Data ss:Type="Number">500</Data></Cell>
+   </Row>
+   <Row>
+    <Cell><Data ss:Type="DateTime">2024-01-16T09:00:00.000</Data></Cell>
+    <Cell><Data ss:Type="String">ACC2</Data></Cell>
+    <Cell ss:Index="4"><Data ss:Type="Number">1200.5</Data></Cell>
+   </Row>
+  </Table>
+ </Worksheet>
+</Workbook>"#;
+
+fn sheet(selector: SheetSelector) -> ParseOptions {
+    ParseOptions { sheets: vec![selector], ..Default::default() }
+}
+
+#[test]
+fn html_xls_goes_through_header_mapping() {
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(HTML_STATEMENT.as_bytes(), "statement.xls", None, None).expect("parse");
+
+    assert_eq!(metadata.file_type, "html");
+    assert_eq!(metadata.sheets, ["Table 1"]);
+    assert_eq!(metadata.header_row, Some(2));
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].expense, Some(1250.0));
+    assert_eq!(transactions[0].income, None);
+    assert_eq!(transactions[1].income, Some(3000.0));
+    assert_eq!(transactions[1].row_index, 4);
+
+    let (records, _) =
+        Parser::parse_ip_records_from_bytes(HTML_LOGIN_LOG.as_bytes(), "login.xls", None, None).expect("parse");
+    assert_eq!(records.len(), 2);
+
+    let mut transactions = transactions;
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    assert_eq!(transactions[0].matched_ip.as_deref(), Some("+1s:1.1.1.1 | +1s:2.2.2.2"));
+}
+
+#[test]
+fn html_tables_are_listed_and_selectable() {
+    let headers = Parser::get_headers_from_bytes(HTML_STATEMENT.as_bytes(), "statement.xls", None).expect("headers");
+    let names: Vec<&str> = headers.iter().map(|h| h.name.as_str()).collect();
+    assert_eq!(names, ["Table 1", "Table 2"]);
+
+    let metadata = Parser::get_metadata_from_bytes(
+        HTML_STATEMENT.as_bytes(),
+        "statement.xls",
+        Some(&sheet(SheetSelector::Index(1))),
+    )
+    .expect("metadata");
+    assert_eq!(metadata.headers, ["合計", "2"]);
+}
+
+#[test]
+fn spreadsheetml_reads_selected_worksheet() {
+    let options = sheet(SheetSelector::Name("明細".to_string()));
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(SPREADSHEET_ML.as_bytes(), "export.xls", None, Some(&options))
+            .expect("parse");
+
+    assert_eq!(metadata.file_type, "spreadsheetml");
+    assert_eq!(metadata.header_row, Some(2));
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].timestamp, "2024-01-15 10:30:00");
+    assert_eq!(transactions[0].expense, Some(500.0));
+    assert_eq!(transactions[1].income, Some(1200.5));
+    assert_eq!(transactions[1].row_index, 4);
+
+    let mut streamed = Vec::new();
+    Parser::stream_transactions_from_bytes(SPREADSHEET_ML.as_bytes(), "export.xls", None, Some(&options), 1, |chunk, _| {
+        streamed.extend(chunk)
+    })
+    .expect("stream");
+    assert_eq!(streamed.len(), 2);
+    assert_eq!(streamed[1].row_index, 4);
+}
@@ -0,0 +1,142 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use bankflow_core::{IpMatcher, UncertainReason};
+use lopdf::{dictionary, Document, Object, Stream};
+
+/// Show each `(text, x, y)` in 10pt Helvetica
+fn page_content(texts: &[(&str, i64, i64)]) -> String {
+    texts
+        .iter()
+        .map(|(text, x, y)| format!("BT /F1 10 Tf {} {} Td ({}) Tj ET\n", x, y, text))
+        .collect()
+}
+
+/// A statement PDF with one page per content stream
+fn statement_pdf(pages: &[String]) -> Vec<u8> {
+    let mut document = Document::with_version("1.5");
+    let font = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let pages_id = document.new_object_id();
+    let kids: Vec<Object> = pages
+        .iter()
+        .map(|content| {
+            let content = document.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+            document
+                .add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content,
+                    "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+                    "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                })
+                .into()
+        })
+        .collect();
+    let count = kids.len() as i64;
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => count }),
+    );
+    let catalog = document.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    document.trailer.set("Root", catalog);
+
+    let mut bytes = Vec::new();
+    document.save_to(&mut bytes).expect("save");
+    bytes
+}
+
+/// Two pages with a title block, the header repeated on page 2, page
+/// number footers and a memo wrapped onto a second line
+fn two_page_statement() -> Vec<u8> {
+    let header = |y| {
+        [("Transaction Time", 40, y), ("Account", 150, y), ("Expense", 250, y), ("Income", 330, y), ("Memo", 410, y)]
+    };
+    let mut first = vec![("Account statement 2024-01", 40, 800)];
+    first.extend(header(760));
+    first.extend([
+        ("2024-01-15 10:30:00", 40, 740),
+        ("ACC1", 150, 740),
+        ("1,250.00", 250, 740),
+        ("Transfer to", 410, 740),
+        ("J. Smith", 410, 728),
+        ("2024-01-15 11:00:00", 40, 710),
+        ("ACC1", 150, 710),
+        ("3,000", 330, 710),
+        ("Page 1 of 2", 280, 40),
+    ]);
+    let mut second = vec![("Account statement 2024-01", 40, 800)];
+    second.extend(header(760));
+    second.extend([
+        ("2024-01-16 09:00:00", 40, 740),
+        ("ACC2", 150, 740),
+        ("80.5", 250, 740),
+        ("Page 2 of 2", 280, 40),
+    ]);
+    statement_pdf(&[page_content(&first), page_content(&second)])
+}
+
+#[test]
+fn pdf_statement_rows_keep_page_and_line() {
+    let bytes = two_page_statement();
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(&bytes, "statement.pdf", None, None).expect("parse");
+
+    assert_eq!(metadata.file_type, "pdf");
+    assert_eq!(metadata.sheets, ["Page 1", "Page 2"]);
+    assert_eq!(metadata.header_row, Some(2));
+    assert_eq!(metadata.headers, ["Transaction Time", "Account", "Expense", "Income", "Memo"]);
+
+    let sources: Vec<(Option<&str>, usize)> = transactions.iter().map(|t| (t.sheet.as_deref(), t.row_index)).collect();
+    assert_eq!(sources, [(Some("Page 1"), 3), (Some("Page 1"), 5), (Some("Page 2"), 3)]);
+    let amounts: Vec<(&str, Option<f64>, Option<f64>)> =
+        transactions.iter().map(|t| (t.account.as_str(), t.expense, t.income)).collect();
+    assert_eq!(amounts, [("ACC1", Some(1250.0), None), ("ACC1", None, Some(3000.0)), ("ACC2", Some(80.5), None)]);
+    assert_eq!(transactions[0].memo.as_deref(), Some("Transfer to J. Smith"));
+
+    let report = &metadata.report;
+    assert_eq!(report.rows_read, 3);
+    assert!(report.skipped_rows.is_empty());
+    assert_eq!(report.uncertain_rows.len(), 1);
+    assert_eq!(report.uncertain_rows[0].sheet.as_deref(), Some("Page 1"));
+    assert_eq!(report.uncertain_rows[0].row, 3);
+    assert_eq!(report.uncertain_rows[0].reason, UncertainReason::WrappedLine);
+    assert_eq!(report.uncertain_rows[0].text, "J. Smith");
+
+    let log = "timestamp,account,ip\n2024-01-15 10:30:00,ACC1,1.1.1.1\n";
+    let (records, _) = Parser::parse_ip_records_from_bytes(log.as_bytes(), "login.csv", None, None).expect("parse");
+    let mut transactions = transactions;
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    assert_eq!(transactions[0].matched_ip.as_deref(), Some("1.1.1.1"));
+}
+
+#[test]
+fn pdf_statement_streams_like_it_parses() {
+    let bytes = two_page_statement();
+    let (parsed, _) = Parser::parse_transactions_from_bytes(&bytes, "statement.pdf", None, None).expect("parse");
+
+    let mut streamed = Vec::new();
+    let metadata =
+        Parser::stream_transactions_from_bytes(&bytes, "statement.pdf", None, None, 2, |chunk, _| streamed.extend(chunk))
+            .expect("stream");
+    let key = |t: &bankflow_core::Transaction| (t.sheet.clone(), t.row_index, t.memo.clone(), t.expense, t.income);
+    assert_eq!(streamed.iter().map(key).collect::<Vec<_>>(), parsed.iter().map(key).collect::<Vec<_>>());
+    assert_eq!(metadata.header_row, Some(2));
+    assert_eq!(metadata.sheets, ["Page 1", "Page 2"]);
+    assert_eq!(metadata.report.uncertain_rows.len(), 1);
+}
+
+#[test]
+fn pdf_without_header_needs_header_row() {
+    let bytes = statement_pdf(&[page_content(&[("Date", 40, 760), ("Who", 150, 760), ("x", 40, 740)])]);
+    assert!(Parser::get_headers_from_bytes(&bytes, "other.pdf", None).is_err());
+
+    let options = ParseOptions { header_row: Some(1), ..Default::default() };
+    let headers = Parser::get_headers_from_bytes(&bytes, "other.pdf", Some(&options)).expect("headers");
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers[0].name, "Page 1");
+    assert_eq!(headers[0].header_row, 1);
+    assert_eq!(headers[0].headers, ["Date", "Who"]);
+}
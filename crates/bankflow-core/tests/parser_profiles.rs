@@ -0,0 +1,134 @@
+use bankflow_core::parser::Parser;
+use bankflow_core::{AmountMode, CoreError, EncodingSource, FormatProfile, ParseOptions, ProfileKind};
+
+const PROFILE_TOML: &str = r#"
+version = 1
+name = "Sample Bank 2024"
+kind = "transactions"
+amount_mode = "indicator"
+timestamp_format = "ROC/%m/%d %H:%M:%S"
+sensitive_columns = ["身分證號"]
+fingerprint = ["交易序號", "借貸別"]
+
+[columns]
+timestamp = ["交易日期時間"]
+account = ["存款帳號", "帳號"]
+amount = ["交易金額"]
+direction = ["借貸別"]
+"#;
+
+const STATEMENT: &str = "\
+範例銀行 存款交易明細
+交易序號,交易日期時間,存款帳號,身分證號,交易金額,借貸別
+1,113/01/15 10:30:00,ACC1,A123456789,500,借
+2,113/01/15 11:00:00,ACC1,A123456789,800,貸
+";
+
+#[test]
+fn profile_is_detected_from_header_fingerprint() {
+    let profile = FormatProfile::import(PROFILE_TOML).expect("profile");
+    let ip_profile = FormatProfile::new("ISP log", ProfileKind::IpLog);
+    let profiles = vec![ip_profile, profile];
+
+    let detected = Parser::detect_profile(STATEMENT.as_bytes(), &profiles, ProfileKind::Transactions, None)
+        .expect("detect")
+        .expect("a matching profile");
+    assert_eq!(detected.name, "Sample Bank 2024");
+
+    let other = "交易時間,帳號,支出金額,存入金額\n2024-01-15 10:30:00,ACC1,100,\n";
+    let none = Parser::detect_profile(other.as_bytes(), &profiles, ProfileKind::Transactions, None).expect("detect");
+    assert!(none.is_none());
+}
+
+#[test]
+fn profile_supplies_mapping_and_settings() {
+    let options = ParseOptions {
+        profile: Some(FormatProfile::import(PROFILE_TOML).expect("profile")),
+        ..Default::default()
+    };
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "statement.csv", None, Some(&options))
+            .expect("parse");
+
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].expense, Some(500.0));
+    assert_eq!(transactions[1].income, Some(800.0));
+    let dt = transactions[0].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2024-01-15 10:30");
+
+    assert_eq!(metadata.header_row, Some(2));
+    assert_eq!(metadata.profile.as_deref(), Some("Sample Bank 2024"));
+    assert_eq!(metadata.sensitive_columns, vec![3]);
+}
+
+#[test]
+fn profile_of_the_wrong_kind_is_rejected() {
+    let options = ParseOptions {
+        profile: Some(FormatProfile::import(PROFILE_TOML).expect("profile")),
+        ..Default::default()
+    };
+    let result = Parser::parse_ip_records_from_bytes(STATEMENT.as_bytes(), "statement.csv", None, Some(&options));
+    assert!(result.is_err());
+}
+
+#[test]
+fn profile_amount_mode_is_enforced() {
+    let options = ParseOptions {
+        profile: Some(FormatProfile::import(PROFILE_TOML).expect("profile")),
+        ..Default::default()
+    };
+    // Split columns, where the profile expects an amount with 借/貸
+    let split = "交易日期時間,存款帳號,支出金額,存入金額\n113/01/15 10:30:00,ACC1,500,\n";
+    let err = Parser::parse_transactions_from_bytes(split.as_bytes(), "statement.csv", None, Some(&options))
+        .expect_err("layout mismatch");
+    let missing = match err {
+        CoreError::MissingColumns(missing) => missing,
+        other => panic!("unexpected error: {}", other),
+    };
+    let keys: Vec<&str> = missing.iter().map(|m| m.key.as_str()).collect();
+    assert_eq!(keys, ["amount", "direction"]);
+
+    // A 借貸別 column is not read as a direction when the profile says Signed
+    let mut profile = FormatProfile::import(PROFILE_TOML).expect("profile");
+    profile.amount_mode = Some(AmountMode::Signed);
+    profile.columns.remove("direction");
+    let options = ParseOptions { profile: Some(profile), ..Default::default() };
+    let signed = "交易日期時間,存款帳號,交易金額,借貸別\n113/01/15 10:30:00,ACC1,-500,貸\n";
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(signed.as_bytes(), "statement.csv", None, Some(&options)).expect("parse");
+    assert_eq!((transactions[0].expense, transactions[0].income), (Some(500.0), None));
+}
+
+#[test]
+fn headers_are_listed_with_the_profile_encoding_and_header_row() {
+    let profile = r#"
+version = 1
+name = "Big5 return"
+kind = "transactions"
+encoding = "big5"
+header_row = 3
+
+[columns]
+timestamp = ["交易時間"]
+account = ["帳號"]
+expense = ["支出"]
+income = ["存入"]
+"#;
+    // The query line of the title block repeats the column names
+    let text = "存款交易明細\n帳號,交易時間,支出,存入\n交易時間,帳號,支出,存入\n2024-01-15 10:30:00,ACC1,500,\n";
+    let (bytes, _, _) = encoding_rs::BIG5.encode(text);
+    let options = ParseOptions {
+        profile: Some(FormatProfile::import(profile).expect("profile")),
+        ..Default::default()
+    };
+
+    let sheets = Parser::get_headers_from_bytes(&bytes, "return.csv", Some(&options)).expect("headers");
+    assert_eq!(sheets[0].header_row, 3);
+    assert_eq!(sheets[0].headers, ["交易時間", "帳號", "支出", "存入"]);
+
+    let metadata = Parser::get_metadata_from_bytes(&bytes, "return.csv", Some(&options)).expect("metadata");
+    let encoding = metadata.encoding.expect("encoding");
+    assert_eq!((encoding.encoding.as_str(), encoding.source), ("Big5", EncodingSource::Override));
+    assert_eq!(metadata.header_row, Some(3));
+    assert_eq!(metadata.headers, sheets[0].headers);
+}
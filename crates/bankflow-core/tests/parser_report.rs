@@ -0,0 +1,93 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::parser::Parser;
+use bankflow_core::SkipReason;
+use calamine::{open_workbook_auto_from_rs, Reader};
+use std::io::Cursor;
+
+const STATEMENT: &str = "\
+交易時間,帳號,支出金額,存入金額
+2024-01-15 10:30:00,ACC1,100,
+2024-01-15 11:00:00,,200,
+,ACC1,300,
+not a time,ACC1,400,
+2024-01-15 13:00:00,ACC1,abc,
+";
+
+const IP_LOG: &str = "\
+登入時間,帳號,IP位址
+2024-01-15 10:29:00,ACC1,1.2.3.4
+2024-01-15 10:31:00,ACC1,
+";
+
+#[test]
+fn skipped_rows_and_unreadable_cells_are_reported() {
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, None)
+            .expect("parse");
+    let report = &metadata.report;
+
+    assert_eq!(transactions.len(), 3);
+    assert_eq!(report.rows_read, 5);
+
+    let skipped: Vec<(usize, SkipReason)> = report.skipped_rows.iter().map(|s| (s.row, s.reason)).collect();
+    assert_eq!(
+        skipped,
+        vec![(3, SkipReason::MissingAccount), (4, SkipReason::MissingTimestamp)]
+    );
+
+    assert_eq!(report.unparsed_timestamps.len(), 1);
+    assert_eq!(report.unparsed_timestamps[0].row, 5);
+    assert_eq!(report.unparsed_timestamps[0].value, "not a time");
+
+    assert_eq!(report.unparsed_amounts.len(), 1);
+    assert_eq!(report.unparsed_amounts[0].row, 6);
+    assert!(!report.is_clean());
+}
+
+#[test]
+fn ip_rows_without_address_are_reported() {
+    let (records, metadata) =
+        Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None)
+            .expect("parse");
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(metadata.report.rows_read, 2);
+    assert_eq!(metadata.report.skipped_rows.len(), 1);
+    assert_eq!(metadata.report.skipped_rows[0].row, 3);
+    assert_eq!(metadata.report.skipped_rows[0].reason, SkipReason::MissingIpAddress);
+}
+
+#[test]
+fn export_includes_parse_report_sheet_on_request() {
+    let (transactions, file_a) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, None)
+            .expect("parse");
+    let (_, file_b) =
+        Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None)
+            .expect("parse");
+
+    let plain = Exporter::export_to_bytes(&transactions, &[], &[], &[]).expect("export");
+    let workbook = open_workbook_auto_from_rs(Cursor::new(plain)).expect("open workbook");
+    assert!(!workbook.sheet_names().contains(&"Parse Report".to_string()));
+
+    let bytes = Exporter::export_to_bytes_with_report(&transactions, &[], &[], &[], &[file_a, file_b])
+        .expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    let range = workbook.worksheet_range("Parse Report").expect("report sheet");
+
+    let issues: Vec<(String, String)> = range
+        .rows()
+        .skip(1)
+        .map(|row| (row[0].to_string(), row[3].to_string()))
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            ("a.csv".to_string(), "Missing account".to_string()),
+            ("a.csv".to_string(), "Missing timestamp".to_string()),
+            ("a.csv".to_string(), "Unparsed timestamp".to_string()),
+            ("a.csv".to_string(), "Unparsed amount".to_string()),
+            ("b.csv".to_string(), "Missing IP address".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,112 @@
+use bankflow_core::parser::{ParseOptions, Parser, SheetSelector};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+const FILE_A_HEADERS: [&str; 4] = ["交易時間", "帳號", "支出金額", "存入金額"];
+
+/// Workbook with a cover sheet followed by two monthly data sheets
+fn build_monthly_workbook() -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+
+    let cover = workbook.add_worksheet().set_name("說明")?;
+    cover.write_string(0, 0, "對帳單")?;
+
+    for (name, rows) in [
+        ("2024-01", vec![("2024-01-15 10:30:00", "ACC1", 500.0)]),
+        (
+            "2024-02",
+            vec![
+                ("2024-02-01 09:00:00", "ACC1", 200.0),
+                ("2024-02-02 09:00:00", "ACC2", 300.0),
+            ],
+        ),
+    ] {
+        let ws = workbook.add_worksheet().set_name(name)?;
+        for (col, header) in FILE_A_HEADERS.iter().enumerate() {
+            ws.write_string(0, col as u16, *header)?;
+        }
+        for (i, (ts, account, expense)) in rows.into_iter().enumerate() {
+            let row = i as u32 + 1;
+            ws.write_string(row, 0, ts)?;
+            ws.write_string(row, 1, account)?;
+            ws.write_number(row, 2, expense)?;
+        }
+    }
+
+    workbook.save_to_buffer()
+}
+
+#[test]
+fn headers_are_reported_per_sheet() {
+    let bytes = build_monthly_workbook().expect("build workbook");
+    let sheets = Parser::get_headers_from_bytes(&bytes, "a.xlsx", None).expect("headers");
+    let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["說明", "2024-01", "2024-02"]);
+    assert_eq!(sheets[1].index, 1);
+    assert_eq!(sheets[2].headers, FILE_A_HEADERS);
+}
+
+#[test]
+fn first_sheet_is_used_by_default() {
+    let bytes = build_monthly_workbook().expect("build workbook");
+    let err = Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, None).unwrap_err();
+    assert!(err.to_string().contains("Missing required columns"));
+}
+
+#[test]
+fn select_sheet_by_name_or_index() {
+    let bytes = build_monthly_workbook().expect("build workbook");
+
+    let by_name = ParseOptions {
+        sheets: vec![SheetSelector::Name("2024-02".to_string())],
+        ..Default::default()
+    };
+    let (transactions, meta) =
+        Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, Some(&by_name)).expect("parse");
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(meta.sheets, vec!["2024-02"]);
+
+    let by_index = ParseOptions {
+        sheets: vec![SheetSelector::Index(1)],
+        ..Default::default()
+    };
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, Some(&by_index)).expect("parse");
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].sheet.as_deref(), Some("2024-01"));
+
+    let missing = ParseOptions {
+        sheets: vec![SheetSelector::Index(9)],
+        ..Default::default()
+    };
+    assert!(Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, Some(&missing)).is_err());
+}
+
+#[test]
+fn merge_sheets_with_same_layout() {
+    let bytes = build_monthly_workbook().expect("build workbook");
+    let options: ParseOptions =
+        serde_json::from_str(r#"{"sheets": ["2024-01", 2]}"#).expect("options json");
+
+    let (transactions, meta) =
+        Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, Some(&options)).expect("parse");
+    assert_eq!(transactions.len(), 3);
+    assert_eq!(meta.sheets, vec!["2024-01", "2024-02"]);
+    assert_eq!(meta.row_count, 5);
+
+    let sheets: Vec<(&str, usize)> = transactions
+        .iter()
+        .map(|tx| (tx.sheet.as_deref().unwrap_or_default(), tx.row_index))
+        .collect();
+    assert_eq!(sheets, vec![("2024-01", 2), ("2024-02", 2), ("2024-02", 3)]);
+}
+
+#[test]
+fn merge_rejects_different_layouts() {
+    let bytes = build_monthly_workbook().expect("build workbook");
+    let options = ParseOptions {
+        all_sheets: true,
+        ..Default::default()
+    };
+    let err = Parser::parse_transactions_from_bytes(&bytes, "a.xlsx", None, Some(&options)).unwrap_err();
+    assert!(err.to_string().contains("different header layout"));
+}
@@ -0,0 +1,131 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+use bankflow_core::{IpRecord, ParseProgress, Transaction};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+const FILE_B_HEADERS: [&str; 3] = ["登入時間", "帳號", "IP位址"];
+
+/// IP log with a title block above the headers, a blank row and a row
+/// without an IP address
+fn build_ip_log(rows: u32) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    ws.write_string(0, 0, "登入紀錄查詢")?;
+    for (col, header) in FILE_B_HEADERS.iter().enumerate() {
+        ws.write_string(2, col as u16, *header)?;
+    }
+    for i in 0..rows {
+        let row = i + 3;
+        if i == 5 {
+            continue;
+        }
+        ws.write_string(row, 0, format!("2024-01-15 10:{:02}:00", i % 60))?;
+        ws.write_string(row, 1, format!("ACC{}", i % 7))?;
+        if i != 8 {
+            ws.write_string(row, 2, format!("10.0.{}.{}", i / 250, i % 250))?;
+        }
+    }
+    workbook.save_to_buffer()
+}
+
+fn collect_ip_records(
+    bytes: &[u8],
+    options: Option<&ParseOptions>,
+    chunk_size: usize,
+) -> (Vec<IpRecord>, Vec<ParseProgress>, bankflow_core::FileMetadata) {
+    let mut records = Vec::new();
+    let mut progress = Vec::new();
+    let metadata = Parser::stream_ip_records_from_bytes(bytes, "b.xlsx", None, options, chunk_size, |chunk, p| {
+        assert!(chunk.len() <= chunk_size);
+        records.extend(chunk);
+        progress.push(*p);
+    })
+    .expect("stream");
+    (records, progress, metadata)
+}
+
+#[test]
+fn streamed_ip_records_match_loaded_ones() {
+    let bytes = build_ip_log(120).expect("build workbook");
+    let (loaded, loaded_metadata) =
+        Parser::parse_ip_records_from_bytes(&bytes, "b.xlsx", None, None).expect("parse");
+    let (streamed, _, metadata) = collect_ip_records(&bytes, None, 25);
+
+    assert_eq!(streamed.len(), loaded.len());
+    for (a, b) in streamed.iter().zip(&loaded) {
+        assert_eq!(a.row_index, b.row_index);
+        assert_eq!(a.ip_address, b.ip_address);
+        assert_eq!(a.datetime, b.datetime);
+    }
+    assert_eq!(metadata.header_row, Some(3));
+    assert_eq!(metadata.report, loaded_metadata.report);
+    assert_eq!(metadata.row_count, loaded_metadata.row_count);
+    assert_eq!(metadata.column_count, loaded_metadata.column_count);
+}
+
+#[test]
+fn chunks_carry_increasing_progress() {
+    let bytes = build_ip_log(120).expect("build workbook");
+    let (records, progress, _) = collect_ip_records(&bytes, None, 25);
+
+    // 118 records in chunks of 25, then the remainder with the final call
+    assert_eq!(records.len(), 118);
+    assert_eq!(progress.len(), 5);
+    assert!(progress.windows(2).all(|w| w[0].records < w[1].records && w[0].fraction <= w[1].fraction));
+    let last = progress.last().unwrap();
+    assert_eq!((last.records, last.rows_read, last.fraction), (118, 119, 1.0));
+}
+
+#[test]
+fn forced_header_row_and_missing_columns() {
+    let bytes = build_ip_log(10).expect("build workbook");
+    let options = ParseOptions {
+        header_row: Some(3),
+        ..Default::default()
+    };
+    let (records, _, metadata) = collect_ip_records(&bytes, Some(&options), 100);
+    assert_eq!(records.len(), 8);
+    assert_eq!(metadata.header_row, Some(3));
+
+    let options = ParseOptions {
+        header_row: Some(1),
+        ..Default::default()
+    };
+    let err = Parser::stream_ip_records_from_bytes(&bytes, "b.xlsx", None, Some(&options), 100, |_, _| {})
+        .unwrap_err();
+    assert!(err.to_string().contains("Missing required columns"));
+}
+
+#[test]
+fn streamed_csv_transactions_match_loaded_ones() {
+    let statement = "\
+帳戶明細
+\n\
+交易時間,帳號,支出金額,存入金額,摘要
+2024-01-15 10:30:00,ACC1,100,,轉帳
+\n\
+2024-01-15 11:00:00,ACC2,,\"1,200\",薪資
+2024-01-15 12:00:00,,300,,
+2024-01-15 13:00:00,ACC1,abc,,
+";
+    let (loaded, loaded_metadata) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), "a.csv", None, None).expect("parse");
+
+    let mut streamed: Vec<Transaction> = Vec::new();
+    let metadata = Parser::stream_transactions_from_bytes(statement.as_bytes(), "a.csv", None, None, 1, |chunk, _| {
+        streamed.extend(chunk)
+    })
+    .expect("stream");
+
+    assert_eq!(streamed.len(), loaded.len());
+    for (a, b) in streamed.iter().zip(&loaded) {
+        assert_eq!((a.row_index, a.expense, a.income), (b.row_index, b.expense, b.income));
+        assert_eq!(a.raw_columns, b.raw_columns);
+    }
+    assert_eq!(streamed[1].income, Some(1200.0));
+    assert_eq!(metadata.file_type, "csv");
+    assert_eq!(metadata.headers, ["交易時間", "帳號", "支出金額", "存入金額", "摘要"]);
+    assert_eq!(metadata.headers, loaded_metadata.headers);
+    assert_eq!(metadata.header_row, loaded_metadata.header_row);
+    assert_eq!(metadata.report, loaded_metadata.report);
+    assert_eq!(metadata.row_count, loaded_metadata.row_count);
+}
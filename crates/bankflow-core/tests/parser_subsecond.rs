@@ -0,0 +1,56 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::parser::Parser;
+use bankflow_core::IpMatcher;
+use calamine::{open_workbook_auto_from_rs, Reader};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::io::Cursor;
+
+/// Excel serial for 2024-01-15 10:30:00 plus `millis`
+fn serial(millis: f64) -> f64 {
+    45306.0 + (10.5 * 3600.0 + millis / 1000.0) / 86400.0
+}
+
+fn build_ip_log() -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    let format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss.000");
+    for (col, header) in ["登入時間", "帳號", "IP位址"].iter().enumerate() {
+        ws.write_string(0, col as u16, *header)?;
+    }
+    for (i, (millis, ip)) in [(-1200.0, "10.0.0.1"), (250.0, "10.0.0.2"), (2100.0, "10.0.0.3")].iter().enumerate() {
+        let row = i as u32 + 1;
+        ws.write_number_with_format(row, 0, serial(*millis), &format)?;
+        ws.write_string(row, 1, "ACC1")?;
+        ws.write_string(row, 2, *ip)?;
+    }
+    workbook.save_to_buffer()
+}
+
+#[test]
+fn milliseconds_survive_parsing_matching_and_export() {
+    let statement = "\
+交易時間,帳號,支出金額,存入金額
+2024-01-15 10:30:00.100,ACC1,100,
+";
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), "a.csv", None, None).expect("parse");
+    let bytes = build_ip_log().expect("build workbook");
+    let (records, _) = Parser::parse_ip_records_from_bytes(&bytes, "b.xlsx", None, None).expect("parse");
+
+    let stamps: Vec<String> = records.iter().map(|r| r.timestamp.clone()).collect();
+    assert_eq!(stamps, ["2024-01-15 10:29:58.800", "2024-01-15 10:30:00.250", "2024-01-15 10:30:02.100"]);
+
+    // 1.3s before the transaction is outside the default window; truncated
+    // to whole seconds it used to read as -1s and match
+    IpMatcher::with_default_window(&records).match_all(&mut transactions);
+    assert_eq!(
+        transactions[0].matched_ip.as_deref(),
+        Some("+0.150s:10.0.0.2 | +2s:10.0.0.3")
+    );
+
+    let exported = Exporter::export_to_bytes(&transactions, &[], &[], &[]).expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(exported)).expect("open workbook");
+    let sheet = workbook.sheet_names()[0].clone();
+    let range = workbook.worksheet_range(&sheet).expect("sheet");
+    assert!(range.rows().any(|row| row.iter().any(|c| *c == "2024-01-15 10:30:00.100")));
+}
@@ -0,0 +1,86 @@
+use bankflow_core::parser::{ParseOptions, Parser};
+
+const ROC_STATEMENT: &str = "\
+交易時間,帳號,支出金額,存入金額\n\
+113/01/15 下午 02:03:22,ACC1,500,\n\
+1130116,ACC1,,200\n\
+１１３年０１月１７日 上午 09:00:00,ACC1,100,\n";
+
+#[test]
+fn roc_and_meridiem_timestamps_are_parsed() {
+    let (transactions, _) =
+        Parser::parse_transactions_from_bytes(ROC_STATEMENT.as_bytes(), "a.csv", None, None)
+            .expect("parse");
+
+    let parsed: Vec<String> = transactions
+        .iter()
+        .map(|t| t.datetime.expect("datetime").format("%Y-%m-%d %H:%M:%S").to_string())
+        .collect();
+    assert_eq!(
+        parsed,
+        vec!["2024-01-15 14:03:22", "2024-01-16 00:00:00", "2024-01-17 09:00:00"]
+    );
+    // The original text is kept for display
+    assert_eq!(transactions[0].timestamp, "113/01/15 下午 02:03:22");
+}
+
+#[test]
+fn timestamp_format_override_applies_to_ip_records() {
+    let log = "登入時間,帳號,IP位址\n15-01-113 14.03.22,ACC1,1.2.3.4\n";
+    let options = ParseOptions {
+        timestamp_format: Some("%d-%m-ROC %H.%M.%S".to_string()),
+        ..Default::default()
+    };
+
+    let (records, _) =
+        Parser::parse_ip_records_from_bytes(log.as_bytes(), "b.csv", None, None).expect("parse");
+    assert!(records[0].datetime.is_none());
+
+    let (records, _) = Parser::parse_ip_records_from_bytes(log.as_bytes(), "b.csv", None, Some(&options))
+        .expect("parse");
+    let dt = records[0].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-15 14:03:22");
+}
+
+#[test]
+fn invalid_timestamp_format_is_rejected() {
+    let options = ParseOptions {
+        timestamp_format: Some("%Q".to_string()),
+        ..Default::default()
+    };
+    let err = Parser::parse_transactions_from_bytes(ROC_STATEMENT.as_bytes(), "a.csv", None, Some(&options))
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid timestamp format"));
+}
+
+#[test]
+fn years_that_are_neither_roc_nor_gregorian_are_reported() {
+    let statement = "交易時間,帳號,支出金額,存入金額\n\
+0113/01/15,ACC1,500,\n\
+5/01/15,ACC1,,200\n\
+24/01/15 10:00:00,ACC1,,300\n\
+099/01/15,ACC1,100,\n";
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), "a.csv", None, None).expect("parse");
+
+    assert_eq!(transactions.len(), 4);
+    let unparsed: Vec<&str> = metadata.report.unparsed_timestamps.iter().map(|c| c.value.as_str()).collect();
+    assert_eq!(unparsed, ["0113/01/15", "5/01/15", "24/01/15 10:00:00"]);
+    let dt = transactions[3].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d").to_string(), "2010-01-15");
+}
+
+#[test]
+fn two_digit_years_follow_the_format_override() {
+    let statement = "交易時間,帳號,支出金額,存入金額\n24/01/15 10:00:00,ACC1,,300\n";
+    let options = ParseOptions {
+        timestamp_format: Some("%y/%m/%d %H:%M:%S".to_string()),
+        ..Default::default()
+    };
+    let (transactions, metadata) =
+        Parser::parse_transactions_from_bytes(statement.as_bytes(), "a.csv", None, Some(&options)).expect("parse");
+
+    assert!(metadata.report.unparsed_timestamps.is_empty());
+    let dt = transactions[0].datetime.expect("datetime");
+    assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-15 10:00:00");
+}
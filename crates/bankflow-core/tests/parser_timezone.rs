@@ -0,0 +1,119 @@
+use bankflow_core::exporter::Exporter;
+use bankflow_core::{FormatProfile, IpMatcher, IpRecord, ParseOptions, Parser, ProfileKind, Transaction};
+use calamine::{open_workbook_auto_from_rs, Reader};
+use std::io::Cursor;
+
+/// Bank statement written in Taiwan time (UTC+8)
+const STATEMENT: &str = "\
+交易時間,帳號,支出金額,存入金額
+2024-01-15 10:30:00,ACC1,100,
+2024-01-16 00:30:00,ACC1,,200
+";
+
+/// Platform log of the same events, in UTC
+const IP_LOG: &str = "\
+登入時間,帳號,IP位址
+2024-01-15 02:30:01,ACC1,1.2.3.4
+2024-01-15 16:29:59,ACC1,5.6.7.8
+";
+
+fn with_timezone(timezone: &str) -> ParseOptions {
+    ParseOptions {
+        timezone: Some(timezone.to_string()),
+        ..Default::default()
+    }
+}
+
+fn matched(transactions: &mut [Transaction], records: &[IpRecord]) -> Vec<String> {
+    IpMatcher::with_default_window(records).match_all(transactions);
+    transactions.iter().map(|t| t.matched_ip.clone().unwrap_or_default()).collect()
+}
+
+#[test]
+fn declared_zones_line_up_before_matching() {
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, None).expect("parse");
+    let (records, _) = Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None).expect("parse");
+    assert_eq!(matched(&mut transactions, &records), ["N/A", "N/A"]);
+
+    let (mut transactions, file_a) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, Some(&with_timezone("Asia/Taipei")))
+            .expect("parse");
+    let (records, file_b) =
+        Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, Some(&with_timezone("UTC")))
+            .expect("parse");
+    assert_eq!(matched(&mut transactions, &records), ["1.2.3.4", "5.6.7.8"]);
+
+    // The original text is kept; only the comparable time moves
+    assert_eq!(transactions[1].timestamp, "2024-01-16 00:30:00");
+    assert_eq!(file_a.timezone.as_deref(), Some("Asia/Taipei"));
+    assert_eq!(file_b.timezone.as_deref(), Some("UTC"));
+}
+
+#[test]
+fn streamed_records_and_profiles_carry_the_zone() {
+    let mut profile = FormatProfile::new("Platform log", ProfileKind::IpLog);
+    profile.timezone = Some("+00:00".to_string());
+    let options = ParseOptions {
+        profile: Some(profile),
+        ..Default::default()
+    };
+
+    let mut records = Vec::new();
+    let metadata =
+        Parser::stream_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, Some(&options), 10, |chunk, _| {
+            records.extend(chunk)
+        })
+        .expect("stream");
+    assert_eq!(metadata.timezone.as_deref(), Some("UTC"));
+
+    let (mut transactions, _) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, Some(&with_timezone("UTC+8")))
+            .expect("parse");
+    assert_eq!(matched(&mut transactions, &records), ["1.2.3.4", "5.6.7.8"]);
+}
+
+#[test]
+fn unknown_zone_is_rejected() {
+    let err = Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, Some(&with_timezone("Taipei")))
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown time zone: Taipei"));
+}
+
+#[test]
+fn export_records_the_declared_zone() {
+    let (transactions, file_a) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, Some(&with_timezone("Asia/Taipei")))
+            .expect("parse");
+    let (_, file_b) = Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None).expect("parse");
+
+    let files = [file_a, file_b];
+    let bytes = Exporter::export_to_bytes_with_report(&transactions, &[], &[], &[], &files).expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    let range = workbook.worksheet_range("Parse Report").expect("report sheet");
+
+    let zones: Vec<(String, String)> = range
+        .rows()
+        .filter(|row| row[3] == "Time zone")
+        .map(|row| (row[0].to_string(), row[5].to_string()))
+        .collect();
+    assert_eq!(zones, [("a.csv".to_string(), "Asia/Taipei".to_string())]);
+}
+
+#[test]
+fn export_without_report_names_the_zone_in_the_timestamp_header() {
+    let (transactions, file_a) =
+        Parser::parse_transactions_from_bytes(STATEMENT.as_bytes(), "a.csv", None, Some(&with_timezone("Asia/Taipei")))
+            .expect("parse");
+    let (_, file_b) = Parser::parse_ip_records_from_bytes(IP_LOG.as_bytes(), "b.csv", None, None).expect("parse");
+
+    let bytes = Exporter::export_to_bytes_with_files(&transactions, &[], &[], &[], &[file_a, file_b], false)
+        .expect("export");
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes)).expect("open workbook");
+    assert!(workbook.worksheet_range("Parse Report").is_err());
+    let summary = workbook.worksheet_range("Summary").expect("summary sheet");
+    assert_eq!(
+        summary.get((0, 0)).map(ToString::to_string).as_deref(),
+        Some("Timestamp (as written; a.csv Asia/Taipei, others undeclared; compared in UTC)")
+    );
+}
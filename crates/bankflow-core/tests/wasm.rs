@@ -52,6 +52,7 @@ fn test_file_metadata_serialization() {
         row_count: 100,
         column_count: 10,
         file_type: "xlsx".to_string(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&meta).unwrap();
@@ -2,9 +2,9 @@
 //!
 //! Orchestrates the IP correlation analysis workflow.
 
-use bankflow_core::{IpMatcher, Processor};
+use bankflow_core::{AccountNormalizer, IpMatcher, Processor, TimeWindow};
 use crate::core::whois::WhoisClient;
-use crate::models::AnalysisResult;
+use crate::models::{AnalysisResult, FileMetadata};
 use crate::state::AppState;
 use tauri::{AppHandle, Emitter, State};
 
@@ -25,6 +25,7 @@ pub async fn run_analysis(
     split_income_expense: bool,
     _ip_cross_reference: bool, // Reserved for future use
     whois_lookup: bool,
+    account_normalizer: Option<AccountNormalizer>,
 ) -> Result<AnalysisResult, String> {
     // Check if files are loaded
     let transactions = state.transactions.read().await;
@@ -39,8 +40,18 @@ pub async fn run_analysis(
     }
 
     // Clone data for processing
-    let mut tx_data = transactions.clone();
-    let ip_data = ip_records.clone();
+    let mut tx_data = transactions.records.clone();
+    let ip_data = ip_records.records.clone();
+
+    // Appended statements share one layout, so the first file's headers
+    // apply to every row; a column any file's profile marks is masked in all
+    let mut raw_headers = transactions.files.first().map(|m| m.headers.clone()).unwrap_or_default();
+    let mut profile_columns: Vec<usize> =
+        transactions.files.iter().flat_map(|m| m.sensitive_columns.iter().copied()).collect();
+    profile_columns.sort_unstable();
+    profile_columns.dedup();
+    let file_a_timezone = declared_timezones(&transactions.files);
+    let file_b_timezone = declared_timezones(&ip_records.files);
 
     // Release locks
     drop(transactions);
@@ -51,7 +62,11 @@ pub async fn run_analysis(
     // Stage 1: IP Matching
     emit_progress(&app, "matching", 0, "Starting IP matching...");
 
-    let matcher = IpMatcher::with_default_window(&ip_data);
+    let matcher = IpMatcher::with_normalizer(
+        &ip_data,
+        TimeWindow::default(),
+        account_normalizer.clone().unwrap_or_default(),
+    );
     matcher.match_all(&mut tx_data);
 
     let stats = matcher.get_stats(&tx_data);
@@ -60,8 +75,13 @@ pub async fn run_analysis(
     // Stage 2: Sensitive Column Processing
     if hide_sensitive {
         emit_progress(&app, "processing", 0, "Hiding sensitive columns...");
-        let processor = Processor::new(true);
-        processor.process(&mut tx_data);
+        // A format profile's sensitive columns replace the default set
+        let processor = if profile_columns.is_empty() {
+            Processor::new(true)
+        } else {
+            Processor::with_sensitive_columns(true, profile_columns)
+        };
+        processor.process_with_headers(&mut tx_data, &mut raw_headers);
         emit_progress(&app, "processing", 100, "Sensitive columns hidden");
     }
 
@@ -105,6 +125,7 @@ pub async fn run_analysis(
         results.summary = tx_data.clone();
         results.income = income.clone();
         results.expense = expense.clone();
+        results.raw_headers = raw_headers;
         results.is_complete = true;
         results.total_records = total_records;
         results.matched_count = stats.matched;
@@ -124,10 +145,24 @@ pub async fn run_analysis(
             split_income_expense,
             ip_cross_reference: _ip_cross_reference,
             whois_lookup,
+            account_normalizer,
         },
+        file_a_timezone,
+        file_b_timezone,
     })
 }
 
+/// Distinct declared time zones of the loaded files, e.g. "Asia/Taipei, UTC"
+fn declared_timezones(files: &[FileMetadata]) -> Option<String> {
+    let mut zones: Vec<&str> = Vec::new();
+    for zone in files.iter().filter_map(|m| m.timezone.as_deref()) {
+        if !zones.contains(&zone) {
+            zones.push(zone);
+        }
+    }
+    (!zones.is_empty()).then(|| zones.join(", "))
+}
+
 /// Emit progress event to frontend
 fn emit_progress(app: &AppHandle, stage: &str, progress: u32, message: &str) {
     let _ = app.emit(
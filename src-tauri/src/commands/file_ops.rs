@@ -1,13 +1,14 @@
 //! File operations commands
 //!
-//! Handles loading Excel files and exporting analysis results.
+//! Handles loading Excel/CSV files and exporting analysis results.
 
-use bankflow_core::{Exporter, Parser};
+use bankflow_core::{Exporter, MissingColumn, ParseOptions, Parser, ProfileKind, SheetHeaders};
+use crate::commands::profiles::with_detected_profile;
 use crate::models::FileMetadata;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
 
 /// Status of loaded files
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,37 +17,62 @@ pub struct LoadStatus {
     pub file_b_loaded: bool,
     pub file_a_records: usize,
     pub file_b_records: usize,
+    /// Most recently loaded File A / File B
     pub file_a_name: Option<String>,
     pub file_b_name: Option<String>,
+    /// Every loaded File A / File B, in load order
+    pub file_a_names: Vec<String>,
+    pub file_b_names: Vec<String>,
 }
 
 /// Load and parse File A (Transaction file)
 use std::collections::HashMap;
 
-/// Load and parse File A (Transaction file)
+/// Load and parse File A (Transaction file).
+///
+/// Without a profile in `options`, the saved profile matching the file's
+/// headers (if any) is applied. With `append`, the statement is merged with
+/// those already loaded: rows another statement already supplied are dropped
+/// and listed in the returned parse report. Otherwise it replaces them.
+/// `options.password` opens a password-encrypted workbook, which is
+/// decrypted in memory only.
 #[tauri::command]
-pub async fn load_file(path: String, mapping: Option<HashMap<String, String>>, state: State<'_, AppState>) -> Result<FileMetadata, String> {
+pub async fn load_file(
+    app: AppHandle,
+    path: String,
+    mapping: Option<HashMap<String, String>>,
+    options: Option<ParseOptions>,
+    append: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<FileMetadata, String> {
     let path_buf = PathBuf::from(&path);
 
     if !path_buf.exists() {
         return Err(format!("File not found: {}", path_buf.display()));
     }
 
-    // Get file metadata
-    let (row_count, column_count) = Parser::get_file_metadata(&path_buf)
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-
-    // Parse transactions
-    let transactions = Parser::parse_transactions(&path_buf, mapping.as_ref())
-        .map_err(|e| format!("Failed to parse transactions: {}", e))?;
+    let data = std::fs::read(&path_buf)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let filename = file_name_of(&path_buf);
+    let options = with_detected_profile(&app, &data, ProfileKind::Transactions, options)?;
 
-    let _record_count = transactions.len();
+    // Parse transactions (workbook or CSV/TSV)
+    let (transactions, mut metadata) =
+        Parser::parse_transactions_from_bytes(&data, &filename, mapping.as_ref(), options.as_ref())
+            .map_err(|e| format!("Failed to parse transactions: {}", e))?;
+    metadata.path = Some(path_buf.to_string_lossy().to_string());
 
     // Store in state
-    {
+    let metadata = {
         let mut tx_store = state.transactions.write().await;
-        *tx_store = transactions;
-    }
+        if !append.unwrap_or(false) {
+            tx_store.clear();
+        }
+        tx_store
+            .append(transactions, metadata)
+            .map_err(|e| format!("Failed to add statement: {}", e))?;
+        tx_store.files.last().cloned().unwrap_or_default()
+    };
 
     // Clear previous analysis results since data changed
     {
@@ -54,43 +80,62 @@ pub async fn load_file(path: String, mapping: Option<HashMap<String, String>>, s
         results.is_complete = false;
     }
 
-    Ok(FileMetadata {
-        path: Some(path_buf.to_string_lossy().to_string()),
-        filename: path_buf
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default(),
-        row_count,
-        column_count,
-        file_type: path_buf
-            .extension()
-            .map(|e| e.to_string_lossy().to_string())
-            .unwrap_or_else(|| "xlsx".to_string()),
-    })
+    Ok(metadata)
 }
 
-/// Load and parse File B (IP log file)
+/// IP records per chunk when streaming File B, i.e. per progress event
+const IP_LOG_CHUNK_ROWS: usize = 50_000;
+
+/// Load and parse File B (IP log file), applying a matching saved profile
+/// and merging with `append` as for `load_file`. Progress is emitted as
+/// `ip-load-progress` events.
 #[tauri::command]
-pub async fn load_ip_file(path: String, mapping: Option<HashMap<String, String>>, state: State<'_, AppState>) -> Result<FileMetadata, String> {
+pub async fn load_ip_file(
+    app: AppHandle,
+    path: String,
+    mapping: Option<HashMap<String, String>>,
+    options: Option<ParseOptions>,
+    append: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<FileMetadata, String> {
     let path_buf = PathBuf::from(&path);
 
     if !path_buf.exists() {
         return Err(format!("File not found: {}", path_buf.display()));
     }
 
-    // Get file metadata
-    let (row_count, column_count) = Parser::get_file_metadata(&path_buf)
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-
-    // Parse IP records
-    let ip_records = Parser::parse_ip_records(&path_buf, mapping.as_ref())
-        .map_err(|e| format!("Failed to parse IP records: {}", e))?;
+    let data = std::fs::read(&path_buf)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let filename = file_name_of(&path_buf);
+    let options = with_detected_profile(&app, &data, ProfileKind::IpLog, options)?;
+
+    // Stream IP records (workbook or CSV/TSV); large logs report progress per chunk
+    let mut ip_records = Vec::new();
+    let mut metadata = Parser::stream_ip_records_from_bytes(
+        &data,
+        &filename,
+        mapping.as_ref(),
+        options.as_ref(),
+        IP_LOG_CHUNK_ROWS,
+        |chunk, progress| {
+            ip_records.extend(chunk);
+            let _ = app.emit("ip-load-progress", progress);
+        },
+    )
+    .map_err(|e| format!("Failed to parse IP records: {}", e))?;
+    metadata.path = Some(path_buf.to_string_lossy().to_string());
 
     // Store in state
-    {
+    let metadata = {
         let mut ip_store = state.ip_records.write().await;
-        *ip_store = ip_records;
-    }
+        if !append.unwrap_or(false) {
+            ip_store.clear();
+        }
+        ip_store
+            .append(ip_records, metadata)
+            .map_err(|e| format!("Failed to add IP log: {}", e))?;
+        ip_store.files.last().cloned().unwrap_or_default()
+    };
 
     // Clear previous analysis results since data changed
     {
@@ -98,19 +143,14 @@ pub async fn load_ip_file(path: String, mapping: Option<HashMap<String, String>>
         results.is_complete = false;
     }
 
-    Ok(FileMetadata {
-        path: Some(path_buf.to_string_lossy().to_string()),
-        filename: path_buf
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default(),
-        row_count,
-        column_count,
-        file_type: path_buf
-            .extension()
-            .map(|e| e.to_string_lossy().to_string())
-            .unwrap_or_else(|| "xlsx".to_string()),
-    })
+    Ok(metadata)
+}
+
+/// File name component of a path, used as the display name in metadata
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
 
 /// Get current load status
@@ -118,17 +158,33 @@ pub async fn load_ip_file(path: String, mapping: Option<HashMap<String, String>>
 pub async fn get_load_status(state: State<'_, AppState>) -> Result<LoadStatus, String> {
     let transactions = state.transactions.read().await;
     let ip_records = state.ip_records.read().await;
+    let names = |files: &[FileMetadata]| files.iter().map(|m| m.filename.clone()).collect::<Vec<_>>();
 
     Ok(LoadStatus {
         file_a_loaded: !transactions.is_empty(),
         file_b_loaded: !ip_records.is_empty(),
         file_a_records: transactions.len(),
         file_b_records: ip_records.len(),
-        file_a_name: None, // Could store filename in state if needed
-        file_b_name: None,
+        file_a_name: transactions.files.last().map(|m| m.filename.clone()),
+        file_b_name: ip_records.files.last().map(|m| m.filename.clone()),
+        file_a_names: names(&transactions.files),
+        file_b_names: names(&ip_records.files),
     })
 }
 
+/// Get the parse reports of the loaded files (File A statements first)
+#[tauri::command]
+pub async fn get_parse_reports(state: State<'_, AppState>) -> Result<Vec<FileMetadata>, String> {
+    Ok(loaded_file_metadata(&state).await)
+}
+
+/// Metadata of every loaded File A, then every loaded File B
+async fn loaded_file_metadata(state: &AppState) -> Vec<FileMetadata> {
+    let file_a = state.transactions.read().await;
+    let file_b = state.ip_records.read().await;
+    file_a.files.iter().chain(file_b.files.iter()).cloned().collect()
+}
+
 /// Clear all loaded files and reset state
 #[tauri::command]
 pub async fn clear_files(state: State<'_, AppState>) -> Result<(), String> {
@@ -137,8 +193,16 @@ pub async fn clear_files(state: State<'_, AppState>) -> Result<(), String> {
 }
 
 /// Export analysis results to Excel file
+///
+/// The timestamp header always names the time zone each loaded file
+/// declared. With `include_parse_report`, a "Parse Report" sheet lists the
+/// rows and cells that could not be read from the loaded files.
 #[tauri::command]
-pub async fn export_excel(output_path: String, state: State<'_, AppState>) -> Result<String, String> {
+pub async fn export_excel(
+    output_path: String,
+    include_parse_report: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     let results = state.results.read().await;
 
     if !results.is_complete {
@@ -146,8 +210,17 @@ pub async fn export_excel(output_path: String, state: State<'_, AppState>) -> Re
     }
 
     let path_buf = PathBuf::from(&output_path);
-
-    Exporter::export_to_excel(&path_buf, &results.summary, &results.income, &results.expense)
+    let files = loaded_file_metadata(&state).await;
+
+    Exporter::export_to_excel_with_files(
+        &path_buf,
+        &results.summary,
+        &results.income,
+        &results.expense,
+        &results.raw_headers,
+        &files,
+        include_parse_report.unwrap_or(false),
+    )
         .map_err(|e| format!("Failed to export Excel: {}", e))?;
 
     Ok(format!(
@@ -159,16 +232,30 @@ pub async fn export_excel(output_path: String, state: State<'_, AppState>) -> Re
 
 use bankflow_core::batch::{BatchProcessor, BatchScanResult};
 
-/// Get headers from an Excel file
+/// Get the header row of every sheet in a file
 #[tauri::command]
-pub async fn get_file_headers(path: String) -> Result<Vec<String>, String> {
+pub async fn get_file_headers(path: String, options: Option<ParseOptions>) -> Result<Vec<SheetHeaders>, String> {
     let path_buf = PathBuf::from(&path);
 
     if !path_buf.exists() {
         return Err(format!("File not found: {}", path_buf.display()));
     }
 
-    Parser::get_file_headers(&path_buf)
+    Parser::get_file_headers(&path_buf, options.as_ref())
+        .map_err(|e| format!("Failed to read headers: {}", e))
+}
+
+/// List the required columns a file is missing, with suggested headers for
+/// each, so the repair dialog can pre-fill the mapping
+#[tauri::command]
+pub async fn suggest_columns(
+    path: String,
+    kind: ProfileKind,
+    mapping: Option<HashMap<String, String>>,
+    options: Option<ParseOptions>,
+) -> Result<Vec<MissingColumn>, String> {
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    Parser::missing_columns_from_bytes(&data, kind, mapping.as_ref(), options.as_ref())
         .map_err(|e| format!("Failed to read headers: {}", e))
 }
 
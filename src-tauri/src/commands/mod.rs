@@ -2,5 +2,6 @@
 
 pub mod analysis;
 pub mod file_ops;
+pub mod profiles;
 pub mod whois;
 pub mod system;
\ No newline at end of file
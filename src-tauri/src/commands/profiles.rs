@@ -0,0 +1,156 @@
+//! Format profile commands
+//!
+//! Stores bank/ISP format profiles in the app data directory so they persist
+//! between sessions, and imports/exports them as JSON or TOML for sharing.
+
+use bankflow_core::{FormatProfile, ParseOptions, Parser, ProfileFormat, ProfileKind};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Directory holding the saved profiles, created on first use
+fn profiles_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to locate app data directory: {}", e))?
+        .join("profiles");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Saved profiles with their file paths. Files that fail to load are skipped
+/// so one broken profile does not hide the others.
+fn stored_profiles(app: &AppHandle) -> Result<Vec<(PathBuf, FormatProfile)>, String> {
+    let dir = profiles_dir(app)?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read profile directory: {}", e))?;
+
+    let mut profiles: Vec<(PathBuf, FormatProfile)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| ProfileFormat::from_filename(&path.to_string_lossy()).is_some())
+        .filter_map(|path| FormatProfile::load(&path).ok().map(|profile| (path, profile)))
+        .collect();
+    profiles.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    Ok(profiles)
+}
+
+/// File name for a profile: the letters and digits of its name, plus a
+/// hash of the exact name so names differing only in punctuation ("Bank A",
+/// "Bank/A") do not share a file
+fn profile_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}-{:08x}.json", stem, name_hash(name))
+}
+
+/// 32-bit FNV-1a hash, stable across builds unlike `DefaultHasher`
+fn name_hash(name: &str) -> u32 {
+    name.bytes().fold(0x811c_9dc5, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193))
+}
+
+/// Save a profile, replacing any saved profile with the same name
+fn store_profile(app: &AppHandle, profile: &FormatProfile) -> Result<(), String> {
+    profile.validate().map_err(|e| e.to_string())?;
+    for (path, existing) in stored_profiles(app)? {
+        if existing.name == profile.name {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to replace profile: {}", e))?;
+        }
+    }
+    let path = profiles_dir(app)?.join(profile_file_name(&profile.name));
+    if let Ok(other) = FormatProfile::load(&path) {
+        if other.name != profile.name {
+            return Err(format!("Profile file {} already holds profile '{}'", path.display(), other.name));
+        }
+    }
+    profile.save(&path).map_err(|e| format!("Failed to save profile: {}", e))
+}
+
+/// Fill in `options.profile` with the saved profile matching the file's
+/// headers, unless the caller already chose one
+pub(crate) fn with_detected_profile(
+    app: &AppHandle,
+    data: &[u8],
+    kind: ProfileKind,
+    options: Option<ParseOptions>,
+) -> Result<Option<ParseOptions>, String> {
+    if options.as_ref().is_some_and(|o| o.profile.is_some()) {
+        return Ok(options);
+    }
+    let profiles: Vec<FormatProfile> = stored_profiles(app)?.into_iter().map(|(_, p)| p).collect();
+    if profiles.is_empty() {
+        return Ok(options);
+    }
+
+    let detected = Parser::detect_profile(data, &profiles, kind, options.as_ref())
+        .map_err(|e| format!("Failed to detect format profile: {}", e))?;
+    Ok(match detected {
+        Some(profile) => {
+            let mut options = options.unwrap_or_default();
+            options.profile = Some(profile.clone());
+            Some(options)
+        }
+        None => options,
+    })
+}
+
+/// List the saved format profiles
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<FormatProfile>, String> {
+    Ok(stored_profiles(&app)?.into_iter().map(|(_, p)| p).collect())
+}
+
+/// Save a format profile (e.g. one built from the current column mapping)
+#[tauri::command]
+pub async fn save_profile(app: AppHandle, profile: FormatProfile) -> Result<(), String> {
+    store_profile(&app, &profile)
+}
+
+/// Import a shared JSON or TOML profile file and save it
+#[tauri::command]
+pub async fn import_profile(app: AppHandle, path: String) -> Result<FormatProfile, String> {
+    let profile = FormatProfile::load(Path::new(&path))
+        .map_err(|e| format!("Failed to import profile: {}", e))?;
+    store_profile(&app, &profile)?;
+    Ok(profile)
+}
+
+/// Export a saved profile; the output format follows the file extension
+#[tauri::command]
+pub async fn export_profile(app: AppHandle, name: String, path: String) -> Result<(), String> {
+    let (_, profile) = stored_profiles(&app)?
+        .into_iter()
+        .find(|(_, p)| p.name == name)
+        .ok_or_else(|| format!("Profile not found: {}", name))?;
+    profile
+        .save(Path::new(&path))
+        .map_err(|e| format!("Failed to export profile: {}", e))
+}
+
+/// Delete a saved profile
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    for (path, profile) in stored_profiles(&app)? {
+        if profile.name == name {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to delete profile: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_names_get_distinct_files() {
+        let names = ["Bank A", "Bank_A", "Bank/A"];
+        let files: Vec<String> = names.iter().map(|n| profile_file_name(n)).collect();
+        assert!(files.iter().all(|f| f.starts_with("Bank_A-") && f.ends_with(".json")));
+        assert_ne!(files[0], files[1]);
+        assert_ne!(files[1], files[2]);
+        assert_ne!(files[0], files[2]);
+        assert_eq!(profile_file_name("Bank A"), files[0]);
+    }
+}
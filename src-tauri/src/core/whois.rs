@@ -6,6 +6,8 @@
 //! **OpSec Warning**: This module makes external network requests.
 
 use crate::models::{Transaction, WhoisResult};
+use bankflow_core::ip::{is_non_public, parse_ip};
+use bankflow_core::matched_ips;
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -135,17 +137,10 @@ impl WhoisClient {
 
         for tx in transactions.iter() {
             if let Some(ref matched) = tx.matched_ip {
-                if matched != "N/A" && !matched.starts_with("N/A") {
-                    // Extract IPs from formatted string like "-1s:1.1.1.1 | +2s:2.2.2.2"
-                    for part in matched.split(" | ") {
-                        let ip = if part.contains(':') {
-                            part.split(':').last().unwrap_or(part)
-                        } else {
-                            part
-                        };
-                        if !unique_ips.contains(&ip.to_string()) {
-                            unique_ips.push(ip.to_string());
-                        }
+                // Extract IPs from formatted string like "-1s:1.1.1.1 | +2s:2001:db8::1"
+                for ip in matched_ips(matched) {
+                    if !unique_ips.iter().any(|known| known == ip) {
+                        unique_ips.push(ip.to_string());
                     }
                 }
             }
@@ -160,18 +155,10 @@ impl WhoisClient {
         // Enrich transactions
         for tx in transactions.iter_mut() {
             if let Some(ref matched) = tx.matched_ip {
-                if matched != "N/A" && !matched.starts_with("N/A") {
-                    // Get first IP for country/ISP info
-                    let first_ip = if matched.contains(':') {
-                        matched.split(':').last().unwrap_or(matched).split(" | ").next().unwrap_or(matched)
-                    } else {
-                        matched.split(" | ").next().unwrap_or(matched)
-                    };
-
-                    if let Some(info) = ip_info.get(first_ip) {
-                        tx.ip_country = info.country.clone();
-                        tx.ip_isp = info.isp.clone();
-                    }
+                // Get first IP for country/ISP info
+                if let Some(info) = matched_ips(matched).first().and_then(|ip| ip_info.get(*ip)) {
+                    tx.ip_country = info.country.clone();
+                    tx.ip_isp = info.isp.clone();
                 }
             }
         }
@@ -193,42 +180,8 @@ impl Default for WhoisClient {
 
 /// Check if an IP address is private/local
 fn is_private_ip(ip: &str) -> bool {
-    // Parse IP and check ranges
-    let parts: Vec<u8> = ip
-        .split('.')
-        .filter_map(|p| p.parse().ok())
-        .collect();
-
-    if parts.len() != 4 {
-        return false;
-    }
-
-    // 10.0.0.0/8
-    if parts[0] == 10 {
-        return true;
-    }
-
-    // 172.16.0.0/12
-    if parts[0] == 172 && (16..=31).contains(&parts[1]) {
-        return true;
-    }
-
-    // 192.168.0.0/16
-    if parts[0] == 192 && parts[1] == 168 {
-        return true;
-    }
-
-    // 127.0.0.0/8 (loopback)
-    if parts[0] == 127 {
-        return true;
-    }
-
-    // 169.254.0.0/16 (link-local)
-    if parts[0] == 169 && parts[1] == 254 {
-        return true;
-    }
-
-    false
+    // Loopback, private and link-local ranges of IPv4 and IPv6
+    parse_ip(ip).is_some_and(|ip| is_non_public(&ip))
 }
 
 #[cfg(test)]
@@ -265,6 +218,12 @@ mod tests {
         assert!(!is_private_ip("1.1.1.1"));
         assert!(!is_private_ip("203.0.113.1"));
 
+        // IPv6 loopback, unique-local and link-local
+        assert!(is_private_ip("::1"));
+        assert!(is_private_ip("fd12:3456::1"));
+        assert!(is_private_ip("fe80::1%eth0"));
+        assert!(!is_private_ip("2001:4860:4860::8888"));
+
         // Invalid IPs
         assert!(!is_private_ip("invalid"));
         assert!(!is_private_ip("256.1.1.1"));
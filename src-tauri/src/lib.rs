@@ -31,11 +31,18 @@ pub fn run() {
             commands::file_ops::load_file,
             commands::file_ops::load_ip_file,
             commands::file_ops::get_load_status,
+            commands::file_ops::get_parse_reports,
             commands::file_ops::clear_files,
             commands::analysis::run_analysis,
             commands::file_ops::export_excel,
             commands::file_ops::get_file_headers,
+            commands::file_ops::suggest_columns,
             commands::file_ops::scan_folder,
+            commands::profiles::list_profiles,
+            commands::profiles::save_profile,
+            commands::profiles::import_profile,
+            commands::profiles::export_profile,
+            commands::profiles::delete_profile,
             commands::whois::query_whois,
             commands::whois::query_whois_batch,
             commands::system::check_update,
@@ -4,8 +4,8 @@
 
 // Re-export core types from bankflow-core
 pub use bankflow_core::{
-    excel_date_to_datetime, AnalysisResult, AnalysisSettings, FileMetadata, IpRecord, Transaction,
-    WhoisResult,
+    excel_date_to_datetime, AnalysisResult, AnalysisSettings, Dataset, FileMetadata, IpRecord,
+    Transaction, WhoisResult,
 };
 
 // Note: bankflow-core uses NaiveDateTime for WASM compatibility.
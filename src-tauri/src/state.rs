@@ -2,17 +2,18 @@
 //!
 //! Manages loaded files and analysis results across Tauri commands.
 
-use crate::models::{IpRecord, Transaction};
+use crate::models::{Dataset, IpRecord, Transaction};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Application state holding loaded data and analysis results
 #[derive(Default)]
 pub struct AppState {
-    /// Loaded transactions from File A
-    pub transactions: Arc<RwLock<Vec<Transaction>>>,
-    /// Loaded IP records from File B
-    pub ip_records: Arc<RwLock<Vec<IpRecord>>>,
+    /// Transactions merged from the loaded File A statements, with the
+    /// metadata and parse report of each file
+    pub transactions: Arc<RwLock<Dataset<Transaction>>>,
+    /// IP records merged from the loaded File B logs
+    pub ip_records: Arc<RwLock<Dataset<IpRecord>>>,
     /// Processed results ready for export
     pub results: Arc<RwLock<AnalysisState>>,
 }
@@ -26,6 +27,8 @@ pub struct AnalysisState {
     pub income: Vec<Transaction>,
     /// Expense transactions only
     pub expense: Vec<Transaction>,
+    /// File A headers naming the raw columns, minus any hidden columns
+    pub raw_headers: Vec<String>,
     /// Whether analysis has been run
     pub is_complete: bool,
     /// Total records processed
@@ -113,6 +116,7 @@ mod tests {
             summary: vec![],
             income: vec![],
             expense: vec![],
+            raw_headers: vec![],
             is_complete: true,
             total_records: 100,
             matched_count: 80,
@@ -139,17 +143,25 @@ mod tests {
         // Add a transaction
         {
             let mut transactions = state.transactions.write().await;
-            transactions.push(Transaction {
+            transactions.records.push(Transaction {
                 datetime: None,
                 timestamp: "2024-01-15 10:30:00".to_string(),
                 account: "ACC001".to_string(),
                 income: Some(1000.0),
                 expense: None,
+                counterparty_account: None,
+                counterparty_name: None,
+                balance: None,
+                currency: None,
+                memo: None,
                 matched_ip: None,
+                matched_login: Default::default(),
                 ip_country: None,
                 ip_isp: None,
                 raw_columns: vec![],
                 row_index: 1,
+                sheet: None,
+                source_file: None,
             });
         }
 
@@ -164,7 +176,7 @@ mod tests {
         // Add an IP record
         {
             let mut ip_records = state.ip_records.write().await;
-            ip_records.push(IpRecord::new(
+            ip_records.records.push(IpRecord::new(
                 "2024-01-15 10:30:00".to_string(),
                 "ACC001".to_string(),
                 "192.168.1.1".to_string(),
@@ -196,22 +208,30 @@ mod tests {
         // Add data
         {
             let mut transactions = state.transactions.write().await;
-            transactions.push(Transaction {
+            transactions.records.push(Transaction {
                 datetime: None,
                 timestamp: "2024-01-15 10:30:00".to_string(),
                 account: "ACC001".to_string(),
                 income: Some(1000.0),
                 expense: None,
+                counterparty_account: None,
+                counterparty_name: None,
+                balance: None,
+                currency: None,
+                memo: None,
                 matched_ip: None,
+                matched_login: Default::default(),
                 ip_country: None,
                 ip_isp: None,
                 raw_columns: vec![],
                 row_index: 1,
+                sheet: None,
+                source_file: None,
             });
         }
         {
             let mut ip_records = state.ip_records.write().await;
-            ip_records.push(IpRecord::new(
+            ip_records.records.push(IpRecord::new(
                 "2024-01-15 10:30:00".to_string(),
                 "ACC001".to_string(),
                 "192.168.1.1".to_string(),
@@ -32,6 +32,8 @@ const en: Translations = {
     splitIncomeExpense: 'Split Income/Expense',
     ipCrossReference: 'IP Cross-Reference',
     whoisLookup: 'Whois Lookup (OpSec Risk)',
+    fileATimezone: 'Time zone of File A (e.g. Asia/Taipei)',
+    fileBTimezone: 'Time zone of File B (e.g. UTC)',
     analyzing: 'Analyzing...',
     executeAnalysis: 'Execute Analysis',
     exportReport: 'Export Report',
@@ -74,6 +76,15 @@ const en: Translations = {
     remindLater: 'Remind Me Later',
     skipVersion: 'Skip This Version',
   },
+  passwordDialog: {
+    title: 'Encrypted File',
+    prompt: 'This workbook is password-protected. Enter its password to open it.',
+    placeholder: 'Password',
+    wrongPassword: 'Wrong password, please try again.',
+    note: 'The file is decrypted in memory only; the password is not saved.',
+    open: 'Open',
+    cancel: 'Cancel',
+  },
   downloadsDialog: {
     open: 'Downloads',
     title: 'Download Desktop App',
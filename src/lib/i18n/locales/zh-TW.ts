@@ -32,6 +32,8 @@ const zhTW: Translations = {
     splitIncomeExpense: '分離收支',
     ipCrossReference: 'IP 交叉比對',
     whoisLookup: 'Whois 查詢（操作安全風險）',
+    fileATimezone: '檔案 A 時區（如 Asia/Taipei）',
+    fileBTimezone: '檔案 B 時區（如 UTC）',
     analyzing: '分析中...',
     executeAnalysis: '執行分析',
     exportReport: '匯出報告',
@@ -74,6 +76,15 @@ const zhTW: Translations = {
     remindLater: '稍後提醒',
     skipVersion: '略過此版本',
   },
+  passwordDialog: {
+    title: '加密檔案',
+    prompt: '此活頁簿受密碼保護，請輸入密碼以開啟。',
+    placeholder: '密碼',
+    wrongPassword: '密碼錯誤，請再試一次。',
+    note: '檔案僅在記憶體中解密，密碼不會被儲存。',
+    open: '開啟',
+    cancel: '取消',
+  },
   downloadsDialog: {
     open: '下載',
     title: '下載桌面版',
@@ -42,6 +42,8 @@ export interface Translations {
     splitIncomeExpense: string;
     ipCrossReference: string;
     whoisLookup: string;
+    fileATimezone: string;
+    fileBTimezone: string;
     analyzing: string;
     executeAnalysis: string;
     exportReport: string;
@@ -95,6 +97,17 @@ export interface Translations {
     skipVersion: string;
   };
 
+  // PasswordDialog component
+  passwordDialog: {
+    title: string;
+    prompt: string;
+    placeholder: string;
+    wrongPassword: string;
+    note: string;
+    open: string;
+    cancel: string;
+  };
+
   // DownloadsDialog component
   downloadsDialog: {
     open: string;
@@ -26,6 +26,18 @@ export interface AnalysisSettings {
   splitIncomeExpense: boolean;
   ipCrossReference: boolean;
   whoisLookup: boolean;
+  // Join accounts of the two files on a normalized key; exact match when unset
+  accountNormalizer?: AccountNormalizer;
+  // Zones the files' timestamps are written in ("Asia/Taipei", "UTC+8");
+  // times are compared in UTC when set
+  fileATimezone?: string;
+  fileBTimezone?: string;
+}
+
+export interface AccountNormalizer {
+  digits_only?: boolean;
+  pad_width?: number;
+  strip_prefixes?: string[];
 }
 
 export interface AnalysisResult {
@@ -34,6 +46,9 @@ export interface AnalysisResult {
   multiIpCount: number;
   whoisQueried: number;
   settings: AnalysisSettings;
+  // Declared time zones; when set, times were compared in UTC
+  fileATimezone?: string;
+  fileBTimezone?: string;
 }
 
 export interface LogEntry {
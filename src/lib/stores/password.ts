@@ -0,0 +1,73 @@
+/**
+ * Password Prompt Store
+ *
+ * Encrypted workbooks are opened by retrying the load with a password the
+ * user types into PasswordDialog. The password is only held in memory for
+ * the load (and, on the web, until the analysis reads the file).
+ */
+
+import { writable } from 'svelte/store';
+
+export interface PasswordRequest {
+  filename: string;
+  // The previous password was wrong
+  retry: boolean;
+}
+
+// Open request, or null when no dialog is shown
+const request = writable<PasswordRequest | null>(null);
+let answer: ((password: string | null) => void) | null = null;
+
+export const passwordRequest = { subscribe: request.subscribe };
+
+/**
+ * Ask for the password of an encrypted file; null when the user cancels
+ */
+export function requestPassword(filename: string, retry: boolean): Promise<string | null> {
+  answer?.(null);
+  request.set({ filename, retry });
+  return new Promise(resolve => {
+    answer = resolve;
+  });
+}
+
+/**
+ * Close the dialog with the typed password, or null to cancel
+ */
+export function answerPassword(password: string | null) {
+  const resolve = answer;
+  answer = null;
+  request.set(null);
+  resolve?.(password);
+}
+
+// Messages of CoreError::PasswordRequired and CoreError::WrongPassword
+export function passwordError(error: unknown): 'required' | 'wrong' | null {
+  const message = String(error instanceof Error ? error.message : error);
+  if (message.includes('Wrong password')) return 'wrong';
+  if (message.includes('password-protected')) return 'required';
+  return null;
+}
+
+/**
+ * Run a file load, asking for the password and retrying while the file is
+ * encrypted and no (or a wrong) password was given. Cancelling rejects with
+ * the load's own error.
+ */
+export async function withPassword<T>(
+  filename: string,
+  load: (password?: string) => Promise<T>,
+  password?: string,
+): Promise<T> {
+  for (;;) {
+    try {
+      return await load(password);
+    } catch (error) {
+      const reason = passwordError(error);
+      if (!reason) throw error;
+      const entered = await requestPassword(filename, reason === 'wrong');
+      if (entered === null) throw error;
+      password = entered;
+    }
+  }
+}
@@ -69,8 +69,21 @@ export interface PlatformAPI {
   // File Operations
   selectAndLoadFileA(): Promise<FileInfo>;
   selectAndLoadFileB(): Promise<FileInfo>;
-  loadFileA(path: string, mapping?: Record<string, string>): Promise<FileInfo>;
-  loadFileB(path: string, mapping?: Record<string, string>): Promise<FileInfo>;
+  // With append, the file is merged with those already loaded instead of
+  // replacing them. A password opens an encrypted workbook; without one (or
+  // with a wrong one) the user is asked for it.
+  loadFileA(
+    path: string,
+    mapping?: Record<string, string>,
+    append?: boolean,
+    password?: string,
+  ): Promise<FileInfo>;
+  loadFileB(
+    path: string,
+    mapping?: Record<string, string>,
+    append?: boolean,
+    password?: string,
+  ): Promise<FileInfo>;
   clearAllFiles(): Promise<void>;
 
   // Analysis
@@ -162,15 +175,17 @@ export async function selectAndLoadFileB(): Promise<FileInfo> {
 export async function loadFileA(
   path: string,
   mapping?: Record<string, string>,
+  password?: string,
 ): Promise<FileInfo> {
-  return getPlatform().loadFileA(path, mapping);
+  return getPlatform().loadFileA(path, mapping, undefined, password);
 }
 
 export async function loadFileB(
   path: string,
   mapping?: Record<string, string>,
+  password?: string,
 ): Promise<FileInfo> {
-  return getPlatform().loadFileB(path, mapping);
+  return getPlatform().loadFileB(path, mapping, undefined, password);
 }
 
 export async function clearAllFiles(): Promise<void> {
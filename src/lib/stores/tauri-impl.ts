@@ -9,12 +9,15 @@ import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import type { PlatformAPI, WhoisResult, BatchScanResult } from "./platform";
 import type {
+  AccountNormalizer,
   FileInfo,
   AnalysisSettings,
   AnalysisResult,
   ProgressInfo,
 } from "./app";
-import { addLog } from "./app";
+import { addLog, settings } from "./app";
+import { withPassword } from "./password";
+import { get } from "svelte/store";
 
 // ============================================
 // Tauri Response Types (snake_case from Rust)
@@ -26,6 +29,29 @@ interface TauriFileMetadata {
   row_count: number;
   column_count: number;
   file_type: string;
+  header_row?: number;
+  timezone?: string;
+  report?: TauriParseReport;
+  profile?: string;
+}
+
+interface TauriUnparsedCell {
+  sheet?: string;
+  row: number;
+  column: string;
+  value: string;
+}
+
+interface TauriParseReport {
+  rows_read: number;
+  skipped_rows: { sheet?: string; row: number; reason: string }[];
+  unparsed_timestamps: TauriUnparsedCell[];
+  unparsed_amounts: TauriUnparsedCell[];
+  unknown_directions?: TauriUnparsedCell[];
+  error_cells: TauriUnparsedCell[];
+  invalid_ips?: TauriUnparsedCell[];
+  duplicate_rows?: { sheet?: string; row: number; original: { file?: string; row: number } }[];
+  uncertain_rows?: { sheet?: string; row: number; reason: string; text: string }[];
 }
 
 interface TauriAnalysisResult {
@@ -38,7 +64,10 @@ interface TauriAnalysisResult {
     split_income_expense: boolean;
     ip_cross_reference: boolean;
     whois_lookup: boolean;
+    account_normalizer?: AccountNormalizer;
   };
+  file_a_timezone?: string;
+  file_b_timezone?: string;
 }
 
 interface TauriWhoisResult {
@@ -48,6 +77,69 @@ interface TauriWhoisResult {
   query_success: boolean;
 }
 
+// Parse options for a file load: the password and the declared time zone
+function loadOptions(password?: string, timezone?: string): Record<string, string> | undefined {
+  const options: Record<string, string> = {};
+  if (password) options.password = password;
+  if (timezone?.trim()) options.timezone = timezone.trim();
+  return Object.keys(options).length > 0 ? options : undefined;
+}
+
+function warnParseReport(result: TauriFileMetadata): void {
+  if (result.profile) {
+    addLog("info", `${result.filename}: using format profile "${result.profile}"`);
+  }
+  if (result.timezone) {
+    addLog("info", `${result.filename}: times in ${result.timezone}, compared in UTC`);
+  }
+
+  const report = result.report;
+  if (!report) return;
+
+  if (report.skipped_rows.length > 0) {
+    const first = report.skipped_rows[0];
+    addLog(
+      "warning",
+      `${result.filename}: ${report.skipped_rows.length} of ${report.rows_read} row(s) skipped (e.g. row ${first.row}: ${first.reason.replace(/_/g, " ")})`,
+    );
+  }
+
+  const cellGroups: [TauriUnparsedCell[], string][] = [
+    [report.unparsed_timestamps, "timestamp(s) could not be read"],
+    [report.unparsed_amounts, "amount cell(s) could not be read"],
+    [report.unknown_directions ?? [], "debit/credit marker(s) not recognized; the amount's sign was used"],
+    [report.error_cells, "cell(s) contain spreadsheet errors"],
+    [report.invalid_ips ?? [], "IP cell(s) hold text that is not an IP address"],
+  ];
+  for (const [cells, message] of cellGroups) {
+    if (cells.length > 0) {
+      const first = cells[0];
+      addLog(
+        "warning",
+        `${result.filename}: ${cells.length} ${message} (e.g. row ${first.row} ${first.column}: "${first.value}")`,
+      );
+    }
+  }
+
+  const duplicates = report.duplicate_rows ?? [];
+  if (duplicates.length > 0) {
+    const first = duplicates[0];
+    addLog(
+      "info",
+      `${result.filename}: ${duplicates.length} row(s) already loaded from another file were merged (e.g. row ${first.row} = ${first.original.file ?? "?"} row ${first.original.row})`,
+    );
+  }
+
+  const uncertain = report.uncertain_rows ?? [];
+  if (uncertain.length > 0) {
+    const first = uncertain[0];
+    addLog(
+      "warning",
+      `${result.filename}: ${uncertain.length} PDF row(s) may have misplaced columns (e.g. ${first.sheet ?? "?"} line ${first.row}: ${first.reason.replace(/_/g, " ")} "${first.text}")`,
+    );
+  }
+}
+
 // ============================================
 // Tauri Platform Implementation
 // ============================================
@@ -66,7 +158,7 @@ export class TauriPlatform implements PlatformAPI {
   async selectAndLoadFileA(): Promise<FileInfo> {
     const selected = await open({
       multiple: false,
-      filters: [{ name: "Excel", extensions: ["xlsx", "xls"] }],
+      filters: [{ name: "Statement", extensions: ["xlsx", "xls", "csv", "tsv", "txt", "json", "ndjson", "jsonl", "htm", "html", "xml", "pdf"] }],
       title: "Select Transaction File (File A)",
     });
 
@@ -74,10 +166,16 @@ export class TauriPlatform implements PlatformAPI {
       throw new Error("No file selected");
     }
 
-    addLog("info", `Loading File A: ${selected.split("/").pop()}`);
-    const result = await invoke<TauriFileMetadata>("load_file", {
-      path: selected,
-    });
+    const filename = selected.split(/[/\\]/).pop() ?? selected;
+    addLog("info", `Loading File A: ${filename}`);
+    const result = await withPassword(filename, (password) =>
+      invoke<TauriFileMetadata>("load_file", {
+        path: selected,
+        options: loadOptions(password, get(settings).fileATimezone),
+      }),
+    );
+
+    warnParseReport(result);
 
     const fileInfo: FileInfo = {
       path: result.path ?? selected,
@@ -98,12 +196,25 @@ export class TauriPlatform implements PlatformAPI {
   async loadFileA(
     path: string,
     mapping?: Record<string, string>,
+    append?: boolean,
+    password?: string,
   ): Promise<FileInfo> {
-    addLog("info", `Loading File A: ${path.split(/[/\\]/).pop()}`);
-    const result = await invoke<TauriFileMetadata>("load_file", {
-      path,
-      mapping,
-    });
+    const filename = path.split(/[/\\]/).pop() ?? path;
+    addLog("info", `Loading File A: ${filename}`);
+    // An encrypted workbook opened without its password asks for it
+    const result = await withPassword(
+      filename,
+      (password) =>
+        invoke<TauriFileMetadata>("load_file", {
+          path,
+          mapping,
+          append,
+          // Kept in memory only; the workbook is decrypted without a temp file
+          options: loadOptions(password, get(settings).fileATimezone),
+        }),
+      password,
+    );
+    warnParseReport(result);
 
     return {
       path: result.path ?? path,
@@ -118,7 +229,7 @@ export class TauriPlatform implements PlatformAPI {
   async selectAndLoadFileB(): Promise<FileInfo> {
     const selected = await open({
       multiple: false,
-      filters: [{ name: "Excel", extensions: ["xlsx", "xls"] }],
+      filters: [{ name: "IP Log", extensions: ["xlsx", "xls", "csv", "tsv", "txt", "json", "ndjson", "jsonl", "htm", "html", "xml", "pdf"] }],
       title: "Select IP Log File (File B)",
     });
 
@@ -132,12 +243,25 @@ export class TauriPlatform implements PlatformAPI {
   async loadFileB(
     path: string,
     mapping?: Record<string, string>,
+    append?: boolean,
+    password?: string,
   ): Promise<FileInfo> {
-    addLog("info", `Loading File B: ${path.split(/[/\\]/).pop()}`);
-    const result = await invoke<TauriFileMetadata>("load_ip_file", {
-      path,
-      mapping,
-    });
+    const filename = path.split(/[/\\]/).pop() ?? path;
+    addLog("info", `Loading File B: ${filename}`);
+    // An encrypted workbook opened without its password asks for it
+    const result = await withPassword(
+      filename,
+      (password) =>
+        invoke<TauriFileMetadata>("load_ip_file", {
+          path,
+          mapping,
+          append,
+          // Kept in memory only; the workbook is decrypted without a temp file
+          options: loadOptions(password, get(settings).fileBTimezone),
+        }),
+      password,
+    );
+    warnParseReport(result);
 
     return {
       path: result.path ?? path,
@@ -182,6 +306,7 @@ export class TauriPlatform implements PlatformAPI {
         splitIncomeExpense: settings.splitIncomeExpense,
         ipCrossReference: settings.ipCrossReference,
         whoisLookup: settings.whoisLookup,
+        accountNormalizer: settings.accountNormalizer ?? null,
       });
 
       const analysisResult: AnalysisResult = {
@@ -194,7 +319,10 @@ export class TauriPlatform implements PlatformAPI {
           splitIncomeExpense: result.settings.split_income_expense,
           ipCrossReference: result.settings.ip_cross_reference,
           whoisLookup: result.settings.whois_lookup,
+          accountNormalizer: result.settings.account_normalizer,
         },
+        fileATimezone: result.file_a_timezone,
+        fileBTimezone: result.file_b_timezone,
       };
 
       addLog(
@@ -234,7 +362,10 @@ export class TauriPlatform implements PlatformAPI {
     }
 
     addLog("info", `Exporting report to: ${outputPath.split("/").pop()}`);
-    const result = await invoke<string>("export_excel", { outputPath });
+    const result = await invoke<string>("export_excel", {
+      outputPath,
+      includeParseReport: true,
+    });
     addLog("success", result);
 
     return result;
@@ -13,6 +13,7 @@ import type {
   ProgressInfo,
 } from "./app";
 import { addLog } from "./app";
+import { passwordError, withPassword } from "./password";
 
 // ============================================
 // WASM Module Types (from bankflow-core)
@@ -29,6 +30,8 @@ interface LoadedFile {
   bytes: Uint8Array;
   name: string;
   info: FileInfo;
+  // Password of an encrypted workbook, kept in memory for the analysis
+  password?: string;
 }
 
 let fileAData: LoadedFile | null = null;
@@ -42,6 +45,10 @@ interface WasmAnalysisData {
   totalRecords: number;
   incomeCount: number;
   expenseCount: number;
+  // File metadata with parse reports, for the export's "Parse Report" sheet
+  files: unknown[];
+  // File A headers naming the raw columns in the export
+  rawHeaders: string[];
 }
 let lastAnalysisData: WasmAnalysisData | null = null;
 
@@ -121,11 +128,12 @@ export class WasmPlatform implements PlatformAPI {
   async selectAndLoadFileA(): Promise<FileInfo> {
     const file = await this.selectFile("Select Transaction File (File A)");
     const bytes = await this.readFileAsBytes(file);
+    const password = await this.unlockFile(bytes, file.name);
 
     // Parse file to get metadata (using WASM or basic parsing)
     const info = await this.parseFileMetadata(bytes, file.name, "A");
 
-    fileAData = { bytes, name: file.name, info };
+    fileAData = { bytes, name: file.name, info, password };
     addLog(
       "success",
       `File A loaded: ${info.filename} (${info.rowCount} rows)`,
@@ -146,10 +154,11 @@ export class WasmPlatform implements PlatformAPI {
   async selectAndLoadFileB(): Promise<FileInfo> {
     const file = await this.selectFile("Select IP Log File (File B)");
     const bytes = await this.readFileAsBytes(file);
+    const password = await this.unlockFile(bytes, file.name);
 
     const info = await this.parseFileMetadata(bytes, file.name, "B");
 
-    fileBData = { bytes, name: file.name, info };
+    fileBData = { bytes, name: file.name, info, password };
     addLog(
       "success",
       `File B loaded: ${info.filename} (${info.rowCount} rows)`,
@@ -207,7 +216,9 @@ export class WasmPlatform implements PlatformAPI {
       });
 
       // Call WASM analyze function with correct parameters
-      // Signature: analyze(file_a_bytes, file_a_name, file_b_bytes, file_b_name, hide_sensitive, ip_cross_reference)
+      // Signature: analyze(file_a_bytes, file_a_name, file_b_bytes, file_b_name, hide_sensitive, ip_cross_reference,
+      //                    file_a_timezone?, file_b_timezone?, account_normalizer?,
+      //                    file_a_password?, file_b_password?)
       // eslint-disable-next-line @typescript-eslint/no-explicit-any
       const analyzeFn = wasmModule.analyze as any;
       const result = analyzeFn(
@@ -217,6 +228,11 @@ export class WasmPlatform implements PlatformAPI {
         fileBData.name,
         settings.hideSensitive,
         settings.ipCrossReference,
+        settings.fileATimezone?.trim() || undefined,
+        settings.fileBTimezone?.trim() || undefined,
+        settings.accountNormalizer,
+        fileAData.password,
+        fileBData.password,
       ) as WasmAnalysisData;
 
       // WASM returns a JavaScript Map, convert to plain objects recursively
@@ -230,6 +246,10 @@ export class WasmPlatform implements PlatformAPI {
         totalRecords: (resultMap.get("totalRecords") || 0) as number,
         incomeCount: (resultMap.get("incomeCount") || 0) as number,
         expenseCount: (resultMap.get("expenseCount") || 0) as number,
+        files: [resultMap.get("fileA"), resultMap.get("fileB")]
+          .filter((meta) => meta !== undefined)
+          .map((meta) => mapToObject(meta)),
+        rawHeaders: (resultMap.get("rawHeaders") || []) as string[],
       };
 
       // Store for export
@@ -264,6 +284,8 @@ export class WasmPlatform implements PlatformAPI {
           ipCrossReference: settings.ipCrossReference,
           whoisLookup: false,
         },
+        fileATimezone: settings.fileATimezone?.trim() || undefined,
+        fileBTimezone: settings.fileBTimezone?.trim() || undefined,
       };
 
       addLog(
@@ -301,11 +323,13 @@ export class WasmPlatform implements PlatformAPI {
     addLog("info", "Generating Excel report...");
 
     try {
-      // export_excel expects JSON string with { transactions, income, expense }
+      // export_excel expects JSON string with { transactions, income, expense, rawHeaders, files }
       const exportData = JSON.stringify({
         transactions: lastAnalysisData.transactions,
         income: lastAnalysisData.income,
         expense: lastAnalysisData.expense,
+        rawHeaders: lastAnalysisData.rawHeaders,
+        files: lastAnalysisData.files,
       });
 
       // eslint-disable-next-line @typescript-eslint/no-explicit-any
@@ -343,7 +367,7 @@ export class WasmPlatform implements PlatformAPI {
     return new Promise((resolve, reject) => {
       const input = document.createElement("input");
       input.type = "file";
-      input.accept = ".xlsx,.xls";
+      input.accept = ".xlsx,.xls,.csv,.tsv,.txt,.json,.ndjson,.jsonl,.htm,.html,.xml,.pdf";
 
       input.onchange = () => {
         const file = input.files?.[0];
@@ -378,6 +402,25 @@ export class WasmPlatform implements PlatformAPI {
     });
   }
 
+  // Ask for the password of an encrypted workbook until it opens; any
+  // other problem with the file is reported when the analysis parses it
+  private async unlockFile(
+    bytes: Uint8Array,
+    filename: string,
+  ): Promise<string | undefined> {
+    if (!wasmModule) return undefined;
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const getFileInfo = wasmModule.get_file_info as any;
+    return withPassword(filename, async (password) => {
+      try {
+        getFileInfo(bytes, filename, password);
+      } catch (error) {
+        if (passwordError(error)) throw error;
+      }
+      return password;
+    });
+  }
+
   private async parseFileMetadata(
     bytes: Uint8Array,
     filename: string,